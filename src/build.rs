@@ -0,0 +1,112 @@
+//! Build script integration.
+//!
+//! A crate configured by Kconfig calls this from its `build.rs`, so that
+//! its code can test symbols with `#[cfg(FOO)]` or `cfg!(FOO = "value")`:
+//!
+//! ```no_run
+//! // build.rs
+//! rust_kbuild::build::BuildScript::new("Kconfig", ".")
+//!     .emit(".config")
+//!     .expect("invalid Kconfig");
+//! ```
+//!
+//! Symbols are named as in the module written by
+//! `ConfigGenerator::generate_config_rs`.
+
+use crate::config::generator::rust_identifiers;
+use crate::config::ConfigEngine;
+use crate::error::Result;
+use crate::kconfig::{Parser, SymbolType};
+use std::path::{Path, PathBuf};
+
+pub struct BuildScript {
+    kconfig_path: PathBuf,
+    srctree: PathBuf,
+}
+
+impl BuildScript {
+    pub fn new(kconfig_path: impl AsRef<Path>, srctree: impl AsRef<Path>) -> Self {
+        Self {
+            kconfig_path: kconfig_path.as_ref().to_path_buf(),
+            srctree: srctree.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The cargo directives for the configuration in `config_path`:
+    /// - `rerun-if-changed` for the `.config`, every Kconfig file and every
+    ///   directory a wildcard `source` lists
+    /// - `rerun-if-env-changed` for every environment variable the Kconfig
+    ///   macros read
+    /// - `rustc-check-cfg` for every symbol, so that misspelt names warn
+    /// - `rustc-cfg=FOO` for each enabled bool or tristate symbol, and
+    ///   `rustc-cfg=FOO="value"` for each int, hex or string symbol with a
+    ///   value
+    ///
+    /// Without a `.config`, every symbol takes its default.
+    pub fn directives(&self, config_path: impl AsRef<Path>) -> Result<Vec<String>> {
+        let config_path = config_path.as_ref();
+        let mut parser = Parser::new(&self.kconfig_path, &self.srctree)?;
+        let ast = parser.parse()?;
+
+        let mut engine = ConfigEngine::from_entries(&ast.entries);
+        if config_path.exists() {
+            engine.read_user_values(config_path)?;
+        }
+
+        let mut directives = vec![format!("cargo:rerun-if-changed={}", config_path.display())];
+        directives.extend(
+            parser
+                .files()
+                .iter()
+                .chain(parser.scanned_dirs())
+                .map(|path| format!("cargo:rerun-if-changed={}", path.display())),
+        );
+        directives.extend(
+            parser
+                .preprocessor()
+                .env_vars()
+                .map(|name| format!("cargo:rerun-if-env-changed={}", name)),
+        );
+
+        let mut checks = Vec::new();
+        let mut cfgs = Vec::new();
+        let identifiers = rust_identifiers(&ast.entries);
+        let resolver = engine.resolver();
+        for name in resolver.symbols() {
+            let Some(ident) = identifiers.get(name) else {
+                continue;
+            };
+            let value = engine.value(name);
+
+            match resolver.symbol_type(name) {
+                Some(SymbolType::Bool | SymbolType::Tristate) => {
+                    checks.push(format!("cargo:rustc-check-cfg=cfg({})", ident));
+                    if matches!(value.as_deref(), Some("y" | "m")) {
+                        cfgs.push(format!("cargo:rustc-cfg={}", ident));
+                    }
+                }
+                _ => {
+                    checks.push(format!(
+                        "cargo:rustc-check-cfg=cfg({}, values(any()))",
+                        ident
+                    ));
+                    if let Some(value) = value {
+                        cfgs.push(format!("cargo:rustc-cfg={}={:?}", ident, value));
+                    }
+                }
+            }
+        }
+
+        directives.extend(checks);
+        directives.extend(cfgs);
+        Ok(directives)
+    }
+
+    /// Print the directives for `config_path` to stdout, where cargo reads them
+    pub fn emit(&self, config_path: impl AsRef<Path>) -> Result<()> {
+        for directive in self.directives(config_path)? {
+            println!("{}", directive);
+        }
+        Ok(())
+    }
+}
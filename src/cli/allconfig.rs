@@ -0,0 +1,39 @@
+use crate::cli::output::write_outputs;
+use crate::config::{AllConfig, AllConfigMode};
+use crate::error::Result;
+use std::path::PathBuf;
+
+pub fn allconfig_command(
+    mode: AllConfigMode,
+    output: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    println!("Generating {}...", mode);
+    println!("Kconfig: {}", kconfig.display());
+
+    if let AllConfigMode::Random(config) = mode {
+        // Printed in the form accepted back through the environment
+        println!("KCONFIG_SEED=0x{:X}", config.seed);
+    }
+
+    let symbols = AllConfig::new(&kconfig, &srctree).generate(mode)?;
+
+    // Write .config, auto.conf and autoconf.h
+    write_outputs(&output, &symbols)?;
+
+    Ok(())
+}
+
+/// Parse a `KCONFIG_SEED` value, either decimal or `0x`-prefixed hex
+pub fn parse_seed(value: &str) -> std::result::Result<u64, String> {
+    let value = value.trim();
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("invalid seed '{}'", value))
+}
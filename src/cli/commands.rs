@@ -1,8 +1,15 @@
-use crate::config::{ConfigGenerator, ConfigReader};
-use crate::error::Result;
-use crate::kconfig::{Parser, SymbolTable};
+use crate::cli::allconfig::parse_seed;
+use crate::cli::fmt::FmtMode;
+use crate::cli::lint::{LintFormat, LintOverrides};
+use crate::cli::output::report_output;
+use crate::config::{
+    AllConfigMode, ConfigEngine, ConfigGenerator, ConfigReader, DefconfigLoader, Probability,
+    RandomConfig,
+};
+use crate::error::{KconfigError, Result};
+use crate::kconfig::{recursive_dependencies, render_diagnostics, KconfigFile, LintRule, Parser};
 use clap::{Parser as ClapParser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(ClapParser, Debug)]
 #[command(name = "rkconf")]
@@ -25,11 +32,15 @@ pub enum Commands {
         srctree: PathBuf,
     },
 
-    /// Apply a defconfig
+    /// Apply a defconfig on top of Kconfig defaults
     Defconfig {
         /// Path to defconfig file
         defconfig: PathBuf,
 
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
         /// Path to Kconfig file
         #[arg(short, long, default_value = "Kconfig")]
         kconfig: PathBuf,
@@ -63,6 +74,18 @@ pub enum Commands {
         /// Source tree path
         #[arg(short, long, default_value = ".")]
         srctree: PathBuf,
+
+        /// Also write the configuration as a Rust module of constants
+        #[arg(long, value_name = "PATH")]
+        rust: Option<PathBuf>,
+
+        /// Prefix for the macros of autoconf.h, such as CONFIG_
+        #[arg(long, default_value = "")]
+        prefix: String,
+
+        /// Also write the IS_ENABLED, IS_BUILTIN and IS_MODULE macros
+        #[arg(long, value_name = "PATH")]
+        kconfig_h: Option<PathBuf>,
     },
 
     /// Load an existing .config and detect changes (oldconfig)
@@ -79,7 +102,7 @@ pub enum Commands {
         #[arg(short, long, default_value = ".")]
         srctree: PathBuf,
 
-        /// Automatically apply defaults to new symbols
+        /// Report the defaults applied to new symbols (they always get them)
         #[arg(long)]
         auto_defaults: bool,
     },
@@ -98,14 +121,204 @@ pub enum Commands {
         #[arg(short, long, default_value = ".")]
         srctree: PathBuf,
     },
+
+    /// Save a minimal defconfig containing only non-default values
+    Savedefconfig {
+        /// Path to the full .config file
+        #[arg(short, long, default_value = ".config")]
+        config: PathBuf,
+
+        /// Output path for the defconfig
+        #[arg(short, long, default_value = "defconfig")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Disable every option (except those marked allnoconfig_y)
+    Allnoconfig {
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Enable every option
+    Allyesconfig {
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Build every tristate option as a module, enable every bool option
+    Allmodconfig {
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Set every option to its default
+    Alldefconfig {
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Set every option to a random value
+    Randconfig {
+        /// Output path for .config
+        #[arg(short, long, default_value = ".config")]
+        output: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+
+        /// Random seed, decimal or 0x-prefixed hex (random if unset)
+        #[arg(long, env = "KCONFIG_SEED", value_parser = parse_seed)]
+        seed: Option<u64>,
+
+        /// Percentage of enabled options: "y", "ty:tm" or "by:ty:tm"
+        #[arg(long, env = "KCONFIG_PROBABILITY")]
+        probability: Option<Probability>,
+    },
+
+    /// Print Kconfig files in the canonical layout, or check or rewrite them
+    Fmt {
+        /// Files to format (default: every file of the Kconfig tree)
+        files: Vec<PathBuf>,
+
+        /// List files that are not formatted and fail if there are any
+        #[arg(long, conflicts_with = "in_place")]
+        check: bool,
+
+        /// Rewrite the files instead of printing them
+        #[arg(short, long)]
+        in_place: bool,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Check Kconfig files for likely mistakes
+    Lint {
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "human")]
+        format: LintFormat,
+
+        /// File of `rule = allow|warn|deny` lines
+        #[arg(long)]
+        rules: Option<PathBuf>,
+
+        /// Turn a rule off (name or code, repeatable)
+        #[arg(long, value_name = "RULE")]
+        allow: Vec<LintRule>,
+
+        /// Report a rule as a warning
+        #[arg(long, value_name = "RULE")]
+        warn: Vec<LintRule>,
+
+        /// Report a rule as an error, failing the run
+        #[arg(long, value_name = "RULE")]
+        deny: Vec<LintRule>,
+    },
+
+    /// Explain why a symbol has its value
+    Explain {
+        /// Symbol name, with or without the CONFIG_ prefix
+        symbol: String,
+
+        /// Path to .config file (defaults only if missing)
+        #[arg(short, long, default_value = ".config")]
+        config: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
+
+    /// Find a minimal set of other changes that let a symbol take a value
+    Solve {
+        /// NAME=VALUE, or NAME to enable it
+        assignment: String,
+
+        /// Path to .config file (defaults only if missing)
+        #[arg(short, long, default_value = ".config")]
+        config: PathBuf,
+
+        /// Path to Kconfig file
+        #[arg(short, long, default_value = "Kconfig")]
+        kconfig: PathBuf,
+
+        /// Source tree path
+        #[arg(short, long, default_value = ".")]
+        srctree: PathBuf,
+    },
 }
 
 pub fn parse_command(kconfig: PathBuf, srctree: PathBuf) -> Result<()> {
     println!("Parsing Kconfig file: {}", kconfig.display());
     println!("Source tree: {}", srctree.display());
 
-    let mut parser = Parser::new(&kconfig, &srctree)?;
-    let ast = parser.parse()?;
+    let ast = parse_kconfig(&kconfig, &srctree)?;
 
     println!("\nParsed {} entries", ast.entries.len());
     println!("\nAST:");
@@ -116,58 +329,227 @@ pub fn parse_command(kconfig: PathBuf, srctree: PathBuf) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_command(config: PathBuf, kconfig: PathBuf, srctree: PathBuf) -> Result<()> {
+/// Parse a Kconfig tree, printing every error and warning to stderr with
+/// its source line
+pub fn parse_kconfig(kconfig: &Path, srctree: &Path) -> Result<KconfigFile> {
+    parse_kconfig_files(kconfig, srctree).map(|(ast, _)| ast)
+}
+
+/// Like `parse_kconfig`, also returning every file of the tree
+pub fn parse_kconfig_files(kconfig: &Path, srctree: &Path) -> Result<(KconfigFile, Vec<PathBuf>)> {
+    let mut parser = Parser::new(kconfig, srctree)?;
+    let result = parser.parse().map(|ast| (ast, parser.files()));
+
+    // Recursive dependencies are reported, but the tree still loads
+    let mut diagnostics = parser.diagnostics().to_vec();
+    if let Ok((ast, _)) = &result {
+        diagnostics.extend(recursive_dependencies(&ast.entries));
+    }
+    if !diagnostics.is_empty() {
+        eprint!("{}", render_diagnostics(&diagnostics));
+    }
+
+    result.map_err(
+        |error| match diagnostics.iter().filter(|d| d.is_error()).count() {
+            0 => error,
+            errors => KconfigError::Parse(format!(
+                "{} has {} error{}",
+                kconfig.display(),
+                errors,
+                if errors == 1 { "" } else { "s" }
+            )),
+        },
+    )
+}
+
+pub fn generate_command(
+    config: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+    rust: Option<PathBuf>,
+    prefix: String,
+    kconfig_h: Option<PathBuf>,
+) -> Result<()> {
     println!("Generating configuration files...");
     println!("Config: {}", config.display());
     println!("Kconfig: {}", kconfig.display());
 
     // Parse Kconfig
-    let mut parser = Parser::new(&kconfig, &srctree)?;
-    let _ast = parser.parse()?;
-
-    // Read .config
-    let config_values = ConfigReader::read(&config)?;
-
-    // Build symbol table
-    let mut symbols = SymbolTable::new();
-    for (name, value) in config_values {
-        // Extract symbol type from name (simplified)
-        symbols.add_symbol(name.clone(), crate::kconfig::SymbolType::Bool);
-        symbols.set_value(&name, value);
+    let ast = parse_kconfig(&kconfig, &srctree)?;
+
+    // Evaluate .config like every other command, so that the outputs keep
+    // choices exclusive, respect dependencies and clamp values to ranges
+    let mut engine = ConfigEngine::from_entries(&ast.entries);
+    let assignments = ConfigReader::read_assignments(&config)?;
+    let warnings = DefconfigLoader::apply_assignments(&mut engine, &assignments);
+    if !warnings.is_empty() {
+        println!();
+        println!("⚠️  Some .config values could not be applied:");
+        for warning in &warnings {
+            println!("  {}", warning);
+        }
+        println!();
     }
+    let symbols = engine.symbols();
 
     // Generate auto.conf
-    ConfigGenerator::generate_auto_conf("auto.conf", &symbols)?;
-    println!("Generated auto.conf");
+    let updated = ConfigGenerator::generate_auto_conf("auto.conf", symbols)?;
+    report_output(Path::new("auto.conf"), updated);
 
     // Generate autoconf.h
-    ConfigGenerator::generate_autoconf_h("autoconf.h", &symbols)?;
-    println!("Generated autoconf.h");
+    let updated = ConfigGenerator::generate_autoconf_h_with_prefix("autoconf.h", symbols, &prefix)?;
+    report_output(Path::new("autoconf.h"), updated);
+
+    if let Some(kconfig_h) = kconfig_h {
+        report_output(&kconfig_h, ConfigGenerator::generate_kconfig_h(&kconfig_h)?);
+    }
+
+    if let Some(rust) = rust {
+        report_output(
+            &rust,
+            ConfigGenerator::generate_config_rs(&rust, symbols, &ast.entries)?,
+        );
+    }
 
     Ok(())
 }
 
 pub fn run_cli() -> Result<()> {
     let cli = Cli::parse();
-    
+
     match cli.command {
-        Commands::Parse { kconfig, srctree } => {
-            parse_command(kconfig, srctree)
-        }
-        Commands::Defconfig { defconfig, kconfig, srctree } => {
-            crate::cli::defconfig::defconfig_command(defconfig, kconfig, srctree)
-        }
+        Commands::Parse { kconfig, srctree } => parse_command(kconfig, srctree),
+        Commands::Defconfig {
+            defconfig,
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::defconfig::defconfig_command(defconfig, output, kconfig, srctree),
         Commands::Menuconfig { kconfig, srctree } => {
             crate::cli::menuconfig::menuconfig_command(kconfig, srctree)
         }
-        Commands::Generate { config, kconfig, srctree } => {
-            generate_command(config, kconfig, srctree)
+        Commands::Generate {
+            config,
+            kconfig,
+            srctree,
+            rust,
+            prefix,
+            kconfig_h,
+        } => generate_command(config, kconfig, srctree, rust, prefix, kconfig_h),
+        Commands::Oldconfig {
+            config,
+            kconfig,
+            srctree,
+            auto_defaults,
+        } => crate::cli::oldconfig::oldconfig_command(config, kconfig, srctree, auto_defaults),
+        Commands::Saveconfig {
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::saveconfig::saveconfig_command(output, kconfig, srctree),
+        Commands::Allnoconfig {
+            output,
+            kconfig,
+            srctree,
+        } => {
+            crate::cli::allconfig::allconfig_command(AllConfigMode::AllNo, output, kconfig, srctree)
+        }
+        Commands::Allyesconfig {
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::allconfig::allconfig_command(
+            AllConfigMode::AllYes,
+            output,
+            kconfig,
+            srctree,
+        ),
+        Commands::Allmodconfig {
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::allconfig::allconfig_command(
+            AllConfigMode::AllMod,
+            output,
+            kconfig,
+            srctree,
+        ),
+        Commands::Alldefconfig {
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::allconfig::allconfig_command(
+            AllConfigMode::AllDef,
+            output,
+            kconfig,
+            srctree,
+        ),
+        Commands::Randconfig {
+            output,
+            kconfig,
+            srctree,
+            seed,
+            probability,
+        } => {
+            let config = RandomConfig::new(
+                seed.unwrap_or_else(RandomConfig::random_seed),
+                probability.unwrap_or_default(),
+            );
+            crate::cli::allconfig::allconfig_command(
+                AllConfigMode::Random(config),
+                output,
+                kconfig,
+                srctree,
+            )
         }
-        Commands::Oldconfig { config, kconfig, srctree, auto_defaults } => {
-            crate::cli::oldconfig::oldconfig_command(config, kconfig, srctree, auto_defaults)
+        Commands::Savedefconfig {
+            config,
+            output,
+            kconfig,
+            srctree,
+        } => crate::cli::savedefconfig::savedefconfig_command(config, output, kconfig, srctree),
+        Commands::Fmt {
+            files,
+            check,
+            in_place,
+            kconfig,
+            srctree,
+        } => {
+            let mode = match (check, in_place) {
+                (true, _) => FmtMode::Check,
+                (_, true) => FmtMode::InPlace,
+                _ => FmtMode::Print,
+            };
+            crate::cli::fmt::fmt_command(files, mode, kconfig, srctree)
         }
-        Commands::Saveconfig { output, kconfig, srctree } => {
-            crate::cli::saveconfig::saveconfig_command(output, kconfig, srctree)
+        Commands::Lint {
+            kconfig,
+            srctree,
+            format,
+            rules,
+            allow,
+            warn,
+            deny,
+        } => {
+            let overrides = LintOverrides {
+                rules_file: rules,
+                allow,
+                warn,
+                deny,
+            };
+            crate::cli::lint::lint_command(kconfig, srctree, format, overrides)
         }
+        Commands::Explain {
+            symbol,
+            config,
+            kconfig,
+            srctree,
+        } => crate::cli::explain::explain_command(symbol, config, kconfig, srctree),
+        Commands::Solve {
+            assignment,
+            config,
+            kconfig,
+            srctree,
+        } => crate::cli::solve::solve_command(assignment, config, kconfig, srctree),
     }
 }
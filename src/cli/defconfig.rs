@@ -1,7 +1,32 @@
+use crate::cli::output::write_outputs;
+use crate::config::DefconfigLoader;
 use crate::error::Result;
 use std::path::PathBuf;
 
-pub fn defconfig_command(_defconfig: PathBuf, _kconfig: PathBuf, _srctree: PathBuf) -> Result<()> {
-    println!("Defconfig command not yet implemented");
+pub fn defconfig_command(
+    defconfig: PathBuf,
+    output: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    println!("Applying defconfig...");
+    println!("Defconfig: {}", defconfig.display());
+    println!("Kconfig: {}", kconfig.display());
+
+    let loader = DefconfigLoader::new(&kconfig, &srctree);
+    let (symbols, warnings) = loader.load(&defconfig)?;
+
+    if !warnings.is_empty() {
+        println!();
+        println!("⚠️  Some defconfig values could not be applied:");
+        for warning in &warnings {
+            println!("  {}", warning);
+        }
+        println!();
+    }
+
+    // Write .config, auto.conf and autoconf.h
+    write_outputs(&output, &symbols)?;
+
     Ok(())
 }
@@ -0,0 +1,27 @@
+use crate::config::ConfigEngine;
+use crate::error::{KconfigError, Result};
+use std::path::PathBuf;
+
+pub fn explain_command(
+    symbol: String,
+    config: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    let mut engine = ConfigEngine::load(&kconfig, &srctree)?;
+    // Without a .config, every symbol has its default
+    if config.exists() {
+        engine.read_user_values(&config)?;
+    }
+
+    let name = match symbol.strip_prefix("CONFIG_") {
+        Some(stripped) if engine.resolver().symbol_type(&symbol).is_none() => stripped,
+        _ => &symbol,
+    };
+    let explanation = engine
+        .explain(name)
+        .ok_or_else(|| KconfigError::UndefinedSymbol(symbol.clone()))?;
+
+    print!("{}", explanation);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::cli::commands::parse_kconfig_files;
+use crate::error::{KconfigError, Result};
+use crate::kconfig::{format, SyntaxTree};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FmtMode {
+    /// Print the formatted files to stdout
+    Print,
+    /// Only report the files that are not formatted
+    Check,
+    /// Rewrite the files that are not formatted
+    InPlace,
+}
+
+pub fn fmt_command(
+    files: Vec<PathBuf>,
+    mode: FmtMode,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    // Formatting a tree that does not parse could hide its errors
+    let files = if files.is_empty() {
+        parse_kconfig_files(&kconfig, &srctree)?.1
+    } else {
+        files
+    };
+
+    let changed = format_files(&files, mode)?;
+    match mode {
+        FmtMode::Print => {}
+        FmtMode::Check => {
+            for file in &changed {
+                println!("{}", file.display());
+            }
+            if !changed.is_empty() {
+                return Err(KconfigError::Config(format!(
+                    "{} of {} file(s) not formatted",
+                    changed.len(),
+                    files.len()
+                )));
+            }
+        }
+        FmtMode::InPlace => {
+            for file in &changed {
+                println!("✅ Formatted {}", file.display());
+            }
+            println!("{} of {} file(s) changed", changed.len(), files.len());
+        }
+    }
+    Ok(())
+}
+
+/// Format `files` according to `mode`
+/// Returns: the files whose layout was not canonical
+pub fn format_files(files: &[PathBuf], mode: FmtMode) -> Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+
+    for file in files {
+        let (source, formatted) = format_file(file)?;
+        if mode == FmtMode::Print {
+            print!("{}", formatted);
+        }
+        if formatted != source {
+            if mode == FmtMode::InPlace {
+                fs::write(file, &formatted)?;
+            }
+            changed.push(file.clone());
+        }
+    }
+    Ok(changed)
+}
+
+/// The content of `path` and its canonical layout
+fn format_file(path: &Path) -> Result<(String, String)> {
+    let source = fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => KconfigError::FileNotFound(path.to_path_buf()),
+        _ => KconfigError::Io(e),
+    })?;
+    let formatted = format(&SyntaxTree::parse(&source, path));
+    Ok((source, formatted))
+}
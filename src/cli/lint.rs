@@ -0,0 +1,120 @@
+use crate::error::{KconfigError, Result};
+use crate::kconfig::{
+    lint, recursive_dependencies, render_diagnostics, Diagnostic, DiagnosticCode, LintConfig,
+    LintLevel, LintRule, Parser,
+};
+use clap::ValueEnum;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LintFormat {
+    /// Compiler-style messages with source lines
+    Human,
+    /// One JSON document, for CI
+    Json,
+}
+
+/// Rule levels from the command line, applied after the rules file
+#[derive(Debug, Clone, Default)]
+pub struct LintOverrides {
+    pub rules_file: Option<PathBuf>,
+    pub allow: Vec<LintRule>,
+    pub warn: Vec<LintRule>,
+    pub deny: Vec<LintRule>,
+}
+
+impl LintOverrides {
+    pub fn config(&self) -> Result<LintConfig> {
+        let mut config = LintConfig::new();
+        if let Some(path) = &self.rules_file {
+            config.apply_rules_file(path)?;
+        }
+        let levels = [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ];
+        for (rules, level) in levels {
+            for rule in rules {
+                config.set(*rule, level);
+            }
+        }
+        Ok(config)
+    }
+}
+
+pub fn lint_command(
+    kconfig: PathBuf,
+    srctree: PathBuf,
+    format: LintFormat,
+    overrides: LintOverrides,
+) -> Result<()> {
+    let config = overrides.config()?;
+
+    let mut parser = Parser::new(&kconfig, &srctree)?;
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(error) => {
+            eprint!("{}", render_diagnostics(parser.diagnostics()));
+            return Err(error);
+        }
+    };
+
+    // Parser warnings come first, then recursive dependencies, then the
+    // findings in source order
+    let mut diagnostics = parser.diagnostics().to_vec();
+    diagnostics.extend(recursive_dependencies(&ast.entries));
+    diagnostics.extend(lint(&ast, &config));
+
+    match format {
+        LintFormat::Human if diagnostics.is_empty() => println!("✅ No problems found"),
+        LintFormat::Human => print!("{}", render_diagnostics(&diagnostics)),
+        LintFormat::Json => println!("{}", to_json(&diagnostics)),
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        return Err(KconfigError::Config(format!(
+            "lint found {} error{}",
+            errors,
+            if errors == 1 { "" } else { "s" }
+        )));
+    }
+    Ok(())
+}
+
+/// Diagnostics as a JSON document:
+///
+/// ```json
+/// {"diagnostics": [{"code": "L0001", "rule": "undefined-symbol", "severity": "warning",
+///   "message": "...", "file": "Kconfig", "line": 3, "column": 13, "notes": []}], "errors": 0, "warnings": 1}
+/// ```
+pub fn to_json(diagnostics: &[Diagnostic]) -> String {
+    let entries: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|d| {
+            let rule = match d.code {
+                DiagnosticCode::Lint(rule) => Some(rule.name()),
+                _ => None,
+            };
+            serde_json::json!({
+                "code": d.code.as_str(),
+                "rule": rule,
+                "severity": d.severity.to_string(),
+                "message": d.message,
+                "file": d.span.file.display().to_string(),
+                "line": d.span.line,
+                "column": d.span.column,
+                "notes": d.notes,
+            })
+        })
+        .collect();
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+
+    serde_json::json!({
+        "diagnostics": entries,
+        "errors": errors,
+        "warnings": diagnostics.len() - errors,
+    })
+    .to_string()
+}
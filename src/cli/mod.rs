@@ -1,11 +1,25 @@
+pub mod allconfig;
 pub mod commands;
 pub mod defconfig;
+pub mod explain;
+pub mod fmt;
+pub mod lint;
 pub mod menuconfig;
 pub mod oldconfig;
+pub mod output;
 pub mod saveconfig;
+pub mod savedefconfig;
+pub mod solve;
 
+pub use allconfig::*;
 pub use commands::*;
-pub use oldconfig::*;
-pub use saveconfig::*;
 pub use defconfig::*;
+pub use explain::*;
+pub use fmt::*;
+pub use lint::*;
 pub use menuconfig::*;
+pub use oldconfig::*;
+pub use output::*;
+pub use saveconfig::*;
+pub use savedefconfig::*;
+pub use solve::*;
@@ -11,11 +11,11 @@ pub fn oldconfig_command(
     println!("Loading existing configuration...");
     println!("Config: {}", config.display());
     println!("Kconfig: {}", kconfig.display());
-    
+
     // Load and merge old config with current Kconfig
     let loader = OldConfigLoader::new(&kconfig, &srctree);
-    let (mut symbols, changes) = loader.load_and_merge(&config)?;
-    
+    let (symbols, changes) = loader.load_and_merge(&config)?;
+
     // Print summary of changes
     if changes.has_changes() {
         println!();
@@ -23,37 +23,26 @@ pub fn oldconfig_command(
     } else {
         println!("✅ No configuration changes detected.");
     }
-    
-    // Apply default values to new symbols if requested
-    if auto_defaults {
-        println!("\nApplying default values to new symbols...");
-        // Collect names first to avoid borrow checker issues
-        let new_symbol_names: Vec<String> = symbols
-            .get_new_symbols()
-            .iter()
-            .map(|s| s.name.clone())
-            .collect();
-        
-        for name in new_symbol_names {
-            if let Some(symbol) = symbols.get_symbol(&name) {
-                if symbol.value.is_none() {
-                    let symbol_type = symbol.symbol_type.clone();
-                    match symbol_type {
-                        crate::kconfig::ast::SymbolType::Bool | 
-                        crate::kconfig::ast::SymbolType::Tristate => {
-                            symbols.set_value(&name, "n".to_string());
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
+
+    // New symbols already carry their Kconfig defaults
+    if auto_defaults && !changes.new_symbols.is_empty() {
+        println!(
+            "\nApplied default values to {} new symbol(s).",
+            changes.new_symbols.len()
+        );
     }
-    
+
     // Save updated configuration
     println!("\nSaving configuration to {}...", config.display());
-    ConfigWriter::write(&config, &symbols)?;
-    println!("✅ Configuration saved successfully.");
-    
+    if ConfigWriter::write(&config, &symbols)? {
+        println!("✅ Configuration saved successfully.");
+        println!(
+            "   Previous configuration kept in {}",
+            ConfigWriter::backup_path(&config).display()
+        );
+    } else {
+        println!("➖ {} is unchanged", config.display());
+    }
+
     Ok(())
 }
@@ -0,0 +1,41 @@
+use crate::config::{ConfigGenerator, ConfigWriter};
+use crate::error::Result;
+use crate::kconfig::SymbolTable;
+use std::path::Path;
+
+/// Write the .config at `output`, and `auto.conf` and `autoconf.h` beside
+/// it, reporting which of them changed
+pub fn write_outputs(output: &Path, symbols: &SymbolTable) -> Result<()> {
+    let dir = output.parent().unwrap_or(Path::new("."));
+    let auto_conf = dir.join("auto.conf");
+    let autoconf_h = dir.join("autoconf.h");
+
+    let replaced = output.exists();
+    let config_updated = ConfigWriter::write(output, symbols)?;
+    report_output(output, config_updated);
+    if config_updated && replaced {
+        println!(
+            "   Previous configuration kept in {}",
+            ConfigWriter::backup_path(output).display()
+        );
+    }
+    report_output(
+        &auto_conf,
+        ConfigGenerator::generate_auto_conf(&auto_conf, symbols)?,
+    );
+    report_output(
+        &autoconf_h,
+        ConfigGenerator::generate_autoconf_h(&autoconf_h, symbols)?,
+    );
+
+    Ok(())
+}
+
+/// Print whether `path` was written or already up to date
+pub fn report_output(path: &Path, updated: bool) {
+    if updated {
+        println!("✅ Updated {}", path.display());
+    } else {
+        println!("➖ {} is unchanged", path.display());
+    }
+}
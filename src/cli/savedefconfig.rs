@@ -0,0 +1,31 @@
+use crate::cli::output::report_output;
+use crate::config::{ConfigWriter, DefconfigLoader};
+use crate::error::Result;
+use std::path::PathBuf;
+
+pub fn savedefconfig_command(
+    config: PathBuf,
+    output: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    println!("Saving minimal configuration...");
+    println!("Config: {}", config.display());
+    println!("Kconfig: {}", kconfig.display());
+
+    let loader = DefconfigLoader::new(&kconfig, &srctree);
+    let engine = loader.load_config(&config)?;
+    let assignments = DefconfigLoader::minimize(&engine);
+
+    if ConfigWriter::write_defconfig(&output, &assignments, engine.symbols())? {
+        println!(
+            "✅ Saved {} non-default option(s) to {}",
+            assignments.len(),
+            output.display()
+        );
+    } else {
+        report_output(&output, false);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::config::ConfigEngine;
+use crate::error::{KconfigError, Result};
+use std::path::PathBuf;
+
+/// Print the other values `assignment` (`NAME=VALUE`, or `NAME` for `y`)
+/// needs, failing if there are none that work
+pub fn solve_command(
+    assignment: String,
+    config: PathBuf,
+    kconfig: PathBuf,
+    srctree: PathBuf,
+) -> Result<()> {
+    let mut engine = ConfigEngine::load(&kconfig, &srctree)?;
+    // Without a .config, start from the defaults
+    if config.exists() {
+        engine.read_user_values(&config)?;
+    }
+
+    let (symbol, value) = assignment.split_once('=').unwrap_or((&assignment, "y"));
+    let name = match symbol.strip_prefix("CONFIG_") {
+        Some(stripped) if engine.resolver().symbol_type(symbol).is_none() => stripped,
+        _ => symbol,
+    };
+    if engine.resolver().symbol_type(name).is_none() {
+        return Err(KconfigError::UndefinedSymbol(symbol.to_string()));
+    }
+
+    let solution = engine.solve(name, value).ok_or_else(|| {
+        let reason = engine
+            .check_value(name, value)
+            .unwrap_or_else(|| "no set of changes found".to_string());
+        KconfigError::Config(format!("cannot set {}={}: {}", name, value, reason))
+    })?;
+
+    print!("{}", solution);
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use crate::config::{ConfigEngine, DefconfigLoader};
+use crate::error::{KconfigError, Result};
+use crate::kconfig::{SymbolTable, SymbolType};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The configuration an all*config target produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllConfigMode {
+    /// Everything disabled, except options marked `option allnoconfig_y`
+    AllNo,
+    /// Everything enabled
+    AllYes,
+    /// Tristate options as modules, bool options enabled
+    AllMod,
+    /// Every option at its default
+    AllDef,
+    /// Random values, reproducible from the seed
+    Random(RandomConfig),
+}
+
+impl fmt::Display for AllConfigMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AllConfigMode::AllNo => "allnoconfig",
+            AllConfigMode::AllYes => "allyesconfig",
+            AllConfigMode::AllMod => "allmodconfig",
+            AllConfigMode::AllDef => "alldefconfig",
+            AllConfigMode::Random(_) => "randconfig",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomConfig {
+    pub seed: u64,
+    pub probability: Probability,
+}
+
+impl RandomConfig {
+    pub fn new(seed: u64, probability: Probability) -> Self {
+        Self { seed, probability }
+    }
+
+    /// A seed derived from the clock, for when none is given
+    pub fn random_seed() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        SplitMix64::new(nanos ^ u64::from(std::process::id())).next_u64()
+    }
+}
+
+/// Percentages used by randconfig, in the `KCONFIG_PROBABILITY` format:
+///
+/// - `p`: bool options are `y` with probability p, tristate options are
+///   split evenly between `y` and `m`
+/// - `y:m`: tristate probabilities for `y` and `m`, bool uses their sum
+/// - `by:ty:tm`: bool `y`, tristate `y` and tristate `m` separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Probability {
+    pub bool_y: u32,
+    pub tristate_y: u32,
+    pub tristate_m: u32,
+}
+
+impl Default for Probability {
+    fn default() -> Self {
+        Self {
+            bool_y: 50,
+            tristate_y: 33,
+            tristate_m: 33,
+        }
+    }
+}
+
+impl FromStr for Probability {
+    type Err = KconfigError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s
+            .split(':')
+            .map(|p| match p.trim().parse::<u32>() {
+                Ok(n) if n <= 100 => Ok(n),
+                _ => Err(KconfigError::Config(format!(
+                    "Invalid probability '{}': expected a percentage between 0 and 100",
+                    p
+                ))),
+            })
+            .collect::<Result<Vec<u32>>>()?;
+
+        let probability = match parts.as_slice() {
+            [p] => Self {
+                bool_y: *p,
+                tristate_y: p - p / 2,
+                tristate_m: p / 2,
+            },
+            [y, m] => Self {
+                bool_y: (y + m).min(100),
+                tristate_y: *y,
+                tristate_m: *m,
+            },
+            [by, ty, tm] => Self {
+                bool_y: *by,
+                tristate_y: *ty,
+                tristate_m: *tm,
+            },
+            _ => {
+                return Err(KconfigError::Config(format!(
+                    "Invalid probability '{}': expected at most three values",
+                    s
+                )))
+            }
+        };
+
+        if probability.tristate_y + probability.tristate_m > 100 {
+            return Err(KconfigError::Config(format!(
+                "Invalid probability '{}': tristate y and m add up to more than 100",
+                s
+            )));
+        }
+
+        Ok(probability)
+    }
+}
+
+pub struct AllConfig {
+    kconfig_path: String,
+    srctree: String,
+}
+
+impl AllConfig {
+    pub fn new(kconfig_path: impl AsRef<Path>, srctree: impl AsRef<Path>) -> Self {
+        Self {
+            kconfig_path: kconfig_path.as_ref().to_string_lossy().to_string(),
+            srctree: srctree.as_ref().to_string_lossy().to_string(),
+        }
+    }
+
+    pub fn allnoconfig(&self) -> Result<SymbolTable> {
+        self.generate(AllConfigMode::AllNo)
+    }
+
+    pub fn allyesconfig(&self) -> Result<SymbolTable> {
+        self.generate(AllConfigMode::AllYes)
+    }
+
+    pub fn allmodconfig(&self) -> Result<SymbolTable> {
+        self.generate(AllConfigMode::AllMod)
+    }
+
+    pub fn alldefconfig(&self) -> Result<SymbolTable> {
+        self.generate(AllConfigMode::AllDef)
+    }
+
+    pub fn randconfig(&self, config: RandomConfig) -> Result<SymbolTable> {
+        self.generate(AllConfigMode::Random(config))
+    }
+
+    /// Parse the Kconfig and build the configuration for `mode`
+    pub fn generate(&self, mode: AllConfigMode) -> Result<SymbolTable> {
+        let mut engine = ConfigEngine::load(&self.kconfig_path, &self.srctree)?;
+        Self::apply(&mut engine, mode);
+
+        Ok(engine.into_symbols())
+    }
+
+    /// Request a value for every bool and tristate option and apply them
+    /// like a defconfig, so dependencies, selects and choices still hold.
+    /// Requests that conflict with those constraints are dropped silently.
+    pub fn apply(engine: &mut ConfigEngine, mode: AllConfigMode) {
+        let resolver = engine.resolver();
+        let mut rng = match mode {
+            AllConfigMode::Random(config) => {
+                Some((SplitMix64::new(config.seed), config.probability))
+            }
+            _ => None,
+        };
+        let mut values = HashMap::new();
+
+        for name in resolver.symbols() {
+            // Choices pick their default option unless set below
+            if resolver.choice_group(name).is_some() || resolver.is_choice(name) {
+                continue;
+            }
+
+            let symbol_type = match resolver.symbol_type(name) {
+                Some(t @ (SymbolType::Bool | SymbolType::Tristate)) => t,
+                _ => continue,
+            };
+            let is_tristate = *symbol_type == SymbolType::Tristate;
+
+            let value = match mode {
+                AllConfigMode::AllDef => continue,
+                AllConfigMode::AllNo if resolver.is_allnoconfig_y(name) => "y",
+                AllConfigMode::AllNo => "n",
+                AllConfigMode::AllYes => "y",
+                AllConfigMode::AllMod if is_tristate => "m",
+                AllConfigMode::AllMod => "y",
+                AllConfigMode::Random(_) => {
+                    let (rng, probability) = rng.as_mut().expect("randconfig without generator");
+                    let roll = rng.below(100) as u32;
+                    if !is_tristate {
+                        if roll < probability.bool_y {
+                            "y"
+                        } else {
+                            "n"
+                        }
+                    } else if roll < probability.tristate_y {
+                        "y"
+                    } else if roll < probability.tristate_y + probability.tristate_m {
+                        "m"
+                    } else {
+                        "n"
+                    }
+                }
+            };
+            values.insert(name.clone(), value.to_string());
+        }
+
+        // Tristate choices go to `m` mode, with every option a module
+        if mode == AllConfigMode::AllMod {
+            for group in resolver.choice_groups() {
+                if group.symbol_type == SymbolType::Tristate {
+                    values.extend(group.options.iter().map(|o| (o.clone(), "m".to_string())));
+                }
+            }
+        }
+
+        if let Some((rng, _)) = rng.as_mut() {
+            for group in resolver.choice_groups() {
+                if group.options.is_empty() {
+                    continue;
+                }
+                let pick = rng.below(group.options.len() as u64) as usize;
+                values.insert(group.options[pick].clone(), "y".to_string());
+            }
+        }
+
+        DefconfigLoader::apply(engine, values);
+    }
+}
+
+/// Small deterministic generator, so a seed reproduces the same randconfig
+/// on every platform and release
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
@@ -0,0 +1,258 @@
+use crate::config::{ConfigAssignment, ConfigEngine, ConfigReader};
+use crate::error::Result;
+use crate::kconfig::dependency_resolver::DependencyResolver;
+use crate::kconfig::{normalize_value, SymbolTable, SymbolType, Tristate};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+pub struct DefconfigLoader {
+    kconfig_path: String,
+    srctree: String,
+}
+
+/// A defconfig or .config assignment that could not be applied as written
+#[derive(Debug, Clone)]
+pub struct DefconfigWarning {
+    pub symbol: String,
+    pub value: String,
+    pub reason: String,
+    /// The line of the assignment, when read from a file
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for DefconfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(
+            f,
+            "{}={} not applied: {}",
+            self.symbol, self.value, self.reason
+        )
+    }
+}
+
+impl DefconfigLoader {
+    pub fn new(kconfig_path: impl AsRef<Path>, srctree: impl AsRef<Path>) -> Self {
+        Self {
+            kconfig_path: kconfig_path.as_ref().to_string_lossy().to_string(),
+            srctree: srctree.as_ref().to_string_lossy().to_string(),
+        }
+    }
+
+    /// Apply a defconfig on top of the Kconfig defaults
+    /// Returns: (full SymbolTable, assignments that could not be applied)
+    pub fn load(
+        &self,
+        defconfig_path: impl AsRef<Path>,
+    ) -> Result<(SymbolTable, Vec<DefconfigWarning>)> {
+        let mut engine = ConfigEngine::load(&self.kconfig_path, &self.srctree)?;
+
+        let assignments = ConfigReader::read_assignments(defconfig_path)?;
+        let warnings = Self::apply_assignments(&mut engine, &assignments);
+
+        Ok((engine.into_symbols(), warnings))
+    }
+
+    /// Load a full configuration and reduce it to a minimal defconfig
+    /// Returns: assignments in Kconfig declaration order
+    pub fn savedefconfig(&self, config_path: impl AsRef<Path>) -> Result<Vec<(String, String)>> {
+        Ok(Self::minimize(&self.load_config(config_path)?))
+    }
+
+    /// Load a full configuration on top of the Kconfig defaults
+    pub fn load_config(&self, config_path: impl AsRef<Path>) -> Result<ConfigEngine> {
+        let mut engine = ConfigEngine::load(&self.kconfig_path, &self.srctree)?;
+
+        let values = ConfigReader::read(config_path)?;
+        Self::apply(&mut engine, values);
+
+        Ok(engine)
+    }
+
+    /// Compute the smallest set of assignments that reproduces the values
+    /// of `engine` once defaults, selects and choice defaults are applied.
+    ///
+    /// Like Linux, a value is kept only when it differs from the default it
+    /// would get in the current configuration, and a choice only records a
+    /// selection other than its default. The candidate set is then checked
+    /// by applying it to a fresh engine, adding any symbol that still ends
+    /// up with a different value.
+    pub fn minimize(engine: &ConfigEngine) -> Vec<(String, String)> {
+        let resolver = engine.resolver();
+        let symbols = engine.symbols();
+
+        let mut chosen: HashSet<String> = resolver
+            .symbols()
+            .iter()
+            .filter(|name| !resolver.is_transitional(name) && !resolver.is_choice(name))
+            .filter(|name| Self::differs_from_default(resolver, symbols, name))
+            .cloned()
+            .collect();
+
+        loop {
+            let mut fresh = ConfigEngine::new(resolver.clone());
+            fresh.set_user_values(Self::assignments(resolver, symbols, &chosen));
+
+            let mismatched: Vec<String> = resolver
+                .symbols()
+                .iter()
+                .filter(|name| !chosen.contains(*name))
+                .filter(|name| !resolver.is_transitional(name) && !resolver.is_choice(name))
+                .filter(|name| symbols.get_value(name).is_some())
+                .filter(|name| fresh.value(name) != symbols.get_value(name))
+                .cloned()
+                .collect();
+
+            if mismatched.is_empty() {
+                break;
+            }
+            chosen.extend(mismatched);
+        }
+
+        Self::assignments(resolver, symbols, &chosen)
+    }
+
+    fn differs_from_default(
+        resolver: &DependencyResolver,
+        symbols: &SymbolTable,
+        name: &str,
+    ) -> bool {
+        let Some(value) = symbols.get_value(name) else {
+            return false;
+        };
+
+        // Choice options at `m` put the choice in `m` mode, which is never
+        // the default
+        if let Some(group) = resolver.choice_group(name) {
+            return match value.as_str() {
+                "y" => resolver.choice_default(group, symbols).map(String::as_str) != Some(name),
+                "m" => true,
+                _ => false,
+            };
+        }
+
+        resolver.default_value(name, symbols).as_ref() != Some(&value)
+    }
+
+    fn assignments(
+        resolver: &DependencyResolver,
+        symbols: &SymbolTable,
+        chosen: &HashSet<String>,
+    ) -> Vec<(String, String)> {
+        resolver
+            .symbols()
+            .iter()
+            .filter(|name| chosen.contains(*name))
+            .filter_map(|name| symbols.get_value(name).map(|value| (name.clone(), value)))
+            .collect()
+    }
+
+    /// Set `values` as user values and report every one that did not end
+    /// up as the symbol's value, e.g. because its dependencies are unmet,
+    /// another option selects it, or it is outside the symbol's range.
+    pub fn apply(
+        engine: &mut ConfigEngine,
+        values: HashMap<String, String>,
+    ) -> Vec<DefconfigWarning> {
+        Self::apply_at_lines(engine, values, &HashMap::new())
+    }
+
+    /// Like `apply`, with warnings naming the line of each assignment.
+    /// A symbol assigned twice keeps the last value.
+    pub fn apply_assignments(
+        engine: &mut ConfigEngine,
+        assignments: &[ConfigAssignment],
+    ) -> Vec<DefconfigWarning> {
+        let values = assignments
+            .iter()
+            .map(|a| (a.name.clone(), a.value.clone()))
+            .collect();
+        let lines = assignments
+            .iter()
+            .map(|a| (a.name.clone(), a.line))
+            .collect();
+        Self::apply_at_lines(engine, values, &lines)
+    }
+
+    fn apply_at_lines(
+        engine: &mut ConfigEngine,
+        values: HashMap<String, String>,
+        lines: &HashMap<String, usize>,
+    ) -> Vec<DefconfigWarning> {
+        let mut warnings: Vec<DefconfigWarning> = engine
+            .set_user_values_at(values.clone(), lines)
+            .into_iter()
+            .map(|name| DefconfigWarning {
+                value: values[&name].clone(),
+                reason: "unknown symbol".to_string(),
+                line: lines.get(&name).copied(),
+                symbol: name,
+            })
+            .collect();
+
+        for name in engine.resolver().symbols() {
+            let Some(value) = values.get(name) else {
+                continue;
+            };
+            // Values are compared in canonical form, e.g. hex with `0x`
+            let written = engine
+                .resolver()
+                .symbol_type(name)
+                .and_then(|symbol_type| normalize_value(symbol_type, value).ok());
+            if written.is_some() && engine.value(name) == written {
+                continue;
+            }
+
+            warnings.push(DefconfigWarning {
+                symbol: name.clone(),
+                value: value.clone(),
+                reason: Self::rejection_reason(engine, name, value),
+                line: lines.get(name).copied(),
+            });
+        }
+
+        warnings
+    }
+
+    fn rejection_reason(engine: &ConfigEngine, name: &str, value: &str) -> String {
+        if let Some(problem) = engine.check_value(name, value) {
+            return problem;
+        }
+
+        let resolver = engine.resolver();
+        let symbols = engine.symbols();
+        let requested = Tristate::from_value(value);
+        let is_bool = matches!(
+            resolver.symbol_type(name),
+            Some(SymbolType::Bool | SymbolType::Tristate)
+        );
+
+        if !is_bool || requested.is_enabled() {
+            if let Err(e) = resolver.can_enable(name, symbols) {
+                return e.to_string();
+            }
+        }
+
+        if is_bool && resolver.select_value(name, symbols) > requested {
+            if let Err(e) = resolver.can_disable(name, symbols) {
+                return e.to_string();
+            }
+        }
+
+        match resolver.prompt_value(name, symbols) {
+            None => return "option has no prompt".to_string(),
+            Some(Tristate::No) => return "its prompt is hidden by its condition".to_string(),
+            Some(_) => {}
+        }
+
+        let max = resolver.max_value(name, symbols);
+        if is_bool && max < requested {
+            return format!("limited to {} by its dependencies", max);
+        }
+
+        "overridden by another option".to_string()
+    }
+}
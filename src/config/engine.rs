@@ -0,0 +1,545 @@
+use crate::config::{solver, ConfigReader, Explanation, Solution};
+use crate::error::Result;
+use crate::kconfig::dependency_resolver::{ChoiceGroup, DependencyResolver};
+use crate::kconfig::{
+    format_number, normalize_value, parse_number, Entry, Parser, SymbolTable, SymbolType, Tristate,
+    ValueOrigin,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Computes symbol values the way Linux Kconfig does.
+///
+/// User values (from a .config, a defconfig or the TUI) are kept apart from
+/// the computed values. A user value only counts while its symbol is
+/// visible; otherwise the symbol takes its default, limited by its direct
+/// dependencies and raised by `imply` and `select`. A user value its type
+/// does not accept is ignored, and int and hex values are clamped into
+/// their active `range`. Values are recomputed until nothing changes,
+/// since symbols depend on each other. Once they settle, each symbol
+/// records where its value came from.
+#[derive(Clone)]
+pub struct ConfigEngine {
+    resolver: DependencyResolver,
+    symbols: SymbolTable,
+    user_values: HashMap<String, String>,
+    /// The .config or defconfig line of each user value read from a file
+    user_lines: HashMap<String, usize>,
+    /// Map: choice option or named choice -> index of its choice in
+    /// `resolver.choice_groups()`
+    choices: HashMap<String, usize>,
+}
+
+impl ConfigEngine {
+    pub fn new(resolver: DependencyResolver) -> Self {
+        let mut symbols = SymbolTable::new();
+        resolver.populate(&mut symbols);
+
+        let mut choices = HashMap::new();
+        for (index, group) in resolver.choice_groups().iter().enumerate() {
+            for name in group.options.iter().chain(&group.name) {
+                choices.insert(name.clone(), index);
+            }
+        }
+
+        let mut engine = Self {
+            resolver,
+            symbols,
+            user_values: HashMap::new(),
+            user_lines: HashMap::new(),
+            choices,
+        };
+        engine.calculate();
+        engine
+    }
+
+    pub fn from_entries(entries: &[Entry]) -> Self {
+        let mut resolver = DependencyResolver::new();
+        resolver.build_from_entries(entries);
+        Self::new(resolver)
+    }
+
+    /// Parse a Kconfig tree and compute its default configuration
+    pub fn load(kconfig_path: impl AsRef<Path>, srctree: impl AsRef<Path>) -> Result<Self> {
+        let mut parser = Parser::new(kconfig_path, srctree)?;
+        let ast = parser.parse()?;
+        Ok(Self::from_entries(&ast.entries))
+    }
+
+    /// Take every assignment of a .config or defconfig as a user value,
+    /// remembering its line
+    /// Returns: the names not declared in the Kconfig, sorted
+    pub fn read_user_values(&mut self, config_path: impl AsRef<Path>) -> Result<Vec<String>> {
+        let assignments = ConfigReader::read_assignments(config_path)?;
+        let lines = assignments
+            .iter()
+            .map(|a| (a.name.clone(), a.line))
+            .collect();
+        let values = assignments.into_iter().map(|a| (a.name, a.value));
+        Ok(self.set_user_values_at(values, &lines))
+    }
+
+    pub fn resolver(&self) -> &DependencyResolver {
+        &self.resolver
+    }
+
+    /// The computed values
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub fn into_symbols(self) -> SymbolTable {
+        self.symbols
+    }
+
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.symbols.get_value(name)
+    }
+
+    pub fn user_value(&self, name: &str) -> Option<&String> {
+        self.user_values.get(name)
+    }
+
+    pub fn visibility(&self, name: &str) -> Tristate {
+        self.resolver.visibility(name, &self.symbols)
+    }
+
+    /// Where the current value of `name` came from
+    pub fn origin(&self, name: &str) -> Option<&ValueOrigin> {
+        self.symbols.get_origin(name)
+    }
+
+    /// Why `name` has its value, and what else bears on it
+    /// Returns: None if the symbol is not declared in the Kconfig
+    pub fn explain(&self, name: &str) -> Option<Explanation> {
+        Explanation::new(self, name)
+    }
+
+    /// A minimal set of other user values that let `name` take `value`,
+    /// e.g. the dependencies to enable before a symbol can be enabled
+    /// Returns: None if no such values were found
+    pub fn solve(&self, name: &str, value: &str) -> Option<Solution> {
+        solver::solve(self, name, value)
+    }
+
+    /// Set a user value and recompute
+    /// Returns: false if the symbol is not declared in the Kconfig
+    pub fn set_user_value(&mut self, name: &str, value: &str) -> bool {
+        if self.resolver.symbol_type(name).is_none() {
+            return false;
+        }
+        self.forget_choice_picks(name, value, &HashSet::new());
+        self.user_values.insert(name.to_string(), value.to_string());
+        self.user_lines.remove(name);
+        self.calculate();
+        true
+    }
+
+    /// Set several user values, recomputing once. Like `set_user_value`,
+    /// they replace earlier picks of their choices; options of one choice
+    /// set together are resolved as when reading a .config.
+    /// Returns: the names not declared in the Kconfig, sorted
+    pub fn set_user_values(
+        &mut self,
+        values: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<String> {
+        self.set_user_values_at(values, &HashMap::new())
+    }
+
+    /// Like `set_user_values`, remembering the line each value was read
+    /// from, so that origins can name it
+    pub fn set_user_values_at(
+        &mut self,
+        values: impl IntoIterator<Item = (String, String)>,
+        lines: &HashMap<String, usize>,
+    ) -> Vec<String> {
+        let values: Vec<(String, String)> = values.into_iter().collect();
+        let batch: HashSet<String> = values.iter().map(|(name, _)| name.clone()).collect();
+
+        let mut unknown = Vec::new();
+        for (name, value) in values {
+            if self.resolver.symbol_type(&name).is_some() {
+                self.forget_choice_picks(&name, &value, &batch);
+                match lines.get(&name) {
+                    Some(line) => self.user_lines.insert(name.clone(), *line),
+                    None => self.user_lines.remove(&name),
+                };
+                self.user_values.insert(name, value);
+            } else {
+                unknown.push(name);
+            }
+        }
+        unknown.sort();
+
+        self.calculate();
+        unknown
+    }
+
+    /// Picking an option of a choice replaces earlier picks: the other
+    /// options set to `y` and the mode of a named choice are forgotten,
+    /// unless they are in `keep`
+    fn forget_choice_picks(&mut self, name: &str, value: &str, keep: &HashSet<String>) {
+        if value != "y" && value != "m" {
+            return;
+        }
+        let Some(group) = Self::choice_of(&self.resolver, &self.choices, name) else {
+            return;
+        };
+        if group.name.as_deref() == Some(name) {
+            return;
+        }
+
+        for sibling in group
+            .options
+            .iter()
+            .filter(|o| *o != name && !keep.contains(*o))
+        {
+            let conflicts = matches!(
+                (self.user_values.get(sibling).map(String::as_str), value),
+                (Some("y"), _) | (Some("m"), "y")
+            );
+            if conflicts {
+                self.user_values.remove(sibling);
+            }
+        }
+        if let Some(choice) = group.name.as_ref().filter(|c| !keep.contains(*c)) {
+            self.user_values.remove(choice);
+        }
+    }
+
+    pub fn clear_user_value(&mut self, name: &str) {
+        self.user_lines.remove(name);
+        if self.user_values.remove(name).is_some() {
+            self.calculate();
+        }
+    }
+
+    pub fn clear_user_values(&mut self) {
+        self.user_values.clear();
+        self.user_lines.clear();
+        self.calculate();
+    }
+
+    /// Recompute every value until a fixed point is reached
+    pub fn calculate(&mut self) {
+        let Self {
+            resolver,
+            symbols,
+            user_values,
+            user_lines,
+            choices,
+        } = self;
+
+        // Each pass settles at least one more symbol unless values
+        // oscillate, which only a dependency cycle can cause
+        for _ in 0..=resolver.symbols().len() {
+            let mut changed = false;
+
+            for name in resolver.symbols() {
+                if choices.contains_key(name) {
+                    continue;
+                }
+
+                let value = Self::calc_value(resolver, symbols, user_values, name);
+                if symbols.get_value(name) != value {
+                    match value {
+                        Some(value) => symbols.set_value(name, value),
+                        None => symbols.clear_value(name),
+                    }
+                    changed = true;
+                }
+            }
+
+            for group in resolver.choice_groups() {
+                let (mode, values) = Self::calc_choice(resolver, symbols, user_values, group);
+                let updates = group
+                    .options
+                    .iter()
+                    .zip(values)
+                    .chain(group.name.iter().map(|n| (n, mode)));
+                for (name, value) in updates {
+                    if symbols.get_value(name).as_deref() != Some(value.as_str()) {
+                        symbols.set_value(name, value.to_string());
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let origins: Vec<_> = resolver
+            .symbols()
+            .iter()
+            .map(|name| {
+                let choice = Self::choice_of(resolver, choices, name);
+                Self::calc_origin(resolver, symbols, user_values, user_lines, choice, name)
+            })
+            .collect();
+        for (name, origin) in resolver.symbols().iter().zip(origins) {
+            symbols.set_origin(name, origin);
+        }
+        for name in resolver.symbols() {
+            let visible = resolver.visibility(name, symbols) != Tristate::No;
+            symbols.set_visible(name, visible);
+        }
+    }
+
+    /// The choice `name` is an option of, or the named choice `name` is
+    fn choice_of<'a>(
+        resolver: &'a DependencyResolver,
+        choices: &HashMap<String, usize>,
+        name: &str,
+    ) -> Option<&'a ChoiceGroup> {
+        choices
+            .get(name)
+            .map(|&index| &resolver.choice_groups()[index])
+    }
+
+    /// Where the settled value of `name` comes from: the first of a
+    /// select, unmet dependencies, the user, a default and an imply that
+    /// accounts for it
+    fn calc_origin(
+        resolver: &DependencyResolver,
+        symbols: &SymbolTable,
+        user_values: &HashMap<String, String>,
+        user_lines: &HashMap<String, usize>,
+        choice: Option<&ChoiceGroup>,
+        name: &str,
+    ) -> Option<ValueOrigin> {
+        let symbol_type = resolver.symbol_type(name)?;
+        let value = symbols.get_value(name);
+        let visible = resolver.visibility(name, symbols);
+        let by_user = user_values
+            .get(name)
+            .is_some_and(|user| visible.is_enabled() && normalize_value(symbol_type, user).is_ok());
+        let user = ValueOrigin::User {
+            line: user_lines.get(name).copied(),
+        };
+
+        if resolver.is_transitional(name) && user_values.contains_key(name) {
+            return Some(user);
+        }
+        if choice.is_some_and(|group| group.name.as_deref() == Some(name)) {
+            return Some(if by_user {
+                user
+            } else {
+                ValueOrigin::ChoiceDefault
+            });
+        }
+        let unmet = !resolver.dependency_value(name, symbols).is_enabled();
+        if unmet && resolver.select_value(name, symbols) == Tristate::No {
+            return Some(ValueOrigin::UnmetDependencies);
+        }
+
+        if let Some(group) = choice {
+            let current = Tristate::from_value(value.as_deref()?);
+            if current == Tristate::No {
+                if let Some(selected) = group
+                    .options
+                    .iter()
+                    .find(|o| symbols.get_value(o).as_deref() == Some("y"))
+                {
+                    return Some(ValueOrigin::ChoiceOther {
+                        selected: selected.clone(),
+                    });
+                }
+            }
+            let picked = user_values
+                .get(name)
+                .is_some_and(|v| Tristate::from_value(v) == current);
+            return Some(if picked && visible.is_enabled() {
+                user
+            } else {
+                ValueOrigin::ChoiceDefault
+            });
+        }
+
+        let default = resolver.active_default(name, symbols);
+        let default_origin = || match default {
+            Some(default) => ValueOrigin::Default {
+                span: default.span.clone(),
+            },
+            None => ValueOrigin::NoDefault,
+        };
+
+        if !matches!(symbol_type, SymbolType::Bool | SymbolType::Tristate) {
+            value.as_ref()?;
+            return Some(if by_user { user } else { default_origin() });
+        }
+
+        let current = Tristate::from_value(value.as_deref()?);
+        if current.is_enabled() {
+            if let Some(selector) = resolver.selected_by(name, current, symbols) {
+                return Some(ValueOrigin::Selected {
+                    by: selector.clone(),
+                });
+            }
+        }
+        if by_user {
+            return Some(user);
+        }
+        if current.is_enabled() && default.is_none_or(|default| default.value(symbols) < current) {
+            if let Some(implier) = resolver.implied_by(name, current, symbols) {
+                return Some(ValueOrigin::Implied {
+                    by: implier.clone(),
+                });
+            }
+        }
+        Some(default_origin())
+    }
+
+    fn calc_value(
+        resolver: &DependencyResolver,
+        symbols: &SymbolTable,
+        user_values: &HashMap<String, String>,
+        name: &str,
+    ) -> Option<String> {
+        let symbol_type = resolver.symbol_type(name)?;
+        let visible = resolver.visibility(name, symbols);
+        let user = user_values
+            .get(name)
+            .filter(|_| visible.is_enabled())
+            .and_then(|user| normalize_value(symbol_type, user).ok());
+
+        // Transitional symbols keep the value of an old .config as is, so
+        // new symbols can take their defaults from it
+        if resolver.is_transitional(name) {
+            if let Some(user) = user_values.get(name) {
+                return Some(user.clone());
+            }
+        }
+
+        let value = match (symbol_type, user) {
+            (SymbolType::Bool | SymbolType::Tristate, Some(user)) => {
+                // (user && visible) || select, as in Linux
+                let value = Tristate::from_value(&user)
+                    .and(visible)
+                    .or(resolver.select_value(name, symbols));
+                Some(resolver.promote(name, value, symbols).to_string())
+            }
+            (_, Some(user)) => Some(user),
+            (_, None) => resolver.default_value(name, symbols),
+        };
+
+        match symbol_type {
+            SymbolType::Int | SymbolType::Hex => {
+                value.map(|value| resolver.clamp_to_range(name, value, symbols))
+            }
+            _ => value,
+        }
+    }
+
+    /// Why `value` would not be kept as written for `name`: a value its
+    /// type does not accept, or a number outside its active range
+    pub fn check_value(&self, name: &str, value: &str) -> Option<String> {
+        let symbol_type = self.resolver.symbol_type(name)?;
+        let value = match normalize_value(symbol_type, value) {
+            Ok(value) => value,
+            Err(expected) => return Some(format!("invalid value, {}", expected)),
+        };
+
+        let (low, high) = self.resolver.active_range(name, &self.symbols)?;
+        let number = parse_number(symbol_type, &value)?;
+        let clamped = if number < low {
+            low
+        } else if number > high {
+            high
+        } else {
+            return None;
+        };
+        Some(format!(
+            "outside range {} to {}, clamped to {}",
+            format_number(symbol_type, low),
+            format_number(symbol_type, high),
+            format_number(symbol_type, clamped)
+        ))
+    }
+
+    /// The mode of a choice and the values of its options, in order.
+    ///
+    /// In `y` mode exactly one option is `y`: the visible option the user
+    /// set to `y`, otherwise the choice default skipping options the user
+    /// set to `n`. In `m` mode, only possible for tristate choices while
+    /// modules are enabled, every option the user set to `m` is `m`. In
+    /// `n` mode, only possible for optional choices, no option is set.
+    fn calc_choice(
+        resolver: &DependencyResolver,
+        symbols: &SymbolTable,
+        user_values: &HashMap<String, String>,
+        group: &ChoiceGroup,
+    ) -> (Tristate, Vec<Tristate>) {
+        let user_value = |name: &String| user_values.get(name).map(|v| Tristate::from_value(v));
+        let allows_module =
+            group.symbol_type == SymbolType::Tristate && resolver.modules_enabled(symbols);
+
+        let picked = group.options.iter().find(|o| {
+            user_value(o) == Some(Tristate::Yes) && resolver.visibility(o, symbols).is_enabled()
+        });
+
+        // The mode the user asked for, through the choice itself or its options
+        let mut mode = match group.name.as_ref().and_then(user_value) {
+            Some(mode) => mode,
+            None if picked.is_some() => Tristate::Yes,
+            None if allows_module
+                && group
+                    .options
+                    .iter()
+                    .any(|o| user_value(o) == Some(Tristate::Module)) =>
+            {
+                Tristate::Module
+            }
+            None if group.optional => Tristate::No,
+            None => Tristate::Yes,
+        };
+        if mode == Tristate::No && !group.optional || mode == Tristate::Module && !allows_module {
+            mode = Tristate::Yes;
+        }
+
+        // Dependencies at `m` force a tristate choice into `m` mode
+        let limit = group
+            .options
+            .iter()
+            .map(|o| resolver.max_value(o, symbols))
+            .max()
+            .unwrap_or(Tristate::No);
+        mode = mode.and(limit);
+
+        let values: Vec<Tristate> = match mode {
+            Tristate::Yes => {
+                let chosen = picked.or_else(|| {
+                    let excluded: HashSet<&String> = group
+                        .options
+                        .iter()
+                        .filter(|o| user_value(o) == Some(Tristate::No))
+                        .collect();
+                    resolver.choice_default_excluding(group, symbols, &excluded)
+                });
+                group
+                    .options
+                    .iter()
+                    .map(|o| Tristate::from(Some(o) == chosen))
+                    .collect()
+            }
+            Tristate::Module => group
+                .options
+                .iter()
+                .map(|o| match user_value(o) {
+                    Some(Tristate::Module | Tristate::Yes) => {
+                        resolver.visibility(o, symbols).and(Tristate::Module)
+                    }
+                    _ => Tristate::No,
+                })
+                .collect(),
+            Tristate::No => vec![Tristate::No; group.options.len()],
+        };
+
+        // A choice with no option set is `n`
+        if values.iter().all(|v| !v.is_enabled()) {
+            mode = Tristate::No;
+        }
+        (mode, values)
+    }
+}
@@ -0,0 +1,87 @@
+use crate::config::ConfigEngine;
+use crate::kconfig::{Expr, Span, Tristate, ValueOrigin};
+use std::fmt;
+
+/// Why a symbol has its value: where the value came from, and the
+/// dependencies, selects and implies that bear on it
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub symbol: String,
+    pub value: Option<String>,
+    pub origin: Option<ValueOrigin>,
+    pub defined_at: Vec<Span>,
+    /// The `depends on` expression, including enclosing blocks, and its value
+    pub depends_on: Option<(Expr, Tristate)>,
+    /// The symbols that select it, with their values
+    pub selected_by: Vec<(String, Option<String>)>,
+    /// The symbols that imply it, with their values
+    pub implied_by: Vec<(String, Option<String>)>,
+}
+
+impl Explanation {
+    /// Returns: None if the symbol is not declared in the Kconfig
+    pub fn new(engine: &ConfigEngine, name: &str) -> Option<Self> {
+        let resolver = engine.resolver();
+        resolver.symbol_type(name)?;
+
+        let with_values =
+            |names: &[String]| names.iter().map(|n| (n.clone(), engine.value(n))).collect();
+        Some(Self {
+            symbol: name.to_string(),
+            value: engine.value(name),
+            origin: engine.origin(name).cloned(),
+            defined_at: resolver.defined_at(name).to_vec(),
+            depends_on: resolver.direct_dependencies(name).map(|expr| {
+                (
+                    expr.clone(),
+                    resolver.dependency_value(name, engine.symbols()),
+                )
+            }),
+            selected_by: with_values(resolver.selectors(name)),
+            implied_by: with_values(resolver.impliers(name)),
+        })
+    }
+}
+
+/// The value and its origin, then one line per related fact:
+///
+/// ```text
+/// CRYPTO=y: selected by NET_TLS
+///   defined at Kconfig:12:1
+///   depends on: NET [y]
+///   selected by: NET_TLS=y, WIREGUARD=n
+/// ```
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={}", self.symbol, value)?,
+            None => write!(f, "{} has no value", self.symbol)?,
+        }
+        match &self.origin {
+            Some(origin) => writeln!(f, ": {}", origin)?,
+            None => writeln!(f)?,
+        }
+
+        for span in &self.defined_at {
+            writeln!(f, "  defined at {}", span)?;
+        }
+        if let Some((expr, value)) = &self.depends_on {
+            writeln!(f, "  depends on: {} [{}]", expr, value)?;
+        }
+
+        let list = |symbols: &[(String, Option<String>)]| {
+            symbols
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value.as_deref().unwrap_or("")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if !self.selected_by.is_empty() {
+            writeln!(f, "  selected by: {}", list(&self.selected_by))?;
+        }
+        if !self.implied_by.is_empty() {
+            writeln!(f, "  implied by: {}", list(&self.implied_by))?;
+        }
+        Ok(())
+    }
+}
@@ -1,72 +1,312 @@
+use crate::config::output::write_if_changed;
 use crate::error::Result;
-use crate::kconfig::SymbolTable;
-use std::fs::File;
+use crate::kconfig::{parse_hex, parse_int, Entry, Property, SymbolTable, SymbolType};
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::path::Path;
 
 /// ConfigGenerator generates build system configuration files.
-/// 
-/// Note: Modules (tristate 'm' values) are treated as 'y' in this implementation
-/// as the rust-kbuild project does not currently support loadable modules.
-/// This is a deviation from the Linux kernel Kconfig which generates separate
-/// _MODULE defines for module options.
 pub struct ConfigGenerator;
 
+/// Test a configuration from C, as Linux's `include/linux/kconfig.h` does:
+/// `IS_BUILTIN(CONFIG_FOO)` is 1 if FOO is `y`, `IS_MODULE(CONFIG_FOO)` if
+/// it is `m`, and `IS_ENABLED(CONFIG_FOO)` if it is either. The argument
+/// is the macro name, so these work with any prefix.
+const KCONFIG_H: &str = r#"/*
+ * Automatically generated file; DO NOT EDIT.
+ */
+
+#ifndef __RKCONF_KCONFIG_H
+#define __RKCONF_KCONFIG_H
+
+#define __ARG_PLACEHOLDER_1 0,
+#define __take_second_arg(__ignored, val, ...) val
+
+#define __or(x, y) ___or(x, y)
+#define ___or(x, y) ____or(__ARG_PLACEHOLDER_##x, y)
+#define ____or(arg1_or_junk, y) __take_second_arg(arg1_or_junk 1, y)
+
+#define __is_defined(x) ___is_defined(x)
+#define ___is_defined(val) ____is_defined(__ARG_PLACEHOLDER_##val)
+#define ____is_defined(arg1_or_junk) __take_second_arg(arg1_or_junk 1, 0)
+
+/* 1 if the option is set to 'y', 0 otherwise */
+#define IS_BUILTIN(option) __is_defined(option)
+
+/* 1 if the option is set to 'm', 0 otherwise */
+#define IS_MODULE(option) __is_defined(option##_MODULE)
+
+/* 1 if the option is set to 'y' or 'm', 0 otherwise */
+#define IS_ENABLED(option) __or(IS_BUILTIN(option), IS_MODULE(option))
+
+#endif /* __RKCONF_KCONFIG_H */
+"#;
+
 impl ConfigGenerator {
-    pub fn generate_auto_conf(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<()> {
-        let mut file = File::create(path)?;
+    /// Write every enabled or valued symbol, in Kconfig declaration order
+    /// Returns: whether the file was written
+    pub fn generate_auto_conf(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<bool> {
+        let mut out = Vec::new();
 
-        writeln!(file, "#")?;
-        writeln!(file, "# Automatically generated file; DO NOT EDIT.")?;
-        writeln!(file, "#")?;
+        writeln!(out, "#")?;
+        writeln!(out, "# Automatically generated file; DO NOT EDIT.")?;
+        writeln!(out, "#")?;
 
-        for (name, symbol) in symbols.all_symbols() {
+        for symbol in symbols
+            .ordered_symbols()
+            .into_iter()
+            .filter(|s| !s.is_transitional)
+        {
             // Strip CONFIG_ prefix if present
-            let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
-            
+            let clean_name = symbol.name.strip_prefix("CONFIG_").unwrap_or(&symbol.name);
+
             if let Some(value) = &symbol.value {
                 if value != "n" {
-                    writeln!(file, "{}={}", clean_name, value)?;
+                    writeln!(out, "{}={}", clean_name, value)?;
                 }
             }
         }
 
-        Ok(())
+        write_if_changed(path, &out)
     }
 
-    pub fn generate_autoconf_h(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<()> {
-        let mut file = File::create(path)?;
+    /// Write the configuration as C macros, with no prefix
+    pub fn generate_autoconf_h(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<bool> {
+        Self::generate_autoconf_h_with_prefix(path, symbols, "")
+    }
+
+    /// Write the configuration as C macros named `prefix` followed by the
+    /// symbol name, in Kconfig declaration order. As in Linux, `y` defines
+    /// `FOO` as 1 and `m` defines `FOO_MODULE` as 1; int and hex values are
+    /// bare literals and strings are C string literals. Disabled symbols,
+    /// and values that do not fit their type, define nothing.
+    /// Returns: whether the file was written
+    pub fn generate_autoconf_h_with_prefix(
+        path: impl AsRef<Path>,
+        symbols: &SymbolTable,
+        prefix: &str,
+    ) -> Result<bool> {
+        let mut out = Vec::new();
 
-        writeln!(file, "/*")?;
-        writeln!(file, " * Automatically generated file; DO NOT EDIT.")?;
-        writeln!(file, " */")?;
-        writeln!(file)?;
+        writeln!(out, "/*")?;
+        writeln!(out, " * Automatically generated file; DO NOT EDIT.")?;
+        writeln!(out, " */")?;
+        writeln!(out)?;
 
-        for (name, symbol) in symbols.all_symbols() {
+        for symbol in symbols
+            .ordered_symbols()
+            .into_iter()
+            .filter(|s| !s.is_transitional)
+        {
             // Strip CONFIG_ prefix if present
-            let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
-            
-            if let Some(value) = &symbol.value {
-                match value.as_str() {
-                    "y" => {
-                        writeln!(file, "#define {} 1", clean_name)?;
-                    }
-                    "m" => {
-                        // Treat modules as 'y' (no module support in rust-kbuild)
-                        // This is a simplification compared to Linux Kconfig which would
-                        // generate a separate _MODULE define
-                        writeln!(file, "#define {} 1", clean_name)?;
-                    }
-                    "n" => {
-                        // Don't define anything for disabled options
-                    }
-                    _ => {
-                        writeln!(file, "#define {} \"{}\"", clean_name, value)?;
-                    }
+            let clean_name = symbol.name.strip_prefix("CONFIG_").unwrap_or(&symbol.name);
+
+            let Some(value) = &symbol.value else {
+                continue;
+            };
+            let define = match symbol.symbol_type {
+                SymbolType::Bool | SymbolType::Tristate => match value.as_str() {
+                    "y" => Some((String::new(), "1".to_string())),
+                    "m" => Some(("_MODULE".to_string(), "1".to_string())),
+                    // Don't define anything for disabled options
+                    _ => None,
+                },
+                SymbolType::Int => parse_int(value).map(|n| (String::new(), n.to_string())),
+                SymbolType::Hex => parse_hex(value).map(|n| (String::new(), format!("0x{:x}", n))),
+                SymbolType::String => Some((String::new(), c_string(value))),
+            };
+            if let Some((suffix, literal)) = define {
+                writeln!(
+                    out,
+                    "#define {}{}{} {}",
+                    prefix, clean_name, suffix, literal
+                )?;
+            }
+        }
+
+        write_if_changed(path, &out)
+    }
+
+    /// Write the `IS_ENABLED`, `IS_BUILTIN` and `IS_MODULE` macros for
+    /// testing the macros of `autoconf.h`
+    /// Returns: whether the file was written
+    pub fn generate_kconfig_h(path: impl AsRef<Path>) -> Result<bool> {
+        write_if_changed(path, KCONFIG_H.as_bytes())
+    }
+
+    /// Write the configuration as a Rust module of constants, in Kconfig
+    /// declaration order: bool and tristate symbols as `bool` (`m` counts
+    /// as enabled), int symbols as `i64`, hex symbols as `u64` hex
+    /// literals and strings as `&str`. Each constant is documented with
+    /// its prompt and help text.
+    ///
+    /// Names are the symbol names made valid Rust identifiers: upper case,
+    /// other characters replaced by `_`, and a leading `_` before a digit;
+    /// names that still clash get `_2`, `_3`, ... in declaration order.
+    /// Symbols without a value, or whose value does not fit their type,
+    /// are left out, as in `autoconf.h`.
+    /// Returns: whether the file was written
+    pub fn generate_config_rs(
+        path: impl AsRef<Path>,
+        symbols: &SymbolTable,
+        entries: &[Entry],
+    ) -> Result<bool> {
+        let mut out = Vec::new();
+
+        writeln!(out, "//! Automatically generated file; DO NOT EDIT.")?;
+
+        let mut declared = Vec::new();
+        collect_declarations(entries, &mut declared);
+        let identifiers = rust_identifiers(entries);
+        let mut seen = HashSet::new();
+
+        for (name, properties) in declared {
+            if !seen.insert(name) {
+                continue;
+            }
+            let Some(ident) = identifiers.get(name) else {
+                continue;
+            };
+            let Some(symbol) = symbols.get_symbol(name).filter(|s| !s.is_transitional) else {
+                continue;
+            };
+            let Some(value) = &symbol.value else {
+                continue;
+            };
+            let (rust_type, literal) = match symbol.symbol_type {
+                SymbolType::Bool | SymbolType::Tristate => {
+                    ("bool", (value == "y" || value == "m").to_string())
                 }
+                SymbolType::Int => match parse_int(value) {
+                    Some(number) => ("i64", number.to_string()),
+                    None => continue,
+                },
+                SymbolType::Hex => match parse_hex(value) {
+                    Some(number) => ("u64", format!("0x{:x}", number)),
+                    None => continue,
+                },
+                SymbolType::String => ("&str", format!("{:?}", value)),
+            };
+
+            writeln!(out)?;
+            write_doc_comment(&mut out, properties)?;
+            writeln!(out, "pub const {}: {} = {};", ident, rust_type, literal)?;
+        }
+
+        write_if_changed(path, &out)
+    }
+}
+
+/// Every config symbol definition in `entries`, in declaration order
+fn collect_declarations<'a>(entries: &'a [Entry], declared: &mut Vec<(&'a str, &'a Property)>) {
+    for entry in entries {
+        match entry {
+            Entry::Config(config) => declared.push((&config.name, &config.properties)),
+            Entry::MenuConfig(config) => declared.push((&config.name, &config.properties)),
+            Entry::Choice(choice) => collect_declarations(&choice.entries, declared),
+            Entry::Menu(menu) => collect_declarations(&menu.entries, declared),
+            Entry::If(if_block) => collect_declarations(&if_block.entries, declared),
+            Entry::Comment(_) | Entry::Source(_) | Entry::MainMenu(_) => {}
+        }
+    }
+}
+
+/// The prompt, then the help text after an empty line
+fn write_doc_comment(out: &mut Vec<u8>, properties: &Property) -> Result<()> {
+    if let Some(prompt) = &properties.prompt {
+        writeln!(out, "/// {}", prompt)?;
+    }
+    if let Some(help) = properties
+        .help
+        .as_deref()
+        .map(str::trim_end)
+        .filter(|h| !h.is_empty())
+    {
+        if properties.prompt.is_some() {
+            writeln!(out, "///")?;
+        }
+        for line in help.lines() {
+            match line.trim_end() {
+                "" => writeln!(out, "///")?,
+                line => writeln!(out, "/// {}", line)?,
             }
         }
+    }
+    Ok(())
+}
+
+/// `value` as a C string literal; control characters are written as
+/// octal escapes, which unlike hex escapes end after three digits
+fn c_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            // `??` could start a trigraph
+            '?' if out.ends_with('?') => out.push_str("\\?"),
+            ch if ch.is_control() && ch.is_ascii() => out.push_str(&format!("\\{:03o}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The Rust identifier of every declared symbol that is not transitional,
+/// numbered over all of them in declaration order, so that the constants
+/// of `generate_config_rs` and the cfgs of `BuildScript` agree whichever
+/// symbols have a value
+pub(crate) fn rust_identifiers(entries: &[Entry]) -> HashMap<String, String> {
+    let mut declared = Vec::new();
+    collect_declarations(entries, &mut declared);
+    let transitional: HashSet<&str> = declared
+        .iter()
+        .filter(|(_, properties)| properties.transitional)
+        .map(|(name, _)| *name)
+        .collect();
+
+    let mut identifiers = HashMap::new();
+    let mut taken = HashSet::new();
+    for (name, _) in declared {
+        if transitional.contains(name) || identifiers.contains_key(name) {
+            continue;
+        }
+        let ident = unique_identifier(name, &mut taken);
+        identifiers.insert(name.to_string(), ident);
+    }
+    identifiers
+}
+
+/// `name` as an upper-case Rust identifier not in `taken`, which it is
+/// added to
+fn unique_identifier(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    // `_` alone is not a name
+    if ident == "_" {
+        ident.push('_');
+    }
 
-        Ok(())
+    let mut unique = ident.clone();
+    let mut suffix = 2;
+    while !taken.insert(unique.clone()) {
+        unique = format!("{}_{}", ident, suffix);
+        suffix += 1;
     }
+    unique
 }
@@ -1,9 +1,20 @@
-pub mod reader;
-pub mod writer;
+pub mod allconfig;
+pub mod defconfig;
+pub mod engine;
+pub mod explain;
 pub mod generator;
 pub mod oldconfig;
+pub mod output;
+pub mod reader;
+pub mod solver;
+pub mod writer;
 
+pub use allconfig::{AllConfig, AllConfigMode, Probability, RandomConfig};
+pub use defconfig::{DefconfigLoader, DefconfigWarning};
+pub use engine::ConfigEngine;
+pub use explain::Explanation;
+pub use generator::*;
+pub use oldconfig::{ConfigChanges, OldConfigLoader};
 pub use reader::*;
+pub use solver::Solution;
 pub use writer::*;
-pub use generator::*;
-pub use oldconfig::{OldConfigLoader, ConfigChanges};
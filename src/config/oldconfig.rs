@@ -1,7 +1,7 @@
-use crate::config::ConfigReader;
+use crate::config::{ConfigEngine, ConfigReader, DefconfigWarning};
 use crate::error::Result;
-use crate::kconfig::{Parser, SymbolTable};
-use std::collections::HashSet;
+use crate::kconfig::SymbolTable;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 pub struct OldConfigLoader {
@@ -9,23 +9,24 @@ pub struct OldConfigLoader {
     srctree: String,
 }
 
+#[derive(Default)]
 pub struct ConfigChanges {
-    pub new_symbols: Vec<String>,      // Symbols added in new Kconfig
-    pub removed_symbols: Vec<String>,  // Symbols removed from Kconfig
+    pub new_symbols: Vec<String>,        // Symbols added in new Kconfig
+    pub removed_symbols: Vec<String>,    // Symbols removed from Kconfig
+    pub warnings: Vec<DefconfigWarning>, // Invalid or out-of-range values
 }
 
 impl ConfigChanges {
     pub fn new() -> Self {
-        Self {
-            new_symbols: Vec::new(),
-            removed_symbols: Vec::new(),
-        }
+        Self::default()
     }
-    
+
     pub fn has_changes(&self) -> bool {
-        !self.new_symbols.is_empty() || !self.removed_symbols.is_empty()
+        !self.new_symbols.is_empty()
+            || !self.removed_symbols.is_empty()
+            || !self.warnings.is_empty()
     }
-    
+
     pub fn print_summary(&self) {
         if !self.new_symbols.is_empty() {
             println!("🆕 New configuration options detected:");
@@ -34,7 +35,7 @@ impl ConfigChanges {
             }
             println!();
         }
-        
+
         if !self.removed_symbols.is_empty() {
             println!("⚠️  Removed configuration options (will be ignored):");
             for symbol in &self.removed_symbols {
@@ -42,7 +43,15 @@ impl ConfigChanges {
             }
             println!();
         }
-        
+
+        if !self.warnings.is_empty() {
+            println!("⚠️  Values not kept as written:");
+            for warning in &self.warnings {
+                println!("  {}", warning);
+            }
+            println!();
+        }
+
         if self.has_changes() {
             println!("💡 Use 'menuconfig' to review and configure new options.");
         }
@@ -56,64 +65,85 @@ impl OldConfigLoader {
             srctree: srctree.as_ref().to_string_lossy().to_string(),
         }
     }
-    
+
     /// Load old config and merge with current Kconfig definitions
     /// Returns: (merged SymbolTable, ConfigChanges)
-    pub fn load_and_merge(&self, config_path: impl AsRef<Path>) 
-        -> Result<(SymbolTable, ConfigChanges)> {
-        // Parse current Kconfig to get all defined symbols
-        let mut parser = Parser::new(&self.kconfig_path, &self.srctree)?;
-        let ast = parser.parse()?;
-        
-        // Build symbol table from Kconfig
-        let mut symbols = SymbolTable::new();
-        
-        // Extract symbols from AST
-        for entry in &ast.entries {
-            if let crate::kconfig::ast::Entry::Config(config) = entry {
-                // Strip CONFIG_ prefix if present in symbol name
-                let clean_name = config.name.strip_prefix("CONFIG_").unwrap_or(&config.name);
-                symbols.add_symbol(clean_name.to_string(), config.symbol_type.clone());
-            }
-        }
-        
+    pub fn load_and_merge(
+        &self,
+        config_path: impl AsRef<Path>,
+    ) -> Result<(SymbolTable, ConfigChanges)> {
+        let mut engine = ConfigEngine::load(&self.kconfig_path, &self.srctree)?;
+
         // Get current symbol names
-        let current_symbols: HashSet<String> = symbols
-            .all_symbols()
-            .map(|(name, _)| name.clone())
+        let current_symbols: HashSet<String> =
+            engine.resolver().symbols().iter().cloned().collect();
+
+        // Read old config file; a symbol assigned twice keeps the last value
+        let assignments = ConfigReader::read_assignments(config_path)?;
+        let old_config: HashMap<String, String> = assignments
+            .iter()
+            .map(|a| (a.name.clone(), a.value.clone()))
+            .collect();
+        let last_lines: HashMap<String, usize> = assignments
+            .iter()
+            .map(|a| (a.name.clone(), a.line))
             .collect();
-        
-        // Read old config file
-        let old_config = ConfigReader::read(config_path)?;
         let old_symbol_names: HashSet<String> = old_config.keys().cloned().collect();
-        
+
         // Detect differences
         let mut changes = ConfigChanges::new();
-        
-        // New symbols = current - old
-        for name in &current_symbols {
-            if !old_symbol_names.contains(name) {
-                changes.new_symbols.push(name.clone());
-                symbols.mark_as_new(name);
-            }
-        }
-        
+
         // Removed symbols = old - current
         for name in &old_symbol_names {
             if !current_symbols.contains(name) {
                 changes.removed_symbols.push(name.clone());
             }
         }
-        
-        // Apply old config values to matching symbols
-        for (name, value) in old_config {
-            if current_symbols.contains(&name) {
-                symbols.set_value(&name, value);
-                symbols.mark_from_config(&name);
+        changes.removed_symbols.sort();
+
+        // Old values become user values; removed symbols are ignored and
+        // new symbols take their defaults
+        engine.set_user_values_at(old_config.clone(), &last_lines);
+
+        // Values the type does not accept fall back to the default, and
+        // numbers outside the range are clamped
+        for assignment in &assignments {
+            if last_lines[&assignment.name] != assignment.line {
+                continue;
+            }
+            if let Some(reason) = engine.check_value(&assignment.name, &assignment.value) {
+                changes.warnings.push(DefconfigWarning {
+                    symbol: assignment.name.clone(),
+                    value: assignment.value.clone(),
+                    reason,
+                    line: Some(assignment.line),
+                });
+            }
+        }
+
+        // New symbols = current - old, in Kconfig order; transitional
+        // symbols and symbols whose prompt is hidden by its condition are
+        // not offered to the user
+        for name in engine.resolver().symbols() {
+            if !old_symbol_names.contains(name)
+                && !engine.resolver().is_transitional(name)
+                && !engine.resolver().is_prompt_hidden(name, engine.symbols())
+            {
+                changes.new_symbols.push(name.clone());
             }
-            // Silently ignore removed symbols
         }
-        
+
+        let mut symbols = engine.into_symbols();
+        for name in &changes.new_symbols {
+            symbols.mark_as_new(name);
+        }
+        for name in old_symbol_names
+            .iter()
+            .filter(|name| current_symbols.contains(*name))
+        {
+            symbols.mark_from_config(name);
+        }
+
         Ok((symbols, changes))
     }
 }
@@ -0,0 +1,64 @@
+//! Writing generated files without disturbing their readers.
+//!
+//! Build systems rebuild whatever depends on a file whose mtime changed,
+//! so a file is only written when its content differs. It is written to a
+//! temporary file in the same directory and renamed into place, so that a
+//! reader never sees a partial file.
+
+use crate::error::Result;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Replace the file at `path` with `content`, unless it already holds
+/// exactly that
+/// Returns: whether the file was written
+pub fn write_if_changed(path: impl AsRef<Path>, content: &[u8]) -> Result<bool> {
+    let path = path.as_ref();
+    if is_unchanged(path, content) {
+        return Ok(false);
+    }
+    write_atomically(path, content)?;
+    Ok(true)
+}
+
+/// Whether the file at `path` holds exactly `content`
+pub fn is_unchanged(path: impl AsRef<Path>, content: &[u8]) -> bool {
+    fs::read(path).is_ok_and(|existing| existing == content)
+}
+
+/// Write `content` to a temporary file beside `path`, then rename it over
+/// `path`
+pub fn write_atomically(path: impl AsRef<Path>, content: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let written = write_temp(&temp, path, content).and_then(|_| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    Ok(written?)
+}
+
+/// Write `content` to `temp` with the permissions of `path`, if it exists,
+/// and flush it to disk, so that a crash after the rename cannot leave an
+/// empty file behind
+fn write_temp(temp: &Path, path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut file = File::create(temp)?;
+    file.write_all(content)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.sync_all()
+}
+
+/// `.NAME.tmp.PID` in the directory of `path`; the process id keeps two
+/// runs writing the same file apart
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp.{}", name, process::id()))
+}
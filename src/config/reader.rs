@@ -1,18 +1,45 @@
 use crate::error::Result;
+use crate::kconfig::unquote_string;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 pub struct ConfigReader;
 
+/// One assignment of a .config or defconfig, with its 1-based line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigAssignment {
+    pub name: String,
+    pub value: String,
+    pub line: usize,
+}
+
 impl ConfigReader {
+    /// Read every assignment; a symbol assigned twice keeps the last value
     pub fn read(path: impl AsRef<Path>) -> Result<HashMap<String, String>> {
+        Ok(Self::read_assignments(path)?
+            .into_iter()
+            .map(|assignment| (assignment.name, assignment.value))
+            .collect())
+    }
+
+    /// Read every assignment in file order
+    pub fn read_assignments(path: impl AsRef<Path>) -> Result<Vec<ConfigAssignment>> {
         let content = fs::read_to_string(path)?;
-        let mut config = HashMap::new();
+        let mut assignments = Vec::new();
 
-        for line in content.lines() {
+        for (index, line) in content.lines().enumerate() {
             let line = line.trim();
-            
+            let mut assign = |name: &str, value: String| {
+                // Strip CONFIG_ prefix if present for backward compatibility
+                let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
+                assignments.push(ConfigAssignment {
+                    name: clean_name.to_string(),
+                    value,
+                    line: index + 1,
+                });
+            };
+
             // Skip empty lines
             if line.is_empty() {
                 continue;
@@ -23,9 +50,7 @@ impl ConfigReader {
                 let name = line
                     .trim_start_matches("# ")
                     .trim_end_matches(" is not set");
-                // Strip CONFIG_ prefix if present for backward compatibility
-                let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
-                config.insert(clean_name.to_string(), "n".to_string());
+                assign(name, "n".to_string());
                 continue;
             }
 
@@ -38,17 +63,16 @@ impl ConfigReader {
             if let Some(pos) = line.find('=') {
                 let name = line[..pos].trim();
                 let value = line[pos + 1..].trim();
-                
-                // Remove quotes from string values
-                let value = value.trim_matches('"');
-                
-                // Strip CONFIG_ prefix if present for backward compatibility
-                let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
-                
-                config.insert(clean_name.to_string(), value.to_string());
+
+                // Strings are quoted with `"` and `\` escaped; a malformed
+                // one only loses its quotes
+                let value =
+                    unquote_string(value).unwrap_or_else(|| value.trim_matches('"').to_string());
+
+                assign(name, value);
             }
         }
 
-        Ok(config)
+        Ok(assignments)
     }
 }
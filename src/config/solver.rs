@@ -0,0 +1,491 @@
+//! Finds the other user values a symbol needs before it can take a value.
+//!
+//! When a symbol cannot be enabled, the planner works backwards from the
+//! target: a symbol is raised by setting it through a visible prompt, by a
+//! symbol that selects it, or by a default; expressions are satisfied
+//! through their operands, taking the cheaper side of an `||`. The plan is
+//! then checked on a copy of the configuration, and every assignment the
+//! target can do without is dropped, so no smaller subset of the result
+//! works.
+
+use crate::config::ConfigEngine;
+use crate::kconfig::dependency_resolver::DependencyResolver;
+use crate::kconfig::{evaluate_expr, normalize_value, Expr, SymbolType, Tristate};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The user values that give `symbol` its `value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub symbol: String,
+    pub value: String,
+    /// Other user values to set, dependencies before their dependents
+    pub assignments: Vec<(String, String)>,
+    /// Symbols not assigned whose value changes too, with the old and the
+    /// new value
+    pub side_effects: Vec<(String, Option<String>, Option<String>)>,
+}
+
+impl Solution {
+    /// Set the assignments and the target as user values, recomputing once
+    pub fn apply(&self, engine: &mut ConfigEngine) {
+        engine.set_user_values(trial(&self.assignments, &self.symbol, &self.value));
+    }
+
+    /// Whether every assignment enables a symbol
+    pub fn only_enables(&self) -> bool {
+        self.assignments
+            .iter()
+            .all(|(_, value)| value == "y" || value == "m")
+    }
+}
+
+/// ```text
+/// To set NET_TLS=y, also set:
+///   NET=y
+/// This also changes:
+///   SCHED_FAIR: y -> n
+/// ```
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.assignments.is_empty() && self.side_effects.is_empty() {
+            return writeln!(f, "{}={} needs no other changes", self.symbol, self.value);
+        }
+
+        if self.assignments.is_empty() {
+            writeln!(
+                f,
+                "{}={} needs no other user values",
+                self.symbol, self.value
+            )?;
+        } else {
+            writeln!(f, "To set {}={}, also set:", self.symbol, self.value)?;
+            for (name, value) in &self.assignments {
+                writeln!(f, "  {}={}", name, value)?;
+            }
+        }
+        if !self.side_effects.is_empty() {
+            writeln!(f, "This also changes:")?;
+            for (name, old, new) in &self.side_effects {
+                let show =
+                    |value: &Option<String>| value.clone().unwrap_or_else(|| "(unset)".to_string());
+                writeln!(f, "  {}: {} -> {}", name, show(old), show(new))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A minimal set of other user values that give `symbol` the value
+/// `value`: none of them can be left out, though a different, smaller set
+/// may exist
+/// Returns: None if the symbol is unknown, the value invalid, or no
+/// assignments were found
+pub fn solve(engine: &ConfigEngine, symbol: &str, value: &str) -> Option<Solution> {
+    let symbol_type = engine.resolver().symbol_type(symbol)?;
+    let value = normalize_value(symbol_type, value).ok()?;
+    let solution = |assignments: Vec<(String, String)>| Solution {
+        symbol: symbol.to_string(),
+        value: value.clone(),
+        side_effects: side_effects(engine, symbol, &value, &assignments),
+        assignments,
+    };
+    if engine.value(symbol).as_deref() == Some(value.as_str()) {
+        return Some(solution(Vec::new()));
+    }
+
+    let mut planner = Planner {
+        engine,
+        visiting: HashSet::new(),
+        budget: MAX_STEPS,
+    };
+    let plan = match symbol_type {
+        SymbolType::Bool | SymbolType::Tristate => match Tristate::from_value(&value) {
+            Tristate::No => planner.lower(symbol),
+            raised => planner.raise(symbol, raised),
+        },
+        _ => planner.assign(symbol, &value),
+    }?;
+
+    // The target itself is set last
+    let mut assignments: Vec<(String, String)> = plan
+        .into_iter()
+        .filter(|(name, _)| name != symbol)
+        .collect();
+    if !reaches(engine, &assignments, symbol, &value) {
+        return None;
+    }
+    for index in (0..assignments.len()).rev() {
+        let mut fewer = assignments.clone();
+        fewer.remove(index);
+        if reaches(engine, &fewer, symbol, &value) {
+            assignments = fewer;
+        }
+    }
+
+    Some(solution(assignments))
+}
+
+/// Planning stops after this many steps, so large trees stay responsive
+const MAX_STEPS: usize = 10_000;
+
+/// User values in the order to set them
+type Plan = Vec<(String, String)>;
+
+/// `engine` with `assignments` and then the target applied
+fn applied(
+    engine: &ConfigEngine,
+    assignments: &[(String, String)],
+    symbol: &str,
+    value: &str,
+) -> ConfigEngine {
+    let mut engine = engine.clone();
+    engine.set_user_values(trial(assignments, symbol, value));
+    engine
+}
+
+/// `assignments` followed by the target
+fn trial(assignments: &[(String, String)], symbol: &str, value: &str) -> Vec<(String, String)> {
+    let mut values = assignments.to_vec();
+    values.push((symbol.to_string(), value.to_string()));
+    values
+}
+
+fn reaches(
+    engine: &ConfigEngine,
+    assignments: &[(String, String)],
+    symbol: &str,
+    value: &str,
+) -> bool {
+    applied(engine, assignments, symbol, value)
+        .value(symbol)
+        .as_deref()
+        == Some(value)
+}
+
+fn side_effects(
+    engine: &ConfigEngine,
+    symbol: &str,
+    value: &str,
+    assignments: &[(String, String)],
+) -> Vec<(String, Option<String>, Option<String>)> {
+    let after = applied(engine, assignments, symbol, value);
+    engine
+        .resolver()
+        .symbols()
+        .iter()
+        .filter(|name| {
+            *name != symbol && !assignments.iter().any(|(assigned, _)| assigned == *name)
+        })
+        .filter_map(|name| {
+            let (old, new) = (engine.value(name), after.value(name));
+            (old != new).then(|| (name.clone(), old, new))
+        })
+        .collect()
+}
+
+/// All of `plans`, merged in order
+/// Returns: None if one is missing or two assign a symbol differently
+fn all(plans: impl IntoIterator<Item = Option<Plan>>) -> Option<Plan> {
+    let mut merged: Plan = Vec::new();
+    for plan in plans {
+        for (name, value) in plan? {
+            match merged.iter().find(|(assigned, _)| *assigned == name) {
+                Some((_, existing)) if *existing != value => return None,
+                Some(_) => {}
+                None => merged.push((name, value)),
+            }
+        }
+    }
+    Some(merged)
+}
+
+/// The shortest of `plans`, the first one on a tie
+fn cheapest(plans: impl IntoIterator<Item = Option<Plan>>) -> Option<Plan> {
+    plans.into_iter().flatten().min_by_key(Vec::len)
+}
+
+struct Planner<'a> {
+    engine: &'a ConfigEngine,
+    /// Symbols being planned for, so dependency cycles end the search
+    visiting: HashSet<String>,
+    budget: usize,
+}
+
+impl Planner<'_> {
+    fn resolver(&self) -> &DependencyResolver {
+        self.engine.resolver()
+    }
+
+    fn current(&self, name: &str) -> Tristate {
+        Tristate::from_value(&self.engine.value(name).unwrap_or_default())
+    }
+
+    fn eval(&self, expr: &Expr) -> Tristate {
+        evaluate_expr(expr, self.engine.symbols()).unwrap_or(Tristate::No)
+    }
+
+    fn is_bool(&self, name: &str) -> bool {
+        matches!(
+            self.resolver().symbol_type(name),
+            Some(SymbolType::Bool | SymbolType::Tristate)
+        )
+    }
+
+    /// Start planning for `name`
+    /// Returns: false if it is already being planned for or the budget is spent
+    fn enter(&mut self, name: &str) -> bool {
+        if self.budget == 0 || self.visiting.contains(name) {
+            return false;
+        }
+        self.budget -= 1;
+        self.visiting.insert(name.to_string());
+        true
+    }
+
+    fn leave(&mut self, name: &str) {
+        self.visiting.remove(name);
+    }
+
+    /// Assignments that make its dependencies and one of its prompts true
+    fn prompt_routes(&mut self, name: &str) -> Vec<Option<Plan>> {
+        let deps = self.resolver().direct_dependencies(name).cloned();
+        let conditions = self.resolver().prompt_conditions(name).to_vec();
+        conditions
+            .iter()
+            .map(|condition| {
+                all([
+                    self.satisfy_opt(deps.as_ref(), Tristate::Yes),
+                    self.satisfy_opt(condition.as_ref(), Tristate::Yes),
+                ])
+            })
+            .collect()
+    }
+
+    /// Assignments that make bool or tristate `name` at least `value`
+    fn raise(&mut self, name: &str, value: Tristate) -> Option<Plan> {
+        if self.current(name) >= value {
+            return Some(Vec::new());
+        }
+        if !self.is_bool(name) || !self.enter(name) {
+            return None;
+        }
+        let value = match self.resolver().symbol_type(name) {
+            Some(SymbolType::Bool) => Tristate::Yes,
+            _ => value,
+        };
+
+        // Set by hand through a visible prompt
+        let mut routes: Vec<Option<Plan>> = self
+            .prompt_routes(name)
+            .into_iter()
+            .map(|route| all([route, Some(vec![(name.to_string(), value.to_string())])]))
+            .collect();
+
+        // Selected by another symbol
+        for selector in self.resolver().selectors(name).to_vec() {
+            let conditions: Vec<Option<Expr>> = self
+                .resolver()
+                .selections(&selector)
+                .iter()
+                .filter(|selection| selection.symbol == name)
+                .map(|selection| selection.condition.clone())
+                .collect();
+            for condition in conditions {
+                let route = all([
+                    self.raise(&selector, value),
+                    self.satisfy_opt(condition.as_ref(), value),
+                ]);
+                routes.push(route);
+            }
+        }
+
+        // Given by a default, unless the user's value overrides it
+        if self.engine.user_value(name).is_none() {
+            let deps = self.resolver().direct_dependencies(name).cloned();
+            for (expr, condition, _) in self.resolver().defaults(name).to_vec() {
+                let route = all([
+                    self.satisfy_opt(deps.as_ref(), value),
+                    self.satisfy(&expr, value),
+                    self.satisfy_opt(condition.as_ref(), value),
+                ]);
+                routes.push(route);
+            }
+        }
+
+        self.leave(name);
+        cheapest(routes)
+    }
+
+    /// Assignments that make bool or tristate `name` `n`
+    fn lower(&mut self, name: &str) -> Option<Plan> {
+        if !self.current(name).is_enabled() {
+            return Some(Vec::new());
+        }
+        if !self.is_bool(name) || !self.enter(name) {
+            return None;
+        }
+
+        // Every select in effect must go
+        let mut unselect = Vec::new();
+        for selector in self.resolver().selectors(name).to_vec() {
+            if !self.current(&selector).is_enabled() {
+                continue;
+            }
+            let conditions: Vec<Option<Expr>> = self
+                .resolver()
+                .selections(&selector)
+                .iter()
+                .filter(|selection| selection.symbol == name)
+                .map(|selection| selection.condition.clone())
+                .collect();
+            for condition in conditions {
+                if condition
+                    .as_ref()
+                    .is_some_and(|c| !self.eval(c).is_enabled())
+                {
+                    continue;
+                }
+                let route = match &condition {
+                    Some(condition) => cheapest([self.lower(&selector), self.falsify(condition)]),
+                    None => self.lower(&selector),
+                };
+                unselect.push(route);
+            }
+        }
+        let unselect = all(unselect);
+
+        // Then set it to `n` by hand, or switch its dependencies off
+        let mut routes: Vec<Option<Plan>> = self
+            .prompt_routes(name)
+            .into_iter()
+            .map(|route| {
+                all([
+                    unselect.clone(),
+                    route,
+                    Some(vec![(name.to_string(), "n".to_string())]),
+                ])
+            })
+            .collect();
+        if let Some(deps) = self.resolver().direct_dependencies(name).cloned() {
+            routes.push(all([unselect.clone(), self.falsify(&deps)]));
+        }
+        // Without a user value, default or imply, the selects were all
+        // that kept it on
+        let symbols = self.engine.symbols();
+        let unset = self.engine.user_value(name).is_none()
+            && self
+                .resolver()
+                .active_default(name, symbols)
+                .is_none_or(|default| !default.value(symbols).is_enabled())
+            && !self.resolver().implied_value(name, symbols).is_enabled();
+        if unset {
+            routes.push(unselect);
+        }
+
+        self.leave(name);
+        cheapest(routes)
+    }
+
+    /// Assignments that give int, hex or string `name` the value `value`
+    fn assign(&mut self, name: &str, value: &str) -> Option<Plan> {
+        if self.engine.value(name).as_deref() == Some(value) {
+            return Some(Vec::new());
+        }
+        if !self.enter(name) {
+            return None;
+        }
+        let routes: Vec<Option<Plan>> = self
+            .prompt_routes(name)
+            .into_iter()
+            .map(|route| all([route, Some(vec![(name.to_string(), value.to_string())])]))
+            .collect();
+        self.leave(name);
+        cheapest(routes)
+    }
+
+    fn satisfy_opt(&mut self, expr: Option<&Expr>, value: Tristate) -> Option<Plan> {
+        match expr {
+            Some(expr) => self.satisfy(expr, value),
+            None => Some(Vec::new()),
+        }
+    }
+
+    /// Assignments that make `expr` at least `value`
+    fn satisfy(&mut self, expr: &Expr, value: Tristate) -> Option<Plan> {
+        if self.eval(expr) >= value {
+            return Some(Vec::new());
+        }
+        match expr {
+            Expr::Symbol(name) if self.resolver().symbol_type(name).is_some() => {
+                self.raise(name, value)
+            }
+            Expr::And(left, right) => all([self.satisfy(left, value), self.satisfy(right, value)]),
+            Expr::Or(left, right) => {
+                cheapest([self.satisfy(left, value), self.satisfy(right, value)])
+            }
+            Expr::Not(inner) => self.falsify(inner),
+            Expr::Equal(left, right) => {
+                let (name, constant) = self.comparison(left, right)?;
+                match constant.as_str() {
+                    "n" if self.is_bool(&name) => self.lower(&name),
+                    "y" | "m" if self.is_bool(&name) => {
+                        self.raise(&name, Tristate::from_value(&constant))
+                    }
+                    _ if self.is_bool(&name) => None,
+                    _ => self.assign(&name, &constant),
+                }
+            }
+            Expr::NotEqual(left, right) => {
+                let (name, constant) = self.comparison(left, right)?;
+                match constant.as_str() {
+                    "n" if self.is_bool(&name) => self.raise(&name, Tristate::Module),
+                    "y" if self.is_bool(&name) => self.lower(&name),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Assignments that make `expr` `n`
+    fn falsify(&mut self, expr: &Expr) -> Option<Plan> {
+        if !self.eval(expr).is_enabled() {
+            return Some(Vec::new());
+        }
+        match expr {
+            Expr::Symbol(name) if self.resolver().symbol_type(name).is_some() => self.lower(name),
+            Expr::And(left, right) => cheapest([self.falsify(left), self.falsify(right)]),
+            Expr::Or(left, right) => all([self.falsify(left), self.falsify(right)]),
+            Expr::Not(inner) => self.satisfy(inner, Tristate::Yes),
+            Expr::Equal(left, right) => {
+                let (name, constant) = self.comparison(left, right)?;
+                match constant.as_str() {
+                    "n" if self.is_bool(&name) => self.raise(&name, Tristate::Module),
+                    "y" if self.is_bool(&name) => self.lower(&name),
+                    _ => None,
+                }
+            }
+            Expr::NotEqual(left, right) => {
+                self.satisfy(&Expr::Equal(left.clone(), right.clone()), Tristate::Yes)
+            }
+            _ => None,
+        }
+    }
+
+    /// The declared symbol and the constant of a comparison, in either order
+    fn comparison(&self, left: &Expr, right: &Expr) -> Option<(String, String)> {
+        let declared = |expr: &Expr| match expr {
+            Expr::Symbol(name) if self.resolver().symbol_type(name).is_some() => Some(name.clone()),
+            _ => None,
+        };
+        let constant = |expr: &Expr| match expr {
+            Expr::Const(value) => Some(value.clone()),
+            Expr::Symbol(name) if self.resolver().symbol_type(name).is_none() => Some(name.clone()),
+            _ => None,
+        };
+        declared(left)
+            .zip(constant(right))
+            .or_else(|| declared(right).zip(constant(left)))
+    }
+}
@@ -1,41 +1,135 @@
+use crate::config::output::{is_unchanged, write_atomically, write_if_changed};
 use crate::error::Result;
-use crate::kconfig::SymbolTable;
-use std::fs::File;
+use crate::kconfig::{quote_string, LayoutItem, Symbol, SymbolTable, SymbolType};
+use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct ConfigWriter;
 
 impl ConfigWriter {
-    pub fn write(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<()> {
-        let mut file = File::create(path)?;
+    /// Write a .config in Kconfig declaration order, each menu under a
+    /// `#`-framed header with its title, as Linux does. Only symbols that
+    /// are visible or have a value other than `n` are written, and menus
+    /// without any of them are left out.
+    ///
+    /// An unchanged file is left alone. Otherwise the previous file is
+    /// kept as `NAME.old`, as Linux keeps `.config.old`, and the new one
+    /// replaces it atomically.
+    ///
+    /// Values are written as they are; `symbols` should be computed by
+    /// `ConfigEngine`, which keeps at most one option of a choice at `y`.
+    /// Returns: whether the file was written
+    pub fn write(path: impl AsRef<Path>, symbols: &SymbolTable) -> Result<bool> {
+        let path = path.as_ref();
+        let mut out = Vec::new();
 
-        writeln!(file, "#")?;
-        writeln!(file, "# Automatically generated file; DO NOT EDIT.")?;
-        writeln!(file, "# Rust Kbuild Configuration")?;
-        writeln!(file, "#")?;
+        writeln!(out, "#")?;
+        writeln!(out, "# Automatically generated file; DO NOT EDIT.")?;
+        writeln!(out, "# Rust Kbuild Configuration")?;
+        writeln!(out, "#")?;
 
-        for (name, symbol) in symbols.all_symbols() {
-            // Strip CONFIG_ prefix if present
-            let clean_name = name.strip_prefix("CONFIG_").unwrap_or(name);
-            
-            if let Some(value) = &symbol.value {
-                match value.as_str() {
-                    "y" | "m" => {
-                        writeln!(file, "{}={}", clean_name, value)?;
-                    }
-                    "n" => {
-                        writeln!(file, "# {} is not set", clean_name)?;
-                    }
-                    _ => {
-                        writeln!(file, "{}=\"{}\"", clean_name, value)?;
+        // Menus entered, and whether their header is written yet
+        let mut menus: Vec<(String, bool)> = Vec::new();
+        let mut need_newline = false;
+
+        for item in symbols.layout() {
+            let name = match item {
+                LayoutItem::MenuStart(title) => {
+                    menus.push((title, false));
+                    continue;
+                }
+                LayoutItem::MenuEnd(_) => {
+                    if let Some((title, true)) = menus.pop() {
+                        writeln!(out, "# end of {}", title)?;
+                        need_newline = true;
                     }
+                    continue;
                 }
+                LayoutItem::Symbol(name) => name,
+            };
+            let Some(symbol) = symbols.get_symbol(&name).filter(|s| Self::is_written(s)) else {
+                continue;
+            };
+
+            // Headers of the menus this is the first written symbol of
+            for (title, written) in menus.iter_mut().filter(|(_, written)| !*written) {
+                write!(out, "\n#\n# {}\n#\n", title)?;
+                *written = true;
+                need_newline = false;
+            }
+            if need_newline {
+                writeln!(out)?;
+                need_newline = false;
+            }
+
+            // Strip CONFIG_ prefix if present
+            let clean_name = name.strip_prefix("CONFIG_").unwrap_or(&name);
+
+            if let Some(value) = &symbol.value {
+                writeln!(
+                    out,
+                    "{}",
+                    Self::format_assignment(clean_name, &symbol.symbol_type, value)
+                )?;
             } else {
-                writeln!(file, "# {} is not set", clean_name)?;
+                writeln!(out, "# {} is not set", clean_name)?;
             }
         }
 
-        Ok(())
+        if is_unchanged(path, &out) {
+            return Ok(false);
+        }
+        if path.exists() {
+            fs::copy(path, Self::backup_path(path))?;
+        }
+        write_atomically(path, &out)?;
+        Ok(true)
+    }
+
+    /// Where `write` keeps the previous version of `path`
+    pub fn backup_path(path: impl AsRef<Path>) -> PathBuf {
+        let mut backup = path.as_ref().as_os_str().to_owned();
+        backup.push(".old");
+        PathBuf::from(backup)
+    }
+
+    fn is_written(symbol: &Symbol) -> bool {
+        !symbol.is_transitional
+            && (symbol.visible || symbol.value.as_deref().is_some_and(|v| v != "n"))
+    }
+
+    /// Write a minimal defconfig, one assignment per line in the given order,
+    /// formatted by the types in `symbols`; symbols it does not declare are
+    /// taken as bool
+    /// Returns: whether the file was written
+    pub fn write_defconfig(
+        path: impl AsRef<Path>,
+        assignments: &[(String, String)],
+        symbols: &SymbolTable,
+    ) -> Result<bool> {
+        let mut out = Vec::new();
+
+        for (name, value) in assignments {
+            let symbol_type = symbols
+                .get_symbol(name)
+                .map_or(&SymbolType::Bool, |s| &s.symbol_type);
+            writeln!(out, "{}", Self::format_assignment(name, symbol_type, value))?;
+        }
+
+        write_if_changed(path, &out)
+    }
+
+    /// Format a single `.config` line for a symbol value: strings are
+    /// quoted, int and hex values written bare, and only bool and tristate
+    /// symbols can be `not set`
+    pub fn format_assignment(name: &str, symbol_type: &SymbolType, value: &str) -> String {
+        match symbol_type {
+            SymbolType::Bool | SymbolType::Tristate if value == "n" => {
+                format!("# {} is not set", name)
+            }
+            SymbolType::String => format!("{}={}", name, quote_string(value)),
+            _ => format!("{}={}", name, value),
+        }
     }
 }
@@ -1,3 +1,4 @@
+use crate::kconfig::diagnostic::DiagnosticCode;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -10,6 +11,7 @@ pub enum KconfigError {
     Syntax {
         file: PathBuf,
         line: usize,
+        code: DiagnosticCode,
         message: String,
     },
 
@@ -34,6 +36,13 @@ pub enum KconfigError {
     #[error("Config error: {0}")]
     Config(String),
 
+    #[error("Macro error at {file}:{line}: {message}")]
+    Macro {
+        file: PathBuf,
+        line: usize,
+        message: String,
+    },
+
     #[error("Recursive source inclusion detected: {chain}")]
     RecursiveSource { chain: String },
 }
@@ -1,5 +1,101 @@
+use crate::kconfig::value::{parse_hex, parse_int};
+use std::fmt;
+use std::ops::Deref;
 use std::path::PathBuf;
 
+/// Where a node sits in the Kconfig sources: the file, the line and column
+/// it starts at (both 1-based) and the line it ends on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+/// The kind of attribute line a [`PropertySpan`] covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    /// `bool`, `tristate`, `string`, `int` or `hex`, with or without a prompt
+    Type,
+    /// `def_bool` or `def_tristate`
+    DefType,
+    Prompt,
+    Default,
+    Depends,
+    Select,
+    Imply,
+    Range,
+    Option,
+    Modules,
+    Transitional,
+    Optional,
+    Visible,
+    Help,
+    /// The condition of an `if` block
+    Condition,
+}
+
+/// The location of one attribute line
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySpan {
+    pub kind: PropertyKind,
+    pub span: Span,
+}
+
+/// A node and where it is written. Nodes built outside the parser have an
+/// empty span. Two nodes are equal if they say the same thing, wherever
+/// they are written.
+#[derive(Debug, Clone, Default)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Self { node, span }
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Self {
+        Self::new(node, Span::default())
+    }
+}
+
+impl From<Expr> for Box<Spanned<Expr>> {
+    fn from(expr: Expr) -> Self {
+        Box::new(expr.into())
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Spanned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SymbolType {
     Bool,
@@ -9,43 +105,160 @@ pub enum SymbolType {
     Hex,
 }
 
+/// An expression node; every operand carries its own span
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Symbol(String),
     Const(String),
-    Not(Box<Expr>),
-    And(Box<Expr>, Box<Expr>),
-    Or(Box<Expr>, Box<Expr>),
-    Equal(Box<Expr>, Box<Expr>),
-    NotEqual(Box<Expr>, Box<Expr>),
-    Less(Box<Expr>, Box<Expr>),
-    LessEqual(Box<Expr>, Box<Expr>),
-    Greater(Box<Expr>, Box<Expr>),
-    GreaterEqual(Box<Expr>, Box<Expr>),
+    Not(Box<Spanned<Expr>>),
+    And(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Or(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Equal(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    NotEqual(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Less(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    LessEqual(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Greater(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    GreaterEqual(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
 }
 
-#[derive(Debug, Clone)]
+impl Spanned<Expr> {
+    /// Every symbol named in the expression, and where, in source order
+    pub fn symbols(&self) -> Vec<(&str, &Span)> {
+        fn collect<'e>(expr: &'e Spanned<Expr>, symbols: &mut Vec<(&'e str, &'e Span)>) {
+            match &expr.node {
+                Expr::Symbol(name) => symbols.push((name, &expr.span)),
+                Expr::Const(_) => {}
+                Expr::Not(inner) => collect(inner, symbols),
+                Expr::And(l, r)
+                | Expr::Or(l, r)
+                | Expr::Equal(l, r)
+                | Expr::NotEqual(l, r)
+                | Expr::Less(l, r)
+                | Expr::LessEqual(l, r)
+                | Expr::Greater(l, r)
+                | Expr::GreaterEqual(l, r) => {
+                    collect(l, symbols);
+                    collect(r, symbols);
+                }
+            }
+        }
+
+        let mut symbols = Vec::new();
+        collect(self, &mut symbols);
+        symbols
+    }
+}
+
+impl Expr {
+    /// Binding strength, loosest first, for printing parentheses
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(..) => 1,
+            Expr::And(..) => 2,
+            Expr::Equal(..)
+            | Expr::NotEqual(..)
+            | Expr::Less(..)
+            | Expr::LessEqual(..)
+            | Expr::Greater(..)
+            | Expr::GreaterEqual(..) => 3,
+            Expr::Not(_) => 4,
+            Expr::Symbol(_) | Expr::Const(_) => 5,
+        }
+    }
+}
+
+/// Prints Kconfig syntax, with parentheses only where needed
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter, expr: &Expr, min: u8| {
+            if expr.precedence() < min {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        };
+        let (left, op, right, min) = match self {
+            Expr::Symbol(name) => return write!(f, "{}", name),
+            Expr::Const(value) if is_bare_number(value) => return write!(f, "{}", value),
+            Expr::Const(value) => return write!(f, "{:?}", value),
+            Expr::Not(inner) => {
+                write!(f, "!")?;
+                return operand(f, inner, 4);
+            }
+            Expr::Or(l, r) => (l, "||", r, 1),
+            Expr::And(l, r) => (l, "&&", r, 2),
+            Expr::Equal(l, r) => (l, "=", r, 4),
+            Expr::NotEqual(l, r) => (l, "!=", r, 4),
+            Expr::Less(l, r) => (l, "<", r, 4),
+            Expr::LessEqual(l, r) => (l, "<=", r, 4),
+            Expr::Greater(l, r) => (l, ">", r, 4),
+            Expr::GreaterEqual(l, r) => (l, ">=", r, 4),
+        };
+        operand(f, left, min)?;
+        write!(f, " {} ", op)?;
+        operand(f, right, min)
+    }
+}
+
+/// Whether `value` reads back as the same constant without quotes: a
+/// decimal number as it prints, or `0x` and hexadecimal digits
+fn is_bare_number(value: &str) -> bool {
+    match value.strip_prefix("0x") {
+        Some(_) => parse_hex(value).is_some(),
+        None => parse_int(value).is_some_and(|n| n >= 0 && n.to_string() == value),
+    }
+}
+
+/// The `if` condition on an attribute line, if it has one
+pub type Condition = Option<Spanned<Expr>>;
+
+#[derive(Debug, Clone, Default)]
 pub struct Property {
     pub prompt: Option<String>,
-    pub default: Option<Expr>,
-    pub depends: Option<Expr>,
-    pub select: Vec<(String, Option<Expr>)>,
-    pub imply: Vec<(String, Option<Expr>)>,
-    pub range: Option<(Expr, Expr, Option<Expr>)>,
+    /// The `if` condition of the prompt; the prompt is hidden while it is `n`
+    pub prompt_condition: Option<Spanned<Expr>>,
+    /// `default` and `def_bool` lines in order, each with its `if`
+    /// condition and the span of the line
+    pub defaults: Vec<(Spanned<Expr>, Condition, Span)>,
+    /// The `depends on` lines, ANDed
+    pub depends: Option<Spanned<Expr>>,
+    /// `select` lines in order, each with its `if` condition and the span
+    /// of the line
+    pub select: Vec<(String, Condition, Span)>,
+    pub imply: Vec<(String, Condition, Span)>,
+    /// `range` lines in order, each with its `if` condition and the span
+    /// of the line; the first whose condition holds applies
+    pub ranges: Vec<(Spanned<Expr>, Spanned<Expr>, Condition, Span)>,
     pub help: Option<String>,
+    pub allnoconfig_y: bool,
+    /// `option modules`: this symbol decides whether `m` is allowed
+    pub modules: bool,
+    /// `option env="VAR"`: default to the environment variable's value
+    pub env: Option<String>,
+    /// Read from an old .config to migrate it, but never shown or written
+    pub transitional: bool,
+    /// Locations of the attribute lines, in source order
+    pub spans: Vec<PropertySpan>,
 }
 
-impl Default for Property {
-    fn default() -> Self {
-        Self {
-            prompt: None,
-            default: None,
-            depends: None,
-            select: Vec::new(),
-            imply: Vec::new(),
-            range: None,
-            help: None,
+impl Property {
+    /// Every expression on the attribute lines
+    pub fn exprs(&self) -> Vec<&Spanned<Expr>> {
+        let mut exprs: Vec<&Spanned<Expr>> = Vec::new();
+        exprs.extend(&self.prompt_condition);
+        for (value, condition, _) in &self.defaults {
+            exprs.push(value);
+            exprs.extend(condition);
+        }
+        exprs.extend(&self.depends);
+        for (_, condition, _) in self.select.iter().chain(&self.imply) {
+            exprs.extend(condition);
         }
+        for (low, high, condition, _) in &self.ranges {
+            exprs.extend([low, high]);
+            exprs.extend(condition);
+        }
+        exprs
     }
 }
 
@@ -54,6 +267,7 @@ pub struct Config {
     pub name: String,
     pub symbol_type: SymbolType,
     pub properties: Property,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -61,41 +275,87 @@ pub struct MenuConfig {
     pub name: String,
     pub symbol_type: SymbolType,
     pub properties: Property,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Choice {
     pub name: Option<String>,
     pub prompt: Option<String>,
+    pub prompt_condition: Option<Spanned<Expr>>,
     pub symbol_type: SymbolType,
-    pub default: Option<String>,
-    pub depends: Option<Expr>,
-    pub options: Vec<Config>,
+    /// `default` lines in order, each with its `if` condition and the span
+    /// of the line
+    pub defaults: Vec<(String, Condition, Span)>,
+    pub depends: Option<Spanned<Expr>>,
+    /// `optional`: the choice may have no option selected
+    pub optional: bool,
+    pub help: Option<String>,
+    /// The options, with the comments and `if` blocks around them
+    pub entries: Vec<Entry>,
+    pub span: Span,
+    pub spans: Vec<PropertySpan>,
+}
+
+impl Choice {
+    /// Every option, those inside `if` blocks included, in order
+    pub fn options(&self) -> Vec<&Config> {
+        fn collect<'a>(entries: &'a [Entry], options: &mut Vec<&'a Config>) {
+            for entry in entries {
+                match entry {
+                    Entry::Config(config) => options.push(config),
+                    Entry::If(if_block) => collect(&if_block.entries, options),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut options = Vec::new();
+        collect(&self.entries, &mut options);
+        options
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Menu {
     pub title: String,
-    pub depends: Option<Expr>,
-    pub visible: Option<Expr>,
+    pub depends: Option<Spanned<Expr>>,
+    pub visible: Option<Spanned<Expr>>,
     pub entries: Vec<Entry>,
+    pub span: Span,
+    pub spans: Vec<PropertySpan>,
 }
 
 #[derive(Debug, Clone)]
 pub struct If {
-    pub condition: Expr,
+    pub condition: Spanned<Expr>,
     pub entries: Vec<Entry>,
+    pub span: Span,
+    pub spans: Vec<PropertySpan>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Source {
     pub path: PathBuf,
+    /// `osource`/`orsource`: a missing file is not an error
+    pub optional: bool,
+    /// `rsource`/`orsource`: the path is relative to the including file
+    pub relative: bool,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Comment {
     pub text: String,
-    pub depends: Option<Expr>,
+    pub depends: Option<Spanned<Expr>>,
+    pub span: Span,
+    pub spans: Vec<PropertySpan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MainMenu {
+    pub title: String,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -107,7 +367,23 @@ pub enum Entry {
     If(If),
     Source(Source),
     Comment(Comment),
-    MainMenu(String),
+    MainMenu(MainMenu),
+}
+
+impl Entry {
+    /// Where the entry is written, from its keyword to its last line
+    pub fn span(&self) -> &Span {
+        match self {
+            Entry::Config(config) => &config.span,
+            Entry::MenuConfig(menuconfig) => &menuconfig.span,
+            Entry::Choice(choice) => &choice.span,
+            Entry::Menu(menu) => &menu.span,
+            Entry::If(if_block) => &if_block.span,
+            Entry::Source(source) => &source.span,
+            Entry::Comment(comment) => &comment.span,
+            Entry::MainMenu(main_menu) => &main_menu.span,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
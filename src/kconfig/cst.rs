@@ -0,0 +1,465 @@
+//! Lossless, line-structured syntax tree of one Kconfig file.
+//!
+//! Unlike [`KconfigFile`](crate::kconfig::KconfigFile), the tree keeps
+//! every character of the source: comments, blank lines, indentation and
+//! the original spelling of each token, so printing it gives back the
+//! file byte for byte. Macros are not expanded and `source` is not
+//! followed. Files that do not parse still produce a tree.
+
+use crate::kconfig::preprocess::is_assignment;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keywords, symbol names, numbers and macro references
+    Word,
+    /// A quoted string, quotes included
+    String,
+    /// `&&`, `||`, `!`, comparisons and parentheses
+    Operator,
+    /// A `#` comment up to the end of the line
+    Comment,
+    /// The raw content of a help text or macro assignment line
+    Text,
+}
+
+/// A token and the whitespace before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub kind: TokenKind,
+    pub leading: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    /// A macro variable assignment, `NAME := value`
+    Assignment,
+    /// `config`, `menuconfig`, `comment`, `source` or `mainmenu`
+    Entry,
+    /// `menu`, `if` or `choice`
+    BlockStart,
+    /// `endmenu`, `endif` or `endchoice`
+    BlockEnd,
+    /// An attribute of the entry or block above
+    Property,
+    /// The `help` keyword
+    Help,
+    /// A line of help text, blank lines included
+    HelpText,
+}
+
+/// One source line: its tokens, the whitespace after the last one, and
+/// whether it ends with a newline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxLine {
+    pub kind: LineKind,
+    /// 1-based line number in the file
+    pub number: usize,
+    pub tokens: Vec<SyntaxToken>,
+    pub trailing: String,
+    pub newline: bool,
+}
+
+impl SyntaxLine {
+    /// The first word of the line, which decides its kind
+    pub fn keyword(&self) -> Option<&str> {
+        self.tokens
+            .first()
+            .filter(|t| t.kind == TokenKind::Word)
+            .map(|t| t.text.as_str())
+    }
+
+    /// The tokens without a trailing comment
+    pub fn code(&self) -> &[SyntaxToken] {
+        match self.tokens.last() {
+            Some(last) if last.kind == TokenKind::Comment => &self.tokens[..self.tokens.len() - 1],
+            _ => &self.tokens,
+        }
+    }
+
+    /// The trailing comment, if any
+    pub fn comment(&self) -> Option<&SyntaxToken> {
+        self.tokens.last().filter(|t| t.kind == TokenKind::Comment)
+    }
+}
+
+impl fmt::Display for SyntaxLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "{}{}", token.leading, token.text)?;
+        }
+        write!(f, "{}", self.trailing)?;
+        if self.newline {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxNode {
+    /// A blank, comment or assignment line between entries, or an end
+    /// keyword that closes no block
+    Line(SyntaxLine),
+    /// An entry and its attribute lines, including comments between them
+    /// and its help text
+    Entry {
+        header: SyntaxLine,
+        body: Vec<SyntaxLine>,
+    },
+    /// A `menu`, `if` or `choice` block: its header and attribute lines,
+    /// the nodes inside it, and its end line unless the file ends first
+    Block {
+        header: SyntaxLine,
+        body: Vec<SyntaxLine>,
+        children: Vec<SyntaxNode>,
+        end: Option<SyntaxLine>,
+    },
+}
+
+impl SyntaxNode {
+    /// Every line of the node, in source order
+    pub fn lines(&self) -> Vec<&SyntaxLine> {
+        match self {
+            SyntaxNode::Line(line) => vec![line],
+            SyntaxNode::Entry { header, body } => std::iter::once(header).chain(body).collect(),
+            SyntaxNode::Block {
+                header,
+                body,
+                children,
+                end,
+            } => std::iter::once(header)
+                .chain(body)
+                .chain(children.iter().flat_map(|child| child.lines()))
+                .chain(end)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxTree {
+    pub path: PathBuf,
+    pub nodes: Vec<SyntaxNode>,
+}
+
+impl SyntaxTree {
+    /// Build the tree of `source`, read from `path`
+    pub fn parse(source: &str, path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            nodes: build_nodes(split_lines(source)),
+        }
+    }
+
+    /// Every line of the file, in source order
+    pub fn lines(&self) -> Vec<&SyntaxLine> {
+        self.nodes.iter().flat_map(|node| node.lines()).collect()
+    }
+}
+
+/// Prints the source the tree was built from, unchanged
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.lines() {
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split and classify lines. Help text follows the lexer: after a `help`
+/// keyword, every empty or indented line belongs to it.
+fn split_lines(source: &str) -> Vec<SyntaxLine> {
+    let mut lines = Vec::new();
+    let mut in_help = false;
+
+    for (index, raw) in source.split_inclusive('\n').enumerate() {
+        let number = index + 1;
+        let newline = raw.ends_with('\n');
+        let text = raw.strip_suffix('\n').unwrap_or(raw);
+
+        if in_help {
+            if text.is_empty() || text.starts_with([' ', '\t']) {
+                lines.push(text_line(LineKind::HelpText, number, text, newline));
+                continue;
+            }
+            in_help = false;
+        }
+
+        if is_assignment(text) {
+            lines.push(text_line(LineKind::Assignment, number, text, newline));
+            continue;
+        }
+
+        let (tokens, trailing) = tokenize(text);
+        in_help = tokens
+            .iter()
+            .any(|t| t.kind == TokenKind::Word && t.text == "help");
+        let mut line = SyntaxLine {
+            kind: LineKind::Blank,
+            number,
+            tokens,
+            trailing,
+            newline,
+        };
+        line.kind = match (line.tokens.first().map(|t| t.kind), line.keyword()) {
+            (None, _) => LineKind::Blank,
+            (Some(TokenKind::Comment), _) => LineKind::Comment,
+            (_, Some(keyword)) => classify(keyword),
+            _ => LineKind::Property,
+        };
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn classify(keyword: &str) -> LineKind {
+    match keyword {
+        "config" | "menuconfig" | "comment" | "source" | "osource" | "rsource" | "orsource"
+        | "mainmenu" => LineKind::Entry,
+        "menu" | "if" | "choice" => LineKind::BlockStart,
+        "endmenu" | "endif" | "endchoice" => LineKind::BlockEnd,
+        "help" => LineKind::Help,
+        _ => LineKind::Property,
+    }
+}
+
+/// A line kept as one token: its indentation, its text and its trailing
+/// whitespace
+fn text_line(kind: LineKind, number: usize, text: &str, newline: bool) -> SyntaxLine {
+    let content = text.trim_start_matches([' ', '\t']);
+    let leading = &text[..text.len() - content.len()];
+    let body = content.trim_end();
+    let tokens = if body.is_empty() {
+        Vec::new()
+    } else {
+        vec![SyntaxToken {
+            kind: TokenKind::Text,
+            leading: leading.to_string(),
+            text: body.to_string(),
+        }]
+    };
+    let trailing = if tokens.is_empty() {
+        text
+    } else {
+        &content[body.len()..]
+    };
+
+    SyntaxLine {
+        kind: if tokens.is_empty() && kind == LineKind::Assignment {
+            LineKind::Blank
+        } else {
+            kind
+        },
+        number,
+        tokens,
+        trailing: trailing.to_string(),
+        newline,
+    }
+}
+
+const OPERATORS: [&str; 13] = [
+    "&&", "||", "!=", "<=", ">=", "==", "=", "<", ">", "!", "(", ")", "&",
+];
+
+/// Split one line into tokens, keeping the whitespace before each token
+/// and after the last one
+fn tokenize(text: &str) -> (Vec<SyntaxToken>, String) {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    loop {
+        let after_space = rest.trim_start_matches([' ', '\t', '\r']);
+        let leading = rest[..rest.len() - after_space.len()].to_string();
+        rest = after_space;
+        if rest.is_empty() {
+            return (tokens, leading);
+        }
+
+        let (kind, len) = if rest.starts_with('#') {
+            (TokenKind::Comment, rest.trim_end().len())
+        } else if rest.starts_with(['"', '\'']) {
+            (TokenKind::String, string_length(rest))
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            (TokenKind::Operator, op.len())
+        } else if rest.starts_with('|') {
+            (TokenKind::Operator, 1)
+        } else {
+            (TokenKind::Word, word_length(rest))
+        };
+
+        tokens.push(SyntaxToken {
+            kind,
+            leading,
+            text: rest[..len].to_string(),
+        });
+        rest = &rest[len..];
+    }
+}
+
+/// Length of the quoted string at the start of `text`, up to the closing
+/// quote or the end of the line
+fn string_length(text: &str) -> usize {
+    let quote = text.chars().next().unwrap_or('"');
+    let mut escaped = false;
+    for (i, ch) in text.char_indices().skip(1) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if ch == quote => return i + 1,
+            _ => {}
+        }
+    }
+    text.trim_end().len()
+}
+
+/// Length of the word at the start of `text`. `$(...)` references are
+/// part of the word, parentheses included.
+fn word_length(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'$' if bytes.get(i + 1) == Some(&b'(') => {
+                depth += 1;
+                i += 1;
+            }
+            b')' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            b' ' | b'\t' | b'\r' | b'"' | b'\'' | b'#' | b'!' | b'=' | b'<' | b'>' | b'('
+            | b')' | b'&' | b'|' => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    // A lone character no other token takes
+    i.max(text.chars().next().map_or(0, char::len_utf8))
+}
+
+struct OpenBlock {
+    header: SyntaxLine,
+    body: Vec<SyntaxLine>,
+    children: Vec<SyntaxNode>,
+}
+
+/// Group lines into entries and blocks. Blank and comment lines at the end
+/// of an entry's or a block header's attributes belong to the enclosing
+/// level instead, since they separate entries.
+fn build_nodes(lines: Vec<SyntaxLine>) -> Vec<SyntaxNode> {
+    let mut root = Vec::new();
+    let mut blocks: Vec<OpenBlock> = Vec::new();
+    let mut entry: Option<(SyntaxLine, Vec<SyntaxLine>)> = None;
+
+    fn level<'a>(
+        root: &'a mut Vec<SyntaxNode>,
+        blocks: &'a mut [OpenBlock],
+    ) -> &'a mut Vec<SyntaxNode> {
+        match blocks.last_mut() {
+            Some(block) => &mut block.children,
+            None => root,
+        }
+    }
+
+    fn close_entry(
+        entry: &mut Option<(SyntaxLine, Vec<SyntaxLine>)>,
+        root: &mut Vec<SyntaxNode>,
+        blocks: &mut [OpenBlock],
+    ) {
+        if let Some((header, mut body)) = entry.take() {
+            let trivia = split_trailing_trivia(&mut body);
+            let nodes = level(root, blocks);
+            nodes.push(SyntaxNode::Entry { header, body });
+            nodes.extend(trivia.into_iter().map(SyntaxNode::Line));
+        }
+    }
+
+    // The first child of a block ends its header's attributes
+    fn start_child(blocks: &mut [OpenBlock]) {
+        if let Some(block) = blocks.last_mut() {
+            if block.children.is_empty() {
+                let trivia = split_trailing_trivia(&mut block.body);
+                block
+                    .children
+                    .extend(trivia.into_iter().map(SyntaxNode::Line));
+            }
+        }
+    }
+
+    for line in lines {
+        match line.kind {
+            LineKind::Entry => {
+                close_entry(&mut entry, &mut root, &mut blocks);
+                start_child(&mut blocks);
+                entry = Some((line, Vec::new()));
+            }
+            LineKind::BlockStart => {
+                close_entry(&mut entry, &mut root, &mut blocks);
+                start_child(&mut blocks);
+                blocks.push(OpenBlock {
+                    header: line,
+                    body: Vec::new(),
+                    children: Vec::new(),
+                });
+            }
+            LineKind::BlockEnd => {
+                close_entry(&mut entry, &mut root, &mut blocks);
+                match blocks.pop() {
+                    Some(mut block) => {
+                        if block.children.is_empty() {
+                            let trivia = split_trailing_trivia(&mut block.body);
+                            block
+                                .children
+                                .extend(trivia.into_iter().map(SyntaxNode::Line));
+                        }
+                        level(&mut root, &mut blocks).push(SyntaxNode::Block {
+                            header: block.header,
+                            body: block.body,
+                            children: block.children,
+                            end: Some(line),
+                        });
+                    }
+                    None => root.push(SyntaxNode::Line(line)),
+                }
+            }
+            // Macro assignments stand on their own, between entries
+            LineKind::Assignment => {
+                close_entry(&mut entry, &mut root, &mut blocks);
+                start_child(&mut blocks);
+                level(&mut root, &mut blocks).push(SyntaxNode::Line(line));
+            }
+            _ => match (&mut entry, blocks.last_mut()) {
+                (Some((_, body)), _) => body.push(line),
+                (None, Some(block)) if block.children.is_empty() => block.body.push(line),
+                _ => level(&mut root, &mut blocks).push(SyntaxNode::Line(line)),
+            },
+        }
+    }
+
+    close_entry(&mut entry, &mut root, &mut blocks);
+    while let Some(block) = blocks.pop() {
+        level(&mut root, &mut blocks).push(SyntaxNode::Block {
+            header: block.header,
+            body: block.body,
+            children: block.children,
+            end: None,
+        });
+    }
+    root
+}
+
+/// Remove the blank and comment lines at the end of `body`
+fn split_trailing_trivia(body: &mut Vec<SyntaxLine>) -> Vec<SyntaxLine> {
+    let keep = body
+        .iter()
+        .rposition(|line| !matches!(line.kind, LineKind::Blank | LineKind::Comment))
+        .map_or(0, |i| i + 1);
+    body.split_off(keep)
+}
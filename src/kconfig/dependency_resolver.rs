@@ -0,0 +1,1040 @@
+use crate::kconfig::ast::{Choice, Entry, Expr, Property, PropertyKind, Span, SymbolType};
+use crate::kconfig::expr::{evaluate_expr, Tristate};
+use crate::kconfig::graph::{DependencyCycle, DependencyGraph};
+use crate::kconfig::symbol::{LayoutItem, SymbolTable};
+use crate::kconfig::value::{format_number, parse_number};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub symbol: String,
+    pub condition: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub symbol: String,
+    pub condition: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Implication {
+    pub symbol: String,
+    pub condition: Option<Expr>,
+}
+
+/// A `default` line in effect, with the value of its condition
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveDefault<'a> {
+    pub expr: &'a Expr,
+    pub condition: Tristate,
+    pub span: &'a Span,
+}
+
+impl ActiveDefault<'_> {
+    /// The value the line gives a bool or tristate symbol
+    pub fn value(&self, symbol_table: &SymbolTable) -> Tristate {
+        eval(self.expr, symbol_table).and(self.condition)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChoiceGroup {
+    /// The name of a named choice, which holds the choice's mode
+    pub name: Option<String>,
+    /// `Tristate` if the choice or any of its options is tristate
+    pub symbol_type: SymbolType,
+    pub options: Vec<String>,
+    /// `default` lines in order, each with its `if` condition
+    pub defaults: Vec<(String, Option<Expr>)>,
+    /// An optional choice selects no option unless the user picks one
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyResolver {
+    /// Symbols in Kconfig declaration order
+    symbol_order: Vec<String>,
+
+    /// Symbols and menus in Kconfig declaration order
+    layout: Vec<LayoutItem>,
+
+    /// Map: symbol -> declared type
+    symbol_types: HashMap<String, SymbolType>,
+
+    /// Map: symbol -> where each of its definitions is written
+    definitions: HashMap<String, Vec<Span>>,
+
+    /// Map: symbol -> `default` lines in declaration order, with conditions
+    /// and locations
+    default_map: HashMap<String, Vec<(Expr, Option<Expr>, Span)>>,
+
+    /// Map: symbol -> `range` lines in declaration order, with conditions
+    range_map: HashMap<String, Vec<(Expr, Expr, Option<Expr>)>>,
+
+    /// Choice blocks, each listing its options
+    choice_groups: Vec<ChoiceGroup>,
+
+    /// Symbols marked `option allnoconfig_y`
+    allnoconfig_y: HashSet<String>,
+
+    /// The symbol marked `option modules`, which gates `m`
+    modules_symbol: Option<String>,
+
+    /// Symbols marked `transitional`
+    transitional: HashSet<String>,
+
+    /// Map: symbol -> the `if` condition of each of its prompts
+    prompts: HashMap<String, Vec<Option<Expr>>>,
+
+    /// Map: symbol -> `depends on` expression
+    direct_deps: HashMap<String, Expr>,
+
+    /// Map: symbol -> list of symbols it depends on
+    depends_map: HashMap<String, Vec<Dependency>>,
+
+    /// Map: symbol -> list of symbols it selects
+    select_map: HashMap<String, Vec<Selection>>,
+
+    /// Map: symbol -> list of symbols it implies
+    imply_map: HashMap<String, Vec<Implication>>,
+
+    /// Map: symbol -> list of symbols that select it (reverse dependencies)
+    reverse_select_map: HashMap<String, Vec<String>>,
+
+    /// Map: symbol -> list of symbols that imply it (weak reverse dependencies)
+    reverse_imply_map: HashMap<String, Vec<String>>,
+
+    /// depends/select/imply relations between symbols, with locations
+    graph: DependencyGraph,
+
+    /// Recursive dependencies found in `graph`
+    cycles: Vec<DependencyCycle>,
+}
+
+impl DependencyResolver {
+    pub fn new() -> Self {
+        Self {
+            symbol_order: Vec::new(),
+            layout: Vec::new(),
+            symbol_types: HashMap::new(),
+            definitions: HashMap::new(),
+            default_map: HashMap::new(),
+            range_map: HashMap::new(),
+            choice_groups: Vec::new(),
+            allnoconfig_y: HashSet::new(),
+            modules_symbol: None,
+            transitional: HashSet::new(),
+            prompts: HashMap::new(),
+            direct_deps: HashMap::new(),
+            depends_map: HashMap::new(),
+            select_map: HashMap::new(),
+            imply_map: HashMap::new(),
+            reverse_select_map: HashMap::new(),
+            reverse_imply_map: HashMap::new(),
+            graph: DependencyGraph::default(),
+            cycles: Vec::new(),
+        }
+    }
+
+    /// Build dependency maps from Kconfig AST
+    pub fn build_from_entries(&mut self, entries: &[Entry]) {
+        self.process_entries(entries, None);
+        self.graph.add_entries(entries);
+        self.cycles = self.graph.cycles();
+    }
+
+    /// `parent_deps` holds the conditions of the enclosing `if` blocks,
+    /// menus and choices, which every child inherits
+    fn process_entries(&mut self, entries: &[Entry], parent_deps: Option<&Expr>) {
+        for entry in entries {
+            match entry {
+                Entry::Config(cfg) => {
+                    self.process_config(
+                        &cfg.name,
+                        &cfg.symbol_type,
+                        &cfg.properties,
+                        &cfg.span,
+                        parent_deps,
+                    );
+                }
+                Entry::MenuConfig(mcfg) => {
+                    self.process_config(
+                        &mcfg.name,
+                        &mcfg.symbol_type,
+                        &mcfg.properties,
+                        &mcfg.span,
+                        parent_deps,
+                    );
+                }
+                Entry::Menu(menu) => {
+                    let deps = and_deps(parent_deps, menu.depends.as_deref());
+                    self.layout.push(LayoutItem::MenuStart(menu.title.clone()));
+                    self.process_entries(&menu.entries, deps.as_ref());
+                    self.layout.push(LayoutItem::MenuEnd(menu.title.clone()));
+                }
+                Entry::If(if_block) => {
+                    let deps = and_deps(parent_deps, Some(&if_block.condition.node));
+                    self.process_entries(&if_block.entries, deps.as_ref());
+                }
+                Entry::Choice(choice) => {
+                    let deps = and_deps(parent_deps, choice.depends.as_deref());
+                    self.process_entries(&choice.entries, deps.as_ref());
+                    self.process_choice(choice, deps);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn process_choice(&mut self, choice: &Choice, deps: Option<Expr>) {
+        let is_tristate = choice.symbol_type == SymbolType::Tristate
+            || choice
+                .options()
+                .iter()
+                .any(|o| o.symbol_type == SymbolType::Tristate);
+        let symbol_type = if is_tristate {
+            SymbolType::Tristate
+        } else {
+            SymbolType::Bool
+        };
+        let options: Vec<String> = choice.options().iter().map(|o| o.name.clone()).collect();
+
+        // A named choice is a symbol too, and may be extended elsewhere
+        if let Some(name) = &choice.name {
+            if !self.symbol_types.contains_key(name) {
+                self.symbol_order.push(name.clone());
+                self.layout.push(LayoutItem::Symbol(name.clone()));
+            }
+            self.symbol_types.insert(name.clone(), symbol_type.clone());
+            self.definitions
+                .entry(name.clone())
+                .or_default()
+                .push(choice.span.clone());
+            if let Some(deps) = deps {
+                self.direct_deps.insert(name.clone(), deps);
+            }
+
+            if let Some(group) = self
+                .choice_groups
+                .iter_mut()
+                .find(|g| g.name.as_ref() == Some(name))
+            {
+                group.options.extend(options);
+                group.defaults.extend(choice_defaults(choice));
+                group.optional |= choice.optional;
+                if is_tristate {
+                    group.symbol_type = SymbolType::Tristate;
+                }
+                return;
+            }
+        }
+
+        self.choice_groups.push(ChoiceGroup {
+            name: choice.name.clone(),
+            symbol_type,
+            options,
+            defaults: choice_defaults(choice),
+            optional: choice.optional,
+        });
+    }
+
+    fn process_config(
+        &mut self,
+        name: &str,
+        symbol_type: &SymbolType,
+        properties: &Property,
+        span: &Span,
+        parent_deps: Option<&Expr>,
+    ) {
+        let first_definition = !self.symbol_types.contains_key(name);
+        if first_definition {
+            self.symbol_order.push(name.to_string());
+            self.layout.push(LayoutItem::Symbol(name.to_string()));
+        }
+        // A definition without a type line keeps the declared type
+        let declares_type = properties
+            .spans
+            .iter()
+            .any(|p| matches!(p.kind, PropertyKind::Type | PropertyKind::DefType));
+        if first_definition || declares_type {
+            self.symbol_types
+                .insert(name.to_string(), symbol_type.clone());
+        }
+        self.definitions
+            .entry(name.to_string())
+            .or_default()
+            .push(span.clone());
+
+        // Defaults from later definitions come after earlier ones.
+        // `option env` defaults to the variable's value, read at load time.
+        let env_default = properties
+            .env
+            .as_ref()
+            .and_then(|var| std::env::var(var).ok())
+            .map(|value| (Expr::Const(value), None, span.clone()));
+        let defaults = properties
+            .defaults
+            .iter()
+            .map(|(expr, cond, span)| (expr.node.clone(), cond.as_deref().cloned(), span.clone()));
+        if env_default.is_some() || !properties.defaults.is_empty() {
+            self.default_map
+                .entry(name.to_string())
+                .or_default()
+                .extend(env_default.into_iter().chain(defaults));
+        }
+
+        if !properties.ranges.is_empty() {
+            self.range_map.entry(name.to_string()).or_default().extend(
+                properties.ranges.iter().map(|(low, high, cond, _)| {
+                    (
+                        low.node.clone(),
+                        high.node.clone(),
+                        cond.as_deref().cloned(),
+                    )
+                }),
+            );
+        }
+
+        if properties.prompt.is_some() {
+            self.prompts
+                .entry(name.to_string())
+                .or_default()
+                .push(properties.prompt_condition.as_deref().cloned());
+        }
+
+        if properties.allnoconfig_y {
+            self.allnoconfig_y.insert(name.to_string());
+        }
+
+        if properties.modules {
+            self.modules_symbol = Some(name.to_string());
+        }
+
+        if properties.transitional {
+            self.transitional.insert(name.to_string());
+        }
+
+        // Extract depends, including inherited ones. A symbol defined in
+        // several places depends on any one of its definitions' conditions.
+        let depends_expr = and_deps(parent_deps, properties.depends.as_deref());
+        let previous = self.direct_deps.remove(name);
+        let combined = match (first_definition, previous, depends_expr.clone()) {
+            (true, _, deps) => deps,
+            (false, Some(previous), Some(deps)) => Some(Expr::Or(previous.into(), deps.into())),
+            // An earlier or this definition is unconditional
+            (false, _, _) => None,
+        };
+        if let Some(combined) = combined {
+            self.direct_deps.insert(name.to_string(), combined);
+        }
+
+        if let Some(depends_expr) = depends_expr {
+            let deps = self.extract_symbols_from_expr(&depends_expr);
+            self.depends_map
+                .entry(name.to_string())
+                .or_default()
+                .extend(deps.into_iter().map(|s| Dependency {
+                    symbol: s,
+                    condition: Some(depends_expr.clone()),
+                }));
+        }
+
+        // Extract selects
+        if !properties.select.is_empty() {
+            let mut selections = Vec::new();
+            for (selected_symbol, condition, _) in &properties.select {
+                selections.push(Selection {
+                    symbol: selected_symbol.clone(),
+                    condition: condition.as_deref().cloned(),
+                });
+
+                // Build reverse map
+                self.reverse_select_map
+                    .entry(selected_symbol.clone())
+                    .or_default()
+                    .push(name.to_string());
+            }
+            self.select_map
+                .entry(name.to_string())
+                .or_default()
+                .extend(selections);
+        }
+
+        // Extract implies
+        if !properties.imply.is_empty() {
+            let implications: Vec<Implication> = properties
+                .imply
+                .iter()
+                .map(|(symbol, condition, _)| Implication {
+                    symbol: symbol.clone(),
+                    condition: condition.as_deref().cloned(),
+                })
+                .collect();
+            for implication in &implications {
+                self.reverse_imply_map
+                    .entry(implication.symbol.clone())
+                    .or_default()
+                    .push(name.to_string());
+            }
+            self.imply_map
+                .entry(name.to_string())
+                .or_default()
+                .extend(implications);
+        }
+    }
+
+    fn extract_symbols_from_expr(&self, expr: &Expr) -> Vec<String> {
+        let mut symbols = Vec::new();
+        self.collect_symbols(expr, &mut symbols);
+        symbols
+    }
+
+    fn collect_symbols(&self, expr: &Expr, symbols: &mut Vec<String>) {
+        match expr {
+            Expr::Symbol(name) => symbols.push(name.clone()),
+            Expr::And(left, right)
+            | Expr::Or(left, right)
+            | Expr::Equal(left, right)
+            | Expr::NotEqual(left, right)
+            | Expr::Less(left, right)
+            | Expr::LessEqual(left, right)
+            | Expr::Greater(left, right)
+            | Expr::GreaterEqual(left, right) => {
+                self.collect_symbols(left, symbols);
+                self.collect_symbols(right, symbols);
+            }
+            Expr::Not(inner) => self.collect_symbols(inner, symbols),
+            _ => {}
+        }
+    }
+
+    /// Check if a symbol can be enabled (all dependencies met)
+    pub fn can_enable(
+        &self,
+        symbol: &str,
+        symbol_table: &SymbolTable,
+    ) -> Result<(), DependencyError> {
+        if self.dependency_value(symbol, symbol_table).is_enabled() {
+            return Ok(());
+        }
+
+        // Dependencies that lead back to the symbol may never be met
+        if let Some(cycle) = self.cycles.iter().find(|cycle| cycle.contains(symbol)) {
+            return Err(DependencyError::CircularDependency {
+                chain: cycle.starting_at(symbol).symbols(),
+            });
+        }
+
+        // Name a disabled dependency when there is one, otherwise the
+        // expression as a whole (e.g. `depends on !FOO`) is unmet
+        let deps = self
+            .depends_map
+            .get(symbol)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if let Some(dep) = deps
+            .iter()
+            .find(|dep| !symbol_table.is_enabled(&dep.symbol))
+        {
+            return Err(DependencyError::DependencyNotMet {
+                symbol: symbol.to_string(),
+                required: dep.symbol.clone(),
+            });
+        }
+
+        Err(DependencyError::ConditionNotMet {
+            symbol: symbol.to_string(),
+            condition: format!("{:?}", self.direct_deps[symbol]),
+        })
+    }
+
+    /// Value of the symbol's `depends on` expression, `y` if it has none
+    pub fn dependency_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+        self.direct_deps
+            .get(symbol)
+            .map(|expr| eval(expr, symbol_table))
+            .unwrap_or(Tristate::Yes)
+    }
+
+    /// The highest value the dependencies allow: `depends on` an `m`
+    /// symbol caps a tristate at `m`, while a bool can still be `y`
+    pub fn max_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+        self.promote(
+            symbol,
+            self.dependency_value(symbol, symbol_table),
+            symbol_table,
+        )
+    }
+
+    /// The lowest value a symbol can have given the symbols selecting it
+    pub fn select_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+        let Some(selectors) = self.reverse_select_map.get(symbol) else {
+            return Tristate::No;
+        };
+
+        let value = selectors
+            .iter()
+            .flat_map(|selector| {
+                let selector_value = value_of(selector, symbol_table);
+                self.select_map[selector]
+                    .iter()
+                    .filter(|selection| selection.symbol == symbol)
+                    .map(move |selection| match &selection.condition {
+                        Some(condition) => selector_value.and(eval(condition, symbol_table)),
+                        None => selector_value,
+                    })
+            })
+            .max()
+            .unwrap_or(Tristate::No);
+
+        self.promote(symbol, value, symbol_table)
+    }
+
+    /// The value implied on a symbol; unlike select, the user can override it
+    pub fn implied_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+        let Some(impliers) = self.reverse_imply_map.get(symbol) else {
+            return Tristate::No;
+        };
+
+        let value = impliers
+            .iter()
+            .flat_map(|implier| {
+                let implier_value = value_of(implier, symbol_table);
+                self.imply_map[implier]
+                    .iter()
+                    .filter(|implication| implication.symbol == symbol)
+                    .map(move |implication| match &implication.condition {
+                        Some(condition) => implier_value.and(eval(condition, symbol_table)),
+                        None => implier_value,
+                    })
+            })
+            .max()
+            .unwrap_or(Tristate::No);
+
+        self.promote(symbol, value, symbol_table)
+    }
+
+    /// The largest value among a symbol's prompt conditions (`y` for a
+    /// prompt without one); `None` if the symbol has no prompt
+    pub fn prompt_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Option<Tristate> {
+        self.prompts
+            .get(symbol)?
+            .iter()
+            .map(|cond| {
+                cond.as_ref()
+                    .map_or(Tristate::Yes, |c| eval(c, symbol_table))
+            })
+            .max()
+    }
+
+    /// Whether every prompt of a symbol is switched off by its `if`
+    /// condition, so the user cannot see or set it
+    pub fn is_prompt_hidden(&self, symbol: &str, symbol_table: &SymbolTable) -> bool {
+        self.prompt_value(symbol, symbol_table) == Some(Tristate::No)
+    }
+
+    /// How far the user can set a symbol: `n` without a visible prompt,
+    /// otherwise the value of its prompt condition and its dependencies
+    pub fn visibility(&self, symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+        let Some(prompt) = self.prompt_value(symbol, symbol_table) else {
+            return Tristate::No;
+        };
+        self.promote(
+            symbol,
+            prompt.and(self.max_value(symbol, symbol_table)),
+            symbol_table,
+        )
+    }
+
+    /// Only tristate symbols can be `m`, and only while modules are
+    /// enabled; anything else enabled is `y`
+    pub fn promote(&self, symbol: &str, value: Tristate, symbol_table: &SymbolTable) -> Tristate {
+        match self.symbol_types.get(symbol) {
+            Some(SymbolType::Tristate) if self.modules_enabled(symbol_table) => value,
+            _ if value == Tristate::Module => Tristate::Yes,
+            _ => value,
+        }
+    }
+
+    /// Whether `m` is allowed: the value of the `option modules` symbol.
+    /// Without such a symbol, modules are always allowed.
+    pub fn modules_enabled(&self, symbol_table: &SymbolTable) -> bool {
+        self.modules_symbol
+            .as_ref()
+            .is_none_or(|modules| value_of(modules, symbol_table).is_enabled())
+    }
+
+    /// Check if a symbol can be disabled (nothing selects it)
+    pub fn can_disable(
+        &self,
+        symbol: &str,
+        symbol_table: &SymbolTable,
+    ) -> Result<(), DependencyError> {
+        if let Some(selectors) = self.reverse_select_map.get(symbol) {
+            for selector in selectors {
+                if symbol_table.is_enabled(selector) {
+                    return Err(DependencyError::SelectedBy {
+                        symbol: symbol.to_string(),
+                        selector: selector.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply select cascade when enabling a symbol
+    pub fn apply_selects(&self, symbol: &str, symbol_table: &mut SymbolTable) -> Vec<String> {
+        let mut enabled = Vec::new();
+
+        // Values only rise, so each symbol is queued at most twice (to `m`,
+        // then to `y`), even when selects lead back to it
+        let mut pending = vec![symbol.to_string()];
+        while let Some(current) = pending.pop() {
+            let value = value_of(&current, symbol_table);
+            let Some(selections) = self.select_map.get(&current) else {
+                continue;
+            };
+
+            for selection in selections {
+                // A symbol at m selects at m; the condition caps it further
+                let level = match &selection.condition {
+                    Some(condition) => value.and(eval(condition, symbol_table)),
+                    None => value,
+                };
+                let level = self.promote(&selection.symbol, level, symbol_table);
+
+                let previous = value_of(&selection.symbol, symbol_table);
+                if level > previous {
+                    symbol_table.set_value(&selection.symbol, level.to_string());
+                    if value_of(&selection.symbol, symbol_table) > previous {
+                        enabled.push(selection.symbol.clone());
+                        pending.push(selection.symbol.clone());
+                    }
+                }
+            }
+        }
+
+        enabled
+    }
+
+    /// The symbol dependency graph
+    pub fn graph(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
+    /// Every recursive dependency, each starting at its earliest declared symbol
+    pub fn dependency_cycles(&self) -> &[DependencyCycle] {
+        &self.cycles
+    }
+
+    /// Apply imply suggestions when enabling a symbol
+    pub fn get_implied_symbols(&self, symbol: &str, symbol_table: &SymbolTable) -> Vec<String> {
+        let mut implied = Vec::new();
+
+        if let Some(implications) = self.imply_map.get(symbol) {
+            for implication in implications {
+                let should_imply = implication
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| eval(condition, symbol_table).is_enabled());
+
+                if should_imply && !symbol_table.is_enabled(&implication.symbol) {
+                    implied.push(implication.symbol.clone());
+                }
+            }
+        }
+
+        implied
+    }
+
+    /// All declared symbols in Kconfig declaration order
+    pub fn symbols(&self) -> &[String] {
+        &self.symbol_order
+    }
+
+    pub fn symbol_type(&self, symbol: &str) -> Option<&SymbolType> {
+        self.symbol_types.get(symbol)
+    }
+
+    /// Where the symbol is defined, in parse order; a symbol may be
+    /// defined several times, each adding to the earlier definitions
+    pub fn defined_at(&self, symbol: &str) -> &[Span] {
+        self.definitions.get(symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbol marked `option modules`, if any
+    pub fn modules_symbol(&self) -> Option<&String> {
+        self.modules_symbol.as_ref()
+    }
+
+    /// Whether the symbol is marked `transitional`: it is read from an old
+    /// .config but hidden and never written
+    pub fn is_transitional(&self, symbol: &str) -> bool {
+        self.transitional.contains(symbol)
+    }
+
+    /// Whether the symbol is marked `option allnoconfig_y`
+    pub fn is_allnoconfig_y(&self, symbol: &str) -> bool {
+        self.allnoconfig_y.contains(symbol)
+    }
+
+    /// All choice blocks in declaration order
+    pub fn choice_groups(&self) -> &[ChoiceGroup] {
+        &self.choice_groups
+    }
+
+    /// Add every declared symbol to the symbol table
+    pub fn populate(&self, symbol_table: &mut SymbolTable) {
+        for name in &self.symbol_order {
+            symbol_table.add_symbol(name.clone(), self.symbol_types[name].clone());
+            if let Some(symbol) = symbol_table.get_symbol_mut(name) {
+                symbol.is_transitional = self.transitional.contains(name);
+                symbol.is_choice = self.is_choice(name);
+            }
+        }
+        symbol_table.set_layout(self.layout.clone());
+    }
+
+    /// The value a symbol takes when the user has not set it
+    pub fn default_value(&self, symbol: &str, symbol_table: &SymbolTable) -> Option<String> {
+        let symbol_type = self.symbol_types.get(symbol)?;
+
+        let dependency = self.dependency_value(symbol, symbol_table);
+        let active_default = self.active_default(symbol, symbol_table);
+
+        if matches!(symbol_type, SymbolType::Bool | SymbolType::Tristate) {
+            // ((default || imply) && depends) || select, as in Linux
+            let default = active_default
+                .map(|default| default.value(symbol_table))
+                .unwrap_or(Tristate::No);
+            let value = default
+                .or(self.implied_value(symbol, symbol_table))
+                .and(dependency)
+                .or(self.select_value(symbol, symbol_table));
+            return Some(self.promote(symbol, value, symbol_table).to_string());
+        }
+
+        // Other types only take a default while their dependencies are met
+        if !dependency.is_enabled() {
+            return None;
+        }
+
+        match active_default?.expr {
+            Expr::Const(val) => Some(val.clone()),
+            // An undefined symbol is a constant named by its value
+            Expr::Symbol(name) => match symbol_table.get_symbol(name) {
+                Some(referenced) => referenced.value.clone(),
+                None => Some(name.clone()),
+            },
+            expr => Some(eval(expr, symbol_table).to_string()),
+        }
+    }
+
+    /// The first `default` whose condition is not `n`
+    pub fn active_default(
+        &self,
+        symbol: &str,
+        symbol_table: &SymbolTable,
+    ) -> Option<ActiveDefault<'_>> {
+        self.default_map
+            .get(symbol)?
+            .iter()
+            .map(|(expr, cond, span)| ActiveDefault {
+                expr,
+                condition: cond
+                    .as_ref()
+                    .map_or(Tristate::Yes, |c| eval(c, symbol_table)),
+                span,
+            })
+            .find(|default| default.condition.is_enabled())
+    }
+
+    /// The `depends on` expression of a symbol, including the conditions
+    /// of enclosing blocks
+    pub fn direct_dependencies(&self, symbol: &str) -> Option<&Expr> {
+        self.direct_deps.get(symbol)
+    }
+
+    /// The condition of each prompt of a symbol, `None` for an unconditional one
+    pub fn prompt_conditions(&self, symbol: &str) -> &[Option<Expr>] {
+        self.prompts.get(symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// The `default` lines of a symbol in declaration order, with their
+    /// conditions and locations
+    pub fn defaults(&self, symbol: &str) -> &[(Expr, Option<Expr>, Span)] {
+        self.default_map.get(symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// The `select` lines of a symbol
+    pub fn selections(&self, symbol: &str) -> &[Selection] {
+        self.select_map.get(symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbols with a `select` of `symbol`, in declaration order
+    pub fn selectors(&self, symbol: &str) -> &[String] {
+        self.reverse_select_map
+            .get(symbol)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbols with an `imply` of `symbol`, in declaration order
+    pub fn impliers(&self, symbol: &str) -> &[String] {
+        self.reverse_imply_map
+            .get(symbol)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The first symbol whose `select` of `symbol` gives it at least `value`
+    pub fn selected_by(
+        &self,
+        symbol: &str,
+        value: Tristate,
+        symbol_table: &SymbolTable,
+    ) -> Option<&String> {
+        self.selectors(symbol).iter().find(|selector| {
+            self.select_map[*selector]
+                .iter()
+                .filter(|selection| selection.symbol == symbol)
+                .any(|selection| {
+                    self.relation_value(
+                        selector,
+                        selection.condition.as_ref(),
+                        symbol,
+                        symbol_table,
+                    ) >= value
+                })
+        })
+    }
+
+    /// The first symbol whose `imply` of `symbol` gives it at least `value`
+    pub fn implied_by(
+        &self,
+        symbol: &str,
+        value: Tristate,
+        symbol_table: &SymbolTable,
+    ) -> Option<&String> {
+        self.impliers(symbol).iter().find(|implier| {
+            self.imply_map[*implier]
+                .iter()
+                .filter(|implication| implication.symbol == symbol)
+                .any(|implication| {
+                    self.relation_value(
+                        implier,
+                        implication.condition.as_ref(),
+                        symbol,
+                        symbol_table,
+                    ) >= value
+                })
+        })
+    }
+
+    /// The value a select or imply by `source` gives `symbol`
+    fn relation_value(
+        &self,
+        source: &str,
+        condition: Option<&Expr>,
+        symbol: &str,
+        symbol_table: &SymbolTable,
+    ) -> Tristate {
+        let value = value_of(source, symbol_table);
+        let value = condition.map_or(value, |c| value.and(eval(c, symbol_table)));
+        self.promote(symbol, value, symbol_table)
+    }
+
+    /// The bounds of the first `range` whose condition holds. A bound may
+    /// name another symbol, whose current value is used; the range does not
+    /// apply while a bound is not a number.
+    pub fn active_range(&self, symbol: &str, symbol_table: &SymbolTable) -> Option<(i128, i128)> {
+        let symbol_type = self.symbol_types.get(symbol)?;
+        let (low, high, _) = self.range_map.get(symbol)?.iter().find(|(_, _, cond)| {
+            cond.as_ref()
+                .is_none_or(|c| eval(c, symbol_table).is_enabled())
+        })?;
+
+        let bound = |expr: &Expr| match expr {
+            Expr::Const(value) => parse_number(symbol_type, value),
+            // An undefined symbol is a constant named by its value
+            Expr::Symbol(name) => match symbol_table.get_symbol(name) {
+                Some(referenced) => parse_number(symbol_type, referenced.value.as_deref()?),
+                None => parse_number(symbol_type, name),
+            },
+            _ => None,
+        };
+        Some((bound(low)?, bound(high)?))
+    }
+
+    /// An int or hex value moved to the nearest bound of the active range
+    /// when it lies outside; other values are returned unchanged
+    pub fn clamp_to_range(
+        &self,
+        symbol: &str,
+        value: String,
+        symbol_table: &SymbolTable,
+    ) -> String {
+        let (Some(symbol_type), Some((low, high))) = (
+            self.symbol_types.get(symbol),
+            self.active_range(symbol, symbol_table),
+        ) else {
+            return value;
+        };
+        match parse_number(symbol_type, &value) {
+            Some(number) if number < low => format_number(symbol_type, low),
+            Some(number) if number > high => format_number(symbol_type, high),
+            _ => value,
+        }
+    }
+
+    /// The named choice called `name`, if any
+    pub fn choice(&self, name: &str) -> Option<&ChoiceGroup> {
+        self.choice_groups
+            .iter()
+            .find(|group| group.name.as_deref() == Some(name))
+    }
+
+    /// Whether `name` is a named choice rather than a config symbol
+    pub fn is_choice(&self, name: &str) -> bool {
+        self.choice(name).is_some()
+    }
+
+    /// The choice block a symbol belongs to, if any
+    pub fn choice_group(&self, symbol: &str) -> Option<&ChoiceGroup> {
+        self.choice_groups
+            .iter()
+            .find(|group| group.options.iter().any(|o| o == symbol))
+    }
+
+    /// The option a choice selects when the user has not picked one: the
+    /// first `default` whose condition holds, if that option can be enabled,
+    /// otherwise the first option that can. Optional choices select none.
+    pub fn choice_default<'a>(
+        &self,
+        group: &'a ChoiceGroup,
+        symbol_table: &SymbolTable,
+    ) -> Option<&'a String> {
+        self.choice_default_excluding(group, symbol_table, &HashSet::new())
+    }
+
+    /// Like `choice_default`, skipping the options in `excluded`
+    pub fn choice_default_excluding<'a>(
+        &self,
+        group: &'a ChoiceGroup,
+        symbol_table: &SymbolTable,
+        excluded: &HashSet<&String>,
+    ) -> Option<&'a String> {
+        if group.optional {
+            return None;
+        }
+
+        let selectable: Vec<&String> = group
+            .options
+            .iter()
+            .filter(|o| !excluded.contains(o) && self.can_enable(o, symbol_table).is_ok())
+            .collect();
+
+        let default = group
+            .defaults
+            .iter()
+            .find(|(_, cond)| {
+                cond.as_ref()
+                    .is_none_or(|c| eval(c, symbol_table).is_enabled())
+            })
+            .map(|(option, _)| option);
+
+        selectable
+            .iter()
+            .find(|o| default == Some(**o))
+            .or_else(|| selectable.first())
+            .copied()
+    }
+
+    /// Check for conflicts when disabling a symbol
+    /// Returns: the enabled symbols that depend on it, in declaration order
+    pub fn check_disable_cascade(&self, symbol: &str, symbol_table: &SymbolTable) -> Vec<String> {
+        self.symbol_order
+            .iter()
+            .filter(|dependent| symbol_table.is_enabled(dependent))
+            .filter(|dependent| {
+                self.depends_map
+                    .get(*dependent)
+                    .is_some_and(|deps| deps.iter().any(|dep| dep.symbol == symbol))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for DependencyResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DependencyError {
+    DependencyNotMet { symbol: String, required: String },
+    ConditionNotMet { symbol: String, condition: String },
+    SelectedBy { symbol: String, selector: String },
+    CircularDependency { chain: Vec<String> },
+}
+
+impl std::fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DependencyError::DependencyNotMet { symbol, required } => {
+                write!(
+                    f,
+                    "Cannot enable {}: requires {} to be enabled first",
+                    symbol, required
+                )
+            }
+            DependencyError::ConditionNotMet { symbol, condition } => {
+                write!(
+                    f,
+                    "Cannot enable {}: condition not met: {}",
+                    symbol, condition
+                )
+            }
+            DependencyError::SelectedBy { symbol, selector } => {
+                write!(f, "Cannot disable {}: selected by {}", symbol, selector)
+            }
+            DependencyError::CircularDependency { chain } => {
+                write!(f, "Circular dependency: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+/// `parent && local`, either of which may be absent
+pub(crate) fn and_deps(parent: Option<&Expr>, local: Option<&Expr>) -> Option<Expr> {
+    match (parent, local) {
+        (Some(parent), Some(local)) => Some(Expr::And(parent.clone().into(), local.clone().into())),
+        (parent, local) => parent.or(local).cloned(),
+    }
+}
+
+/// The `default` lines of a choice, each with its `if` condition
+fn choice_defaults(choice: &Choice) -> Vec<(String, Option<Expr>)> {
+    choice
+        .defaults
+        .iter()
+        .map(|(option, condition, _)| (option.clone(), condition.as_deref().cloned()))
+        .collect()
+}
+
+/// Evaluate an expression, treating one that cannot be evaluated as `n`
+fn eval(expr: &Expr, symbol_table: &SymbolTable) -> Tristate {
+    evaluate_expr(expr, symbol_table).unwrap_or(Tristate::No)
+}
+
+fn value_of(symbol: &str, symbol_table: &SymbolTable) -> Tristate {
+    symbol_table
+        .get_value(symbol)
+        .map(|value| Tristate::from_value(&value))
+        .unwrap_or(Tristate::No)
+}
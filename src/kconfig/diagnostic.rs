@@ -0,0 +1,218 @@
+use crate::error::KconfigError;
+use crate::kconfig::ast::Span;
+use crate::kconfig::lint::LintRule;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// What a diagnostic is about. Each code keeps its number across releases,
+/// so scripts and editors can match on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// A token that cannot start or continue the current entry
+    UnexpectedToken,
+    ExpectedIdentifier,
+    ExpectedExpression,
+    ExpectedString,
+    UnterminatedString,
+    /// A character that starts no token, or a lone `&` or `|`
+    InvalidCharacter,
+    UnsupportedOption,
+    /// A `menu`, `if` or `choice` without its end keyword
+    UnclosedBlock,
+    /// An `endmenu`, `endif` or `endchoice` that closes nothing
+    UnmatchedEnd,
+    MissingSource,
+    RecursiveSource,
+    MacroError,
+    Io,
+    /// `option defconfig_list`, accepted but ignored
+    ObsoleteOption,
+    /// A second prompt for the same config entry replaces the first
+    PromptRedefined,
+    /// Symbols whose values depend on each other through `depends on`,
+    /// `select` or `imply`
+    RecursiveDependency,
+    /// A finding of `rkconf lint`
+    Lint(LintRule),
+}
+
+impl DiagnosticCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticCode::UnexpectedToken => "E0001",
+            DiagnosticCode::ExpectedIdentifier => "E0002",
+            DiagnosticCode::ExpectedExpression => "E0003",
+            DiagnosticCode::ExpectedString => "E0004",
+            DiagnosticCode::UnterminatedString => "E0005",
+            DiagnosticCode::InvalidCharacter => "E0006",
+            DiagnosticCode::UnsupportedOption => "E0007",
+            DiagnosticCode::UnclosedBlock => "E0008",
+            DiagnosticCode::UnmatchedEnd => "E0009",
+            DiagnosticCode::MissingSource => "E0010",
+            DiagnosticCode::RecursiveSource => "E0011",
+            DiagnosticCode::MacroError => "E0012",
+            DiagnosticCode::Io => "E0013",
+            DiagnosticCode::RecursiveDependency => "E0014",
+            DiagnosticCode::ObsoleteOption => "W0001",
+            DiagnosticCode::PromptRedefined => "W0002",
+            DiagnosticCode::Lint(rule) => rule.code(),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An error or warning found while reading the Kconfig sources
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub span: Span,
+    /// Further lines printed after the source snippet
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: DiagnosticCode, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(code: DiagnosticCode, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Describe a parse error at `span`
+    pub fn from_error(error: &KconfigError, span: Span) -> Self {
+        let (code, message) = match error {
+            KconfigError::Syntax { code, message, .. } => (*code, message.clone()),
+            KconfigError::Macro { message, .. } => (DiagnosticCode::MacroError, message.clone()),
+            KconfigError::FileNotFound(path) => (
+                DiagnosticCode::MissingSource,
+                format!("File not found: {}", path.display()),
+            ),
+            KconfigError::RecursiveSource { .. } => {
+                (DiagnosticCode::RecursiveSource, error.to_string())
+            }
+            KconfigError::Io(_) => (DiagnosticCode::Io, error.to_string()),
+            // Not raised while parsing
+            _ => (DiagnosticCode::UnexpectedToken, error.to_string()),
+        };
+        Self::error(code, message, span)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render in the style of a compiler: the message, the location, and
+    /// the source line with a caret under the token the span starts at.
+    /// `source` is the content of the span's file, if it can be read.
+    ///
+    /// ```text
+    /// error[E0002]: Expected identifier after 'config'
+    ///  --> Kconfig:3:7
+    ///   |
+    /// 3 | config
+    ///   |       ^
+    /// ```
+    ///
+    /// Notes follow as `  = note: ...` lines.
+    pub fn render(&self, source: Option<&str>) -> String {
+        let mut out = format!("{}[{}]: {}\n", self.severity, self.code, self.message);
+        let snippet = source.and_then(|s| s.lines().nth(self.span.line.saturating_sub(1)));
+        let gutter = " ".repeat(self.span.line.to_string().len());
+        out.push_str(&format!("{}--> {}\n", gutter, self.span));
+
+        if let Some(line) = snippet {
+            let before: String = line
+                .chars()
+                .take(self.span.column.saturating_sub(1))
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            let token = line
+                .chars()
+                .skip(self.span.column.saturating_sub(1))
+                .take_while(|ch| !ch.is_whitespace())
+                .count()
+                .max(1);
+            out.push_str(&format!("{} |\n", gutter));
+            out.push_str(&format!("{} | {}\n", self.span.line, line));
+            out.push_str(&format!("{} | {}{}\n", gutter, before, "^".repeat(token)));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}[{}]: {}",
+            self.span, self.severity, self.code, self.message
+        )
+    }
+}
+
+/// Render every diagnostic with its source snippet, followed by a count
+/// of errors and warnings
+pub fn render_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut out = String::new();
+
+    for diagnostic in diagnostics {
+        let source = sources
+            .entry(diagnostic.span.file.clone())
+            .or_insert_with(|| fs::read_to_string(&diagnostic.span.file).ok());
+        out.push_str(&diagnostic.render(source.as_deref()));
+        out.push('\n');
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    out.push_str(&format!(
+        "{} error{}, {} warning{}\n",
+        errors,
+        plural(errors),
+        warnings,
+        plural(warnings)
+    ));
+    out
+}
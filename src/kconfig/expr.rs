@@ -1,73 +1,212 @@
 use crate::error::{KconfigError, Result};
-use crate::kconfig::ast::Expr;
+use crate::kconfig::ast::{Expr, SymbolType};
 use crate::kconfig::symbol::SymbolTable;
+use crate::kconfig::value::{parse_hex, parse_int};
+use std::cmp::Ordering;
+use std::fmt;
 
-pub fn evaluate_expr(expr: &Expr, symbols: &SymbolTable) -> Result<bool> {
-    match expr {
-        Expr::Symbol(name) => {
-            // Check if symbol is defined and enabled
-            Ok(symbols.is_enabled(name))
+/// Kconfig tristate value, ordered `n < m < y`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tristate {
+    No = 0,
+    Module = 1,
+    Yes = 2,
+}
+
+impl Tristate {
+    /// Parse `y`, `m` or `n`; anything else is `n`
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "y" => Tristate::Yes,
+            "m" => Tristate::Module,
+            _ => Tristate::No,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tristate::No => "n",
+            Tristate::Module => "m",
+            Tristate::Yes => "y",
+        }
+    }
+
+    /// `&&`: the smaller of both values
+    pub fn and(self, other: Tristate) -> Tristate {
+        self.min(other)
+    }
+
+    /// `||`: the larger of both values
+    pub fn or(self, other: Tristate) -> Tristate {
+        self.max(other)
+    }
+
+    pub fn is_enabled(self) -> bool {
+        self != Tristate::No
+    }
+}
+
+impl fmt::Display for Tristate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// `!`: `2 - x`, so `!m` stays `m`
+impl std::ops::Not for Tristate {
+    type Output = Tristate;
+
+    fn not(self) -> Tristate {
+        match self {
+            Tristate::No => Tristate::Yes,
+            Tristate::Module => Tristate::Module,
+            Tristate::Yes => Tristate::No,
         }
-        Expr::Const(val) => {
-            // "y", "m", "n" constants
-            Ok(val == "y" || val == "m")
+    }
+}
+
+impl From<bool> for Tristate {
+    fn from(value: bool) -> Self {
+        if value {
+            Tristate::Yes
+        } else {
+            Tristate::No
         }
+    }
+}
+
+/// Evaluate an expression with Kconfig tristate semantics
+///
+/// Unquoted `y`, `m` and `n` are constants, bool and tristate symbols
+/// evaluate to their value and every other symbol to `n`. Comparisons
+/// use the declared type of their operands, so `int` and `hex` symbols
+/// compare numerically.
+pub fn evaluate_expr(expr: &Expr, symbols: &SymbolTable) -> Result<Tristate> {
+    match expr {
+        Expr::Symbol(name) => Ok(symbol_tristate(name, symbols)),
+        Expr::Const(val) => Ok(Tristate::from_value(val)),
         Expr::Not(inner) => Ok(!evaluate_expr(inner, symbols)?),
         Expr::And(left, right) => {
-            Ok(evaluate_expr(left, symbols)? && evaluate_expr(right, symbols)?)
+            Ok(evaluate_expr(left, symbols)?.and(evaluate_expr(right, symbols)?))
         }
         Expr::Or(left, right) => {
-            Ok(evaluate_expr(left, symbols)? || evaluate_expr(right, symbols)?)
-        }
-        Expr::Equal(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(left_val == right_val)
-        }
-        Expr::NotEqual(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(left_val != right_val)
-        }
-        Expr::Less(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(compare_values(&left_val, &right_val)? < 0)
+            Ok(evaluate_expr(left, symbols)?.or(evaluate_expr(right, symbols)?))
         }
-        Expr::LessEqual(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(compare_values(&left_val, &right_val)? <= 0)
-        }
-        Expr::Greater(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(compare_values(&left_val, &right_val)? > 0)
-        }
-        Expr::GreaterEqual(left, right) => {
-            let left_val = get_expr_value(left, symbols)?;
-            let right_val = get_expr_value(right, symbols)?;
-            Ok(compare_values(&left_val, &right_val)? >= 0)
+        Expr::Equal(left, right) => Ok(compare(left, right, symbols)?.is_eq().into()),
+        Expr::NotEqual(left, right) => Ok(compare(left, right, symbols)?.is_ne().into()),
+        Expr::Less(left, right) => Ok(compare(left, right, symbols)?.is_lt().into()),
+        Expr::LessEqual(left, right) => Ok(compare(left, right, symbols)?.is_le().into()),
+        Expr::Greater(left, right) => Ok(compare(left, right, symbols)?.is_gt().into()),
+        Expr::GreaterEqual(left, right) => Ok(compare(left, right, symbols)?.is_ge().into()),
+    }
+}
+
+fn symbol_tristate(name: &str, symbols: &SymbolTable) -> Tristate {
+    if matches!(name, "y" | "m" | "n") {
+        return Tristate::from_value(name);
+    }
+
+    match symbols.get_symbol(name) {
+        Some(symbol) if matches!(symbol.symbol_type, SymbolType::Bool | SymbolType::Tristate) => {
+            symbol
+                .value
+                .as_deref()
+                .map(Tristate::from_value)
+                .unwrap_or(Tristate::No)
         }
+        _ => Tristate::No,
     }
 }
 
-fn get_expr_value(expr: &Expr, symbols: &SymbolTable) -> Result<String> {
+/// The string value of a comparison operand and its declared type
+///
+/// Like Linux, `y`, `m` and `n` are tristate constants and an undefined
+/// symbol is an untyped constant whose value is its name.
+fn get_operand(expr: &Expr, symbols: &SymbolTable) -> Result<(String, Option<SymbolType>)> {
     match expr {
-        Expr::Symbol(name) => Ok(symbols.get_value(name).unwrap_or_else(|| "n".to_string())),
-        Expr::Const(val) => Ok(val.clone()),
+        Expr::Symbol(name) | Expr::Const(name) if matches!(name.as_str(), "y" | "m" | "n") => {
+            Ok((name.clone(), Some(SymbolType::Tristate)))
+        }
+        Expr::Symbol(name) => match symbols.get_symbol(name) {
+            Some(symbol) => {
+                let value = symbol
+                    .value
+                    .clone()
+                    .unwrap_or_else(|| match symbol.symbol_type {
+                        SymbolType::Bool | SymbolType::Tristate => "n".to_string(),
+                        _ => String::new(),
+                    });
+                Ok((value, Some(symbol.symbol_type.clone())))
+            }
+            None => Ok((name.clone(), None)),
+        },
+        Expr::Const(val) => Ok((val.clone(), None)),
         _ => Err(KconfigError::InvalidExpression(
             "Complex expression in comparison".to_string(),
         )),
     }
 }
 
-fn compare_values(left: &str, right: &str) -> Result<i32> {
-    // Try to parse as numbers
-    if let (Ok(l), Ok(r)) = (left.parse::<i64>(), right.parse::<i64>()) {
-        return Ok((l - r).signum() as i32);
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Signed(i64),
+    Unsigned(u64),
+}
+
+/// Parse an operand according to its type; `None` means compare as string
+fn parse_number(value: &str, symbol_type: Option<&SymbolType>) -> Option<Number> {
+    match symbol_type {
+        Some(SymbolType::Bool | SymbolType::Tristate) => Some(Number::Signed(match value {
+            "n" => 0,
+            "m" => 1,
+            "y" => 2,
+            _ => -1,
+        })),
+        Some(SymbolType::Int) => parse_int(value).map(Number::Signed),
+        Some(SymbolType::Hex) => parse_hex(value).map(Number::Unsigned),
+        // Untyped constants and strings: decimal, 0x hex or 0 octal
+        _ => {
+            let (negative, digits) = match value.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, value.strip_prefix('+').unwrap_or(value)),
+            };
+            let magnitude = if let Some(hex) = digits
+                .strip_prefix("0x")
+                .or_else(|| digits.strip_prefix("0X"))
+            {
+                i64::from_str_radix(hex, 16).ok()
+            } else if digits.len() > 1 && digits.starts_with('0') {
+                i64::from_str_radix(&digits[1..], 8).ok()
+            } else {
+                digits.parse().ok()
+            }?;
+            Some(Number::Signed(if negative {
+                -magnitude
+            } else {
+                magnitude
+            }))
+        }
+    }
+}
+
+fn compare(left: &Expr, right: &Expr, symbols: &SymbolTable) -> Result<Ordering> {
+    let (left_val, left_type) = get_operand(left, symbols)?;
+    let (right_val, right_type) = get_operand(right, symbols)?;
+
+    let ordering = match (
+        parse_number(&left_val, left_type.as_ref()),
+        parse_number(&right_val, right_type.as_ref()),
+    ) {
+        (Some(Number::Signed(l)), Some(Number::Signed(r))) => l.cmp(&r),
+        (Some(l), Some(r)) => as_unsigned(l).cmp(&as_unsigned(r)),
+        _ => left_val.cmp(&right_val),
+    };
+    Ok(ordering)
+}
+
+fn as_unsigned(number: Number) -> u64 {
+    match number {
+        Number::Signed(n) => n as u64,
+        Number::Unsigned(n) => n,
     }
-    
-    // String comparison
-    Ok(left.cmp(right) as i32)
 }
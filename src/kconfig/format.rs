@@ -0,0 +1,296 @@
+//! Canonical layout of Kconfig files, as printed by `rkconf fmt`.
+//!
+//! Entries, blocks and end keywords start at column 0, attributes are
+//! indented by one tab with one attribute per line, and help text by a tab
+//! and two spaces. Prompts, titles and paths are double-quoted, tokens are
+//! separated by single spaces, and runs of blank lines collapse into one.
+//! Formatting never changes what the file parses to.
+
+use crate::kconfig::cst::{LineKind, SyntaxLine, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind};
+use crate::kconfig::lexer::{normalize_help, Lexer, Token};
+
+/// Attribute keywords; each one starts a new line
+const ATTRIBUTES: [&str; 19] = [
+    "bool",
+    "tristate",
+    "string",
+    "int",
+    "hex",
+    "def_bool",
+    "def_tristate",
+    "prompt",
+    "default",
+    "depends",
+    "select",
+    "imply",
+    "range",
+    "visible",
+    "option",
+    "optional",
+    "transitional",
+    "modules",
+    "help",
+];
+
+/// Keywords followed by a prompt, title or path
+const TAKES_STRING: [&str; 13] = [
+    "bool", "tristate", "string", "int", "hex", "prompt", "menu", "comment", "mainmenu", "source",
+    "osource", "rsource", "orsource",
+];
+
+/// Format a whole file
+pub fn format(tree: &SyntaxTree) -> String {
+    let mut out = Output::default();
+    for node in &tree.nodes {
+        out.node(node);
+    }
+    out.finish()
+}
+
+/// Format the Kconfig source `source`
+pub fn format_source(source: &str) -> String {
+    format(&SyntaxTree::parse(source, ""))
+}
+
+#[derive(Default)]
+struct Output {
+    /// Lines and whether they are part of help text, where blank lines
+    /// are significant
+    lines: Vec<(String, bool)>,
+}
+
+impl Output {
+    fn push(&mut self, line: String) {
+        self.lines.push((line, false));
+    }
+
+    fn node(&mut self, node: &SyntaxNode) {
+        match node {
+            SyntaxNode::Line(line) => self.line(line, ""),
+            SyntaxNode::Entry { header, body } => {
+                self.header(header);
+                self.body(body);
+            }
+            SyntaxNode::Block {
+                header,
+                body,
+                children,
+                end,
+            } => {
+                self.header(header);
+                self.body(body);
+                for child in children {
+                    self.node(child);
+                }
+                if let Some(end) = end {
+                    self.line(end, "");
+                }
+            }
+        }
+    }
+
+    /// An entry or block line, split before any attribute on it
+    fn header(&mut self, line: &SyntaxLine) {
+        let mut statements = split_statements(line).into_iter();
+        if let Some(first) = statements.next() {
+            self.push(first);
+        }
+        for statement in statements {
+            self.push(format!("\t{}", statement));
+        }
+    }
+
+    fn body(&mut self, body: &[SyntaxLine]) {
+        let mut i = 0;
+        while i < body.len() {
+            if body[i].kind == LineKind::HelpText {
+                let end = body[i..]
+                    .iter()
+                    .position(|line| line.kind != LineKind::HelpText)
+                    .map_or(body.len(), |n| i + n);
+                self.help_text(&body[i..end]);
+                i = end;
+            } else {
+                self.line(&body[i], "\t");
+                i += 1;
+            }
+        }
+    }
+
+    fn line(&mut self, line: &SyntaxLine, indent: &str) {
+        match line.kind {
+            LineKind::Blank => self.push(String::new()),
+            LineKind::Comment | LineKind::Assignment => {
+                let text = line.tokens.first().map_or("", |t| t.text.as_str());
+                self.push(format!("{}{}", indent, text));
+            }
+            LineKind::Entry | LineKind::BlockStart | LineKind::BlockEnd => self.header(line),
+            LineKind::HelpText => self.push(line.to_string().trim_end().to_string()),
+            LineKind::Property | LineKind::Help => {
+                for statement in split_statements(line) {
+                    self.push(format!("\t{}", statement));
+                }
+            }
+        }
+    }
+
+    /// Help text re-indented by a tab and two spaces. Blank lines after
+    /// the text are not part of it and may collapse.
+    fn help_text(&mut self, lines: &[SyntaxLine]) {
+        let raw: String = lines
+            .iter()
+            .map(|line| format!("{}\n", line.to_string().trim_end()))
+            .collect();
+        let help = normalize_help(&raw);
+        for text in help.lines() {
+            let line = if text.is_empty() {
+                String::new()
+            } else {
+                format!("\t  {}", text)
+            };
+            self.lines.push((line, true));
+        }
+        if raw.trim_end().lines().count() < lines.len() {
+            self.push(String::new());
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        let mut previous_blank = true;
+        for (line, help) in &self.lines {
+            let blank = line.is_empty();
+            if blank && !help && previous_blank {
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+            previous_blank = blank;
+        }
+        while out.ends_with("\n\n") {
+            out.pop();
+        }
+        if out == "\n" {
+            out.clear();
+        }
+        out
+    }
+}
+
+/// The statements on one line, formatted. A new statement starts at each
+/// attribute keyword after the first token; a trailing comment stays with
+/// the last statement.
+fn split_statements(line: &SyntaxLine) -> Vec<String> {
+    let mut statements: Vec<Vec<&SyntaxToken>> = Vec::new();
+
+    for token in line.code() {
+        let starts_statement =
+            token.kind == TokenKind::Word && ATTRIBUTES.contains(&token.text.as_str());
+        match statements.last_mut() {
+            // `option modules` is one attribute
+            Some(current)
+                if !starts_statement || current.last().is_some_and(|t| t.text == "option") =>
+            {
+                current.push(token)
+            }
+            _ => statements.push(vec![token]),
+        }
+    }
+
+    let mut formatted: Vec<String> = statements
+        .iter()
+        .map(|tokens| join_tokens(tokens))
+        .collect();
+    if let Some(comment) = line.comment() {
+        match formatted.last_mut() {
+            Some(last) => {
+                last.push(' ');
+                last.push_str(&comment.text);
+            }
+            None => formatted.push(comment.text.clone()),
+        }
+    }
+    formatted
+}
+
+/// Join tokens with canonical spacing: one space between operands and
+/// around binary operators, none after `!` and `(` or before `)`, and none
+/// around the `=` of `option env=`
+fn join_tokens(tokens: &[&SyntaxToken]) -> String {
+    let is_option = tokens.first().is_some_and(|t| t.text == "option");
+    let mut out = String::new();
+    let mut previous: Option<&SyntaxToken> = None;
+
+    for token in tokens {
+        let text = match previous {
+            Some(prev)
+                if TAKES_STRING.contains(&prev.text.as_str()) && prev.kind == TokenKind::Word =>
+            {
+                quote(token)
+            }
+            Some(prev) if is_option && prev.text == "=" => quote(token),
+            _ => normalize_string(token),
+        };
+
+        if let Some(prev) = previous {
+            let tight = (prev.kind == TokenKind::Operator && (prev.text == "(" || prev.text == "!")
+                // `! =` would read as `!=`
+                && !text.starts_with('='))
+                || (token.kind == TokenKind::Operator && token.text == ")")
+                || (is_option && (prev.text == "=" || token.text == "="));
+            if !tight {
+                out.push(' ');
+            }
+        }
+        out.push_str(&text);
+        previous = Some(token);
+    }
+    out
+}
+
+/// A prompt, title or path in double quotes. Words that are not plain
+/// identifiers, such as keywords and macro references, are left alone.
+fn quote(token: &SyntaxToken) -> String {
+    match token.kind {
+        TokenKind::Word if is_identifier(&token.text) => format!("\"{}\"", escape(&token.text)),
+        _ => normalize_string(token),
+    }
+}
+
+/// A string literal re-escaped the canonical way. Strings containing macro
+/// references are expanded before lexing and stay as written.
+fn normalize_string(token: &SyntaxToken) -> String {
+    if token.kind != TokenKind::String || token.text.contains('$') {
+        return token.text.clone();
+    }
+    match Lexer::new(token.text.clone(), Default::default()).next_token() {
+        Ok(Token::StringLit(value)) if token.text.len() > 1 && token.text.ends_with('"') => {
+            format!("\"{}\"", escape(&value))
+        }
+        _ => token.text.clone(),
+    }
+}
+
+fn is_identifier(word: &str) -> bool {
+    if word.contains('$') {
+        return false;
+    }
+    let mut lexer = Lexer::new(word.to_string(), Default::default());
+    matches!(lexer.next_token(), Ok(Token::Identifier(_)))
+        && matches!(lexer.next_token(), Ok(Token::Eof))
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
@@ -0,0 +1,118 @@
+//! Wildcard expansion for `source` directives, following glob(3): `*`,
+//! `?` and `[...]` match within one path component, and names starting
+//! with `.` only match a pattern that starts with `.` too.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Whether a `source` path contains wildcards
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// The existing files matching `pattern`, sorted so the parse order does
+/// not depend on the file system. The directories listed on the way are
+/// added to `scanned`, since a new file in one of them may match too.
+pub fn expand(pattern: &Path, scanned: &mut BTreeSet<PathBuf>) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::new()];
+
+    for component in pattern.components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !matches!(component, Component::Normal(_)) || !is_pattern(&part) {
+            matches.iter_mut().for_each(|m| m.push(component));
+            continue;
+        }
+
+        matches = matches
+            .iter()
+            .flat_map(|dir| {
+                let listing = if dir.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    dir.as_path()
+                };
+                if listing.is_dir() {
+                    scanned.insert(listing.to_path_buf());
+                }
+                let mut names: Vec<String> = fs::read_dir(listing)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .filter(|name| matches_component(&part, name))
+                    .collect();
+                names.sort();
+                names
+                    .into_iter()
+                    .map(|name| dir.join(name))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
+    let mut files: Vec<PathBuf> = matches.into_iter().filter(|path| path.is_file()).collect();
+    files.sort();
+    files
+}
+
+fn matches_component(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches_from(&pattern, &name)
+}
+
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches_from(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches_from(&pattern[1..], &name[1..]),
+        Some('[') => match (name.first(), parse_class(&pattern[1..])) {
+            (Some(&ch), Some((class, rest))) => class.matches(ch) && matches_from(rest, &name[1..]),
+            // An unterminated `[` is a literal character
+            (Some('['), None) => matches_from(&pattern[1..], &name[1..]),
+            _ => false,
+        },
+        Some(&literal) => name.first() == Some(&literal) && matches_from(&pattern[1..], &name[1..]),
+    }
+}
+
+struct CharClass {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    fn matches(&self, ch: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi) != self.negated
+    }
+}
+
+/// Parse the inside of `[...]`, returning the class and the pattern after `]`
+fn parse_class(pattern: &[char]) -> Option<(CharClass, &[char])> {
+    let (negated, mut rest) = match pattern.first() {
+        Some('!') | Some('^') => (true, &pattern[1..]),
+        _ => (false, pattern),
+    };
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        match rest {
+            [] => return None,
+            [']', tail @ ..] if !first => return Some((CharClass { negated, ranges }, tail)),
+            [lo, '-', hi, tail @ ..] if *hi != ']' => {
+                ranges.push((*lo, *hi));
+                rest = tail;
+            }
+            [ch, tail @ ..] => {
+                ranges.push((*ch, *ch));
+                rest = tail;
+            }
+        }
+        first = false;
+    }
+}
@@ -0,0 +1,270 @@
+//! The symbol dependency graph, and recursive dependency detection.
+//!
+//! A symbol's value is computed from the symbols it depends on, the
+//! symbols that select it and the symbols that imply it. When following
+//! those relations leads back to the symbol, Linux reports a "recursive
+//! dependency", since the value cannot be settled; so does this module,
+//! naming each step of the cycle and where it is written.
+
+use crate::kconfig::ast::*;
+use crate::kconfig::diagnostic::{Diagnostic, DiagnosticCode};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// `depends on`, including the conditions of enclosing blocks
+    DependsOn,
+    /// A `select` of the symbol, written in the selecting entry
+    SelectedBy,
+    /// An `imply` of the symbol, written in the implying entry
+    ImpliedBy,
+}
+
+/// `from`'s value depends on `to`'s, because of the line at `span`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    pub span: Span,
+}
+
+/// Worded like Linux's recursive dependency report
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            EdgeKind::DependsOn => write!(f, "symbol {} depends on {}", self.from, self.to),
+            EdgeKind::SelectedBy => write!(f, "symbol {} is selected by {}", self.from, self.to),
+            EdgeKind::ImpliedBy => write!(f, "symbol {} is implied by {}", self.from, self.to),
+        }
+    }
+}
+
+/// A chain of edges that ends where it starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyCycle {
+    pub edges: Vec<Edge>,
+}
+
+impl DependencyCycle {
+    /// The symbols along the cycle, the first one repeated at the end
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.edges.iter().map(|e| e.from.clone()).collect();
+        symbols.extend(self.edges.first().map(|e| e.from.clone()));
+        symbols
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.edges.iter().any(|e| e.from == symbol)
+    }
+
+    /// The same cycle, starting at `symbol`
+    pub fn starting_at(&self, symbol: &str) -> Self {
+        let mut edges = self.edges.clone();
+        if let Some(start) = edges.iter().position(|e| e.from == symbol) {
+            edges.rotate_left(start);
+        }
+        Self { edges }
+    }
+
+    /// An error at the first edge, with every edge as a note
+    pub fn diagnostic(&self) -> Diagnostic {
+        let span = self
+            .edges
+            .first()
+            .map(|e| e.span.clone())
+            .unwrap_or_default();
+        let mut diagnostic = Diagnostic::error(
+            DiagnosticCode::RecursiveDependency,
+            format!(
+                "Recursive dependency detected: {}",
+                self.symbols().join(" -> ")
+            ),
+            span,
+        );
+        diagnostic.notes = self
+            .edges
+            .iter()
+            .map(|e| format!("{}: {}", e.span, e))
+            .collect();
+        diagnostic
+    }
+}
+
+/// Edges between symbols, built from the parsed entries
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// Defined symbols in declaration order
+    order: Vec<String>,
+    /// Symbol -> position in `order`
+    index: HashMap<String, usize>,
+    /// Symbol -> outgoing edges, in source order
+    edges: HashMap<String, Vec<Edge>>,
+}
+
+/// A symbol named in an expression, and where
+type Reference = (String, Span);
+
+enum Visit {
+    /// On the current path, at this depth
+    OnPath(usize),
+    Done,
+}
+
+impl DependencyGraph {
+    pub fn from_entries(entries: &[Entry]) -> Self {
+        let mut graph = Self::default();
+        graph.add_entries(entries);
+        graph
+    }
+
+    /// Add the symbols defined in `entries`
+    pub fn add_entries(&mut self, entries: &[Entry]) {
+        self.collect(entries, &[]);
+    }
+
+    fn collect(&mut self, entries: &[Entry], inherited: &[Reference]) {
+        for entry in entries {
+            match entry {
+                Entry::Config(Config {
+                    name, properties, ..
+                })
+                | Entry::MenuConfig(MenuConfig {
+                    name, properties, ..
+                }) => self.define(name, properties, inherited),
+                Entry::Choice(choice) => {
+                    let deps = with_references(inherited, choice.depends.as_ref());
+                    self.collect(&choice.entries, &deps);
+                }
+                Entry::Menu(menu) => {
+                    let deps = with_references(inherited, menu.depends.as_ref());
+                    self.collect(&menu.entries, &deps);
+                }
+                Entry::If(if_block) => {
+                    let deps = with_references(inherited, Some(&if_block.condition));
+                    self.collect(&if_block.entries, &deps);
+                }
+                Entry::Comment(_) | Entry::Source(_) | Entry::MainMenu(_) => {}
+            }
+        }
+    }
+
+    fn define(&mut self, name: &str, properties: &Property, inherited: &[Reference]) {
+        if !self.index.contains_key(name) {
+            self.index.insert(name.to_string(), self.order.len());
+            self.order.push(name.to_string());
+        }
+
+        for (dep, span) in with_references(inherited, properties.depends.as_ref()) {
+            self.add_edge(name, &dep, EdgeKind::DependsOn, span);
+        }
+
+        for (target, _, span) in &properties.select {
+            self.add_edge(target, name, EdgeKind::SelectedBy, span.clone());
+        }
+        for (target, _, span) in &properties.imply {
+            self.add_edge(target, name, EdgeKind::ImpliedBy, span.clone());
+        }
+    }
+
+    /// Add an edge, unless the same relation is already known
+    fn add_edge(&mut self, from: &str, to: &str, kind: EdgeKind, span: Span) {
+        let edges = self.edges.entry(from.to_string()).or_default();
+        if !edges.iter().any(|e| e.to == to && e.kind == kind) {
+            edges.push(Edge {
+                from: from.to_string(),
+                to: to.to_string(),
+                kind,
+                span,
+            });
+        }
+    }
+
+    /// The edges out of `symbol`, in source order
+    pub fn edges(&self, symbol: &str) -> &[Edge] {
+        self.edges.get(symbol).map_or(&[], Vec::as_slice)
+    }
+
+    /// One cycle for each edge that leads back into the current path of a
+    /// depth-first walk, each starting at its earliest declared symbol.
+    /// The walk keeps its own stack, so long chains cannot overflow.
+    pub fn cycles(&self) -> Vec<DependencyCycle> {
+        let mut visits: HashMap<&str, Visit> = HashMap::new();
+        let mut cycles = Vec::new();
+
+        for root in &self.order {
+            if visits.contains_key(root.as_str()) {
+                continue;
+            }
+            visits.insert(root, Visit::OnPath(0));
+            // Symbols on the path with their next edge, and the edge into
+            // each of them but the root
+            let mut stack: Vec<(&str, usize)> = vec![(root, 0)];
+            let mut path: Vec<&Edge> = Vec::new();
+
+            while let Some(&(symbol, next)) = stack.last() {
+                let Some(edge) = self.edges(symbol).get(next) else {
+                    visits.insert(symbol, Visit::Done);
+                    stack.pop();
+                    path.pop();
+                    continue;
+                };
+                if let Some(top) = stack.last_mut() {
+                    top.1 += 1;
+                }
+
+                match visits.get(edge.to.as_str()) {
+                    Some(Visit::Done) => {}
+                    Some(Visit::OnPath(depth)) => {
+                        let edges = path[*depth..]
+                            .iter()
+                            .copied()
+                            .chain([edge])
+                            .cloned()
+                            .collect();
+                        cycles.push(self.canonical(DependencyCycle { edges }));
+                    }
+                    None => {
+                        visits.insert(&edge.to, Visit::OnPath(stack.len()));
+                        stack.push((&edge.to, 0));
+                        path.push(edge);
+                    }
+                }
+            }
+        }
+        cycles
+    }
+
+    fn canonical(&self, cycle: DependencyCycle) -> DependencyCycle {
+        let first = cycle
+            .edges
+            .iter()
+            .min_by_key(|e| self.index.get(&e.from).copied().unwrap_or(usize::MAX))
+            .map(|e| e.from.clone());
+        match first {
+            Some(first) => cycle.starting_at(&first),
+            None => cycle,
+        }
+    }
+}
+
+/// `inherited`, followed by the symbols named in `expr`
+fn with_references(inherited: &[Reference], expr: Option<&Spanned<Expr>>) -> Vec<Reference> {
+    let mut references = inherited.to_vec();
+    for (name, span) in expr.iter().flat_map(|expr| expr.symbols()) {
+        if !matches!(name, "y" | "m" | "n") {
+            references.push((name.to_string(), span.clone()));
+        }
+    }
+    references
+}
+
+/// Every recursive dependency in `entries`, as errors
+pub fn recursive_dependencies(entries: &[Entry]) -> Vec<Diagnostic> {
+    DependencyGraph::from_entries(entries)
+        .cycles()
+        .iter()
+        .map(DependencyCycle::diagnostic)
+        .collect()
+}
@@ -1,4 +1,5 @@
 use crate::error::{KconfigError, Result};
+use crate::kconfig::diagnostic::DiagnosticCode;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -13,12 +14,17 @@ pub enum Token {
     If,
     EndIf,
     Source,
+    OSource,
+    RSource,
+    ORSource,
     Comment,
     Bool,
     Tristate,
     String,
     Int,
     Hex,
+    DefBool,
+    DefTristate,
     Prompt,
     Default,
     Depends,
@@ -28,6 +34,8 @@ pub enum Token {
     Help,
     Visible,
     Option,
+    Optional,
+    Transitional,
     On,
     MainMenu,
     Modules,
@@ -39,25 +47,25 @@ pub enum Token {
     ListNewConfig,
 
     // Operators
-    Eq,          // =
-    NotEq,       // !=
-    Less,        // <
-    LessEq,      // <=
-    Greater,     // >
-    GreaterEq,   // >=
-    And,         // &&
-    Or,          // ||
-    Not,         // !
-    
+    Eq,        // =
+    NotEq,     // !=
+    Less,      // <
+    LessEq,    // <=
+    Greater,   // >
+    GreaterEq, // >=
+    And,       // &&
+    Or,        // ||
+    Not,       // !
+
     // Literals
     Identifier(String),
     StringLit(String),
     Number(i64),
-    
+
     // Punctuation
-    LParen,      // (
-    RParen,      // )
-    
+    LParen, // (
+    RParen, // )
+
     // Special
     Newline,
     Eof,
@@ -67,6 +75,17 @@ pub struct Lexer {
     input: String,
     position: usize,
     line: usize,
+    /// Byte offset of the current line, for columns
+    line_start: usize,
+    /// Line and column of the last token, and the line it ends on
+    token_line: usize,
+    token_column: usize,
+    token_end_line: usize,
+    /// Byte offset where the last token starts
+    token_start: usize,
+    /// Whether the last token is the first one on its line
+    token_starts_line: bool,
+    line_has_token: bool,
     file: PathBuf,
 }
 
@@ -76,6 +95,13 @@ impl Lexer {
             input,
             position: 0,
             line: 1,
+            line_start: 0,
+            token_line: 1,
+            token_column: 1,
+            token_end_line: 1,
+            token_start: 0,
+            token_starts_line: true,
+            line_has_token: false,
             file,
         }
     }
@@ -88,9 +114,31 @@ impl Lexer {
         &self.file
     }
 
+    /// The line and column, both 1-based, where the last token starts
+    pub fn token_position(&self) -> (usize, usize) {
+        (self.token_line, self.token_column)
+    }
+
+    /// The line the last token, or the last help text line, ends on
+    pub fn token_end_line(&self) -> usize {
+        self.token_end_line
+    }
+
+    /// The source text of the last token, e.g. a number as written
+    pub fn token_text(&self) -> &str {
+        &self.input[self.token_start..self.position]
+    }
+
+    /// Whether the last token is the first one on its line
+    pub fn token_starts_line(&self) -> bool {
+        self.token_starts_line
+    }
+
+    /// Read the help text after a `help` keyword: the empty and indented
+    /// lines that follow it, without their common indentation
     pub fn skip_help_text(&mut self) -> String {
         let mut help = String::new();
-        
+
         // Skip any whitespace/newlines immediately after "help" keyword
         while let Some(ch) = self.current_char() {
             if ch == '\n' {
@@ -103,12 +151,12 @@ impl Lexer {
                 break;
             }
         }
-        
+
         // Now collect all indented lines
         loop {
             // Peek at the start of the line
             let _line_start = self.position;
-            
+
             // Check if this line is indented (starts with space or tab)
             match self.current_char() {
                 None => break, // EOF
@@ -121,6 +169,16 @@ impl Lexer {
                 Some(' ') | Some('\t') => {
                     // Indented line, this is help text
                     // Consume the whole line
+                    let line = self.line;
+                    if !self.input[self.position..]
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .trim()
+                        .is_empty()
+                    {
+                        self.token_end_line = line;
+                    }
                     while let Some(ch) = self.current_char() {
                         help.push(ch);
                         self.advance();
@@ -135,8 +193,10 @@ impl Lexer {
                 }
             }
         }
-        
-        help
+
+        // The help text ends at the start of a line
+        self.line_has_token = false;
+        normalize_help(&help)
     }
 
     fn current_char(&self) -> Option<char> {
@@ -152,6 +212,7 @@ impl Lexer {
         self.position += ch.len_utf8();
         if ch == '\n' {
             self.line += 1;
+            self.line_start = self.position;
         }
         Some(ch)
     }
@@ -221,14 +282,15 @@ impl Lexer {
 
         Err(KconfigError::Syntax {
             file: self.file.clone(),
-            line: self.line,
+            line: self.token_line,
+            code: DiagnosticCode::UnterminatedString,
             message: "Unterminated string literal".to_string(),
         })
     }
 
     fn read_number(&mut self) -> i64 {
         let mut result = String::new();
-        
+
         // Handle hex numbers
         if self.current_char() == Some('0') && self.peek_char(1) == Some('x') {
             self.advance(); // 0
@@ -257,6 +319,26 @@ impl Lexer {
     }
 
     pub fn next_token(&mut self) -> Result<Token> {
+        self.skip_blanks();
+        self.token_line = self.line;
+        self.token_column = self.input[self.line_start..self.position].chars().count() + 1;
+        self.token_start = self.position;
+        self.token_starts_line = !self.line_has_token;
+        self.line_has_token = true;
+
+        let token = self.read_token()?;
+        self.token_end_line = if token == Token::Newline {
+            self.token_line
+        } else {
+            self.line
+        };
+        if token == Token::Newline {
+            self.line_has_token = false;
+        }
+        Ok(token)
+    }
+
+    fn skip_blanks(&mut self) {
         loop {
             self.skip_whitespace();
 
@@ -267,7 +349,9 @@ impl Lexer {
 
             break;
         }
+    }
 
+    fn read_token(&mut self) -> Result<Token> {
         let ch = match self.current_char() {
             Some(c) => c,
             None => return Ok(Token::Eof),
@@ -341,6 +425,7 @@ impl Lexer {
             return Err(KconfigError::Syntax {
                 file: self.file.clone(),
                 line: self.line,
+                code: DiagnosticCode::InvalidCharacter,
                 message: "Expected '&&'".to_string(),
             });
         }
@@ -354,6 +439,7 @@ impl Lexer {
             return Err(KconfigError::Syntax {
                 file: self.file.clone(),
                 line: self.line,
+                code: DiagnosticCode::InvalidCharacter,
                 message: "Expected '||'".to_string(),
             });
         }
@@ -370,12 +456,17 @@ impl Lexer {
                 "if" => Token::If,
                 "endif" => Token::EndIf,
                 "source" => Token::Source,
+                "osource" => Token::OSource,
+                "rsource" => Token::RSource,
+                "orsource" => Token::ORSource,
                 "comment" => Token::Comment,
                 "bool" => Token::Bool,
                 "tristate" => Token::Tristate,
                 "string" => Token::String,
                 "int" => Token::Int,
                 "hex" => Token::Hex,
+                "def_bool" => Token::DefBool,
+                "def_tristate" => Token::DefTristate,
                 "prompt" => Token::Prompt,
                 "default" => Token::Default,
                 "depends" => Token::Depends,
@@ -385,6 +476,8 @@ impl Lexer {
                 "help" => Token::Help,
                 "visible" => Token::Visible,
                 "option" => Token::Option,
+                "optional" => Token::Optional,
+                "transitional" => Token::Transitional,
                 "on" => Token::On,
                 "mainmenu" => Token::MainMenu,
                 "modules" => Token::Modules,
@@ -395,9 +488,12 @@ impl Lexer {
             return Ok(token);
         }
 
+        // Skip it, so a parser recovering from the error moves on
+        self.advance();
         Err(KconfigError::Syntax {
             file: self.file.clone(),
             line: self.line,
+            code: DiagnosticCode::InvalidCharacter,
             message: format!("Unexpected character: '{}'", ch),
         })
     }
@@ -405,9 +501,64 @@ impl Lexer {
     pub fn peek_token(&mut self) -> Result<Token> {
         let saved_position = self.position;
         let saved_line = self.line;
+        let saved_line_start = self.line_start;
+        let saved_token = (
+            self.token_line,
+            self.token_column,
+            self.token_end_line,
+            self.token_start,
+            self.token_starts_line,
+            self.line_has_token,
+        );
         let token = self.next_token()?;
         self.position = saved_position;
         self.line = saved_line;
+        self.line_start = saved_line_start;
+        (
+            self.token_line,
+            self.token_column,
+            self.token_end_line,
+            self.token_start,
+            self.token_starts_line,
+            self.line_has_token,
+        ) = saved_token;
         Ok(token)
     }
 }
+
+/// Strip the indentation of the first help line from every line, as Linux
+/// does, counting tabs as 8 columns. Deeper indentation is kept as spaces
+/// and blank lines at the end are dropped.
+pub(crate) fn normalize_help(raw: &str) -> String {
+    let indent_width = |line: &str| {
+        line.chars()
+            .take_while(|ch| *ch == ' ' || *ch == '\t')
+            .fold(0usize, |col, ch| {
+                if ch == '\t' {
+                    (col / 8 + 1) * 8
+                } else {
+                    col + 1
+                }
+            })
+    };
+    let lines: Vec<&str> = raw.lines().map(str::trim_end).collect();
+    let indent = lines
+        .iter()
+        .find(|l| !l.is_empty())
+        .map_or(0, |l| indent_width(l));
+    let last = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(0, |i| i + 1);
+
+    let mut help = String::new();
+    for line in &lines[..last] {
+        if !line.is_empty() {
+            let text = line.trim_start_matches([' ', '\t']);
+            help.push_str(&" ".repeat(indent_width(line).saturating_sub(indent)));
+            help.push_str(text);
+        }
+        help.push('\n');
+    }
+    help
+}
@@ -0,0 +1,736 @@
+//! Static checks over a parsed Kconfig tree, run by `rkconf lint`.
+//!
+//! Each rule has a stable code and a name, and a level that can be
+//! changed: `allow` turns it off, `warn` and `deny` report it as a warning
+//! or an error. Findings are ordinary [`Diagnostic`]s.
+
+use crate::error::{KconfigError, Result};
+use crate::kconfig::ast::*;
+use crate::kconfig::dependency_resolver::and_deps;
+use crate::kconfig::diagnostic::{Diagnostic, DiagnosticCode, Severity};
+use crate::kconfig::value::{format_number, parse_number};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LintRule {
+    /// A symbol used in an expression, `select` or `imply` that no entry
+    /// defines
+    UndefinedSymbol,
+    /// Definitions of one symbol declaring different types
+    ConflictingType,
+    /// A `select` that does not guarantee the selected symbol's own
+    /// dependencies, so it can force the symbol on while they are unmet
+    SelectUnmetDependency,
+    /// A prompt the user sees without any help text
+    MissingHelp,
+    /// A symbol that can never be set
+    UnreachableSymbol,
+    /// A default that repeats an earlier one, or follows an unconditional one
+    DuplicateDefault,
+    /// An int or hex default outside the symbol's range
+    DefaultOutOfRange,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 7] = [
+        LintRule::UndefinedSymbol,
+        LintRule::ConflictingType,
+        LintRule::SelectUnmetDependency,
+        LintRule::MissingHelp,
+        LintRule::UnreachableSymbol,
+        LintRule::DuplicateDefault,
+        LintRule::DefaultOutOfRange,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            LintRule::UndefinedSymbol => "L0001",
+            LintRule::ConflictingType => "L0002",
+            LintRule::SelectUnmetDependency => "L0003",
+            LintRule::MissingHelp => "L0004",
+            LintRule::UnreachableSymbol => "L0005",
+            LintRule::DuplicateDefault => "L0006",
+            LintRule::DefaultOutOfRange => "L0007",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::UndefinedSymbol => "undefined-symbol",
+            LintRule::ConflictingType => "conflicting-type",
+            LintRule::SelectUnmetDependency => "select-unmet-dependency",
+            LintRule::MissingHelp => "missing-help",
+            LintRule::UnreachableSymbol => "unreachable-symbol",
+            LintRule::DuplicateDefault => "duplicate-default",
+            LintRule::DefaultOutOfRange => "default-out-of-range",
+        }
+    }
+
+    pub fn default_level(self) -> LintLevel {
+        match self {
+            LintRule::ConflictingType => LintLevel::Deny,
+            _ => LintLevel::Warn,
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Accepts the name or the code of a rule
+impl FromStr for LintRule {
+    type Err = KconfigError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        LintRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s || rule.code().eq_ignore_ascii_case(s))
+            .ok_or_else(|| KconfigError::Config(format!("Unknown lint rule '{}'", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    /// The severity findings are reported with, none if the rule is off
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = KconfigError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "allow" | "off" => Ok(LintLevel::Allow),
+            "warn" | "warning" => Ok(LintLevel::Warn),
+            "deny" | "error" => Ok(LintLevel::Deny),
+            other => Err(KconfigError::Config(format!(
+                "Invalid lint level '{}': expected allow, warn or deny",
+                other
+            ))),
+        }
+    }
+}
+
+/// The level of every rule
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    levels: HashMap<LintRule, LintLevel>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            levels: LintRule::ALL
+                .iter()
+                .map(|rule| (*rule, rule.default_level()))
+                .collect(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, rule: LintRule, level: LintLevel) {
+        self.levels.insert(rule, level);
+    }
+
+    pub fn level(&self, rule: LintRule) -> LintLevel {
+        self.levels
+            .get(&rule)
+            .copied()
+            .unwrap_or(rule.default_level())
+    }
+
+    /// Apply `rule = level` lines on top of the current levels. Blank lines
+    /// and lines starting with `#` are skipped.
+    ///
+    /// ```text
+    /// missing-help = allow
+    /// L0001 = deny
+    /// ```
+    pub fn apply_rules(&mut self, text: &str) -> Result<()> {
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (rule, level) = line.split_once('=').ok_or_else(|| {
+                KconfigError::Config(format!(
+                    "Line {}: expected 'rule = level', got '{}'",
+                    number + 1,
+                    line
+                ))
+            })?;
+            self.set(rule.parse()?, level.parse()?);
+        }
+        Ok(())
+    }
+
+    /// Apply a rules file, in the format of `apply_rules`
+    pub fn apply_rules_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|_| KconfigError::FileNotFound(path.to_path_buf()))?;
+        self.apply_rules(&text)
+    }
+}
+
+/// One `config`, `menuconfig` or choice option entry, with the conditions
+/// of its enclosing blocks folded into its dependencies
+struct Definition<'a> {
+    name: &'a str,
+    symbol_type: &'a SymbolType,
+    properties: &'a Property,
+    span: &'a Span,
+    depends: Option<Expr>,
+    in_choice: bool,
+}
+
+impl Definition<'_> {
+    /// The spans of attribute lines of the given kinds, in order
+    fn spans<'s>(&'s self, kinds: &'s [PropertyKind]) -> impl Iterator<Item = &'s Span> {
+        property_spans(&self.properties.spans, kinds)
+    }
+}
+
+fn property_spans<'s>(
+    spans: &'s [PropertySpan],
+    kinds: &'s [PropertyKind],
+) -> impl Iterator<Item = &'s Span> {
+    spans
+        .iter()
+        .filter(move |p| kinds.contains(&p.kind))
+        .map(|p| &p.span)
+}
+
+/// Run every rule that is not allowed over `ast`
+/// Returns: the findings, sorted by location
+pub fn lint(ast: &KconfigFile, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter::default();
+    linter.collect(&ast.entries, None);
+
+    let mut findings = Vec::new();
+    let mut report = |rule: LintRule, message: String, span: &Span| {
+        if let Some(severity) = config.level(rule).severity() {
+            findings.push(Diagnostic {
+                severity,
+                code: DiagnosticCode::Lint(rule),
+                message,
+                span: span.clone(),
+                notes: Vec::new(),
+            });
+        }
+    };
+
+    linter.undefined_symbols(&mut report);
+    linter.conflicting_types(&mut report);
+    linter.select_unmet_dependencies(&mut report);
+    linter.missing_help(&mut report);
+    linter.unreachable_symbols(&mut report);
+    linter.duplicate_defaults(&mut report);
+    linter.defaults_out_of_range(&mut report);
+
+    findings.sort_by(|a, b| {
+        (&a.span.file, a.span.line, a.span.column, a.code.as_str()).cmp(&(
+            &b.span.file,
+            b.span.line,
+            b.span.column,
+            b.code.as_str(),
+        ))
+    });
+    findings
+}
+
+#[derive(Default)]
+struct Linter<'a> {
+    definitions: Vec<Definition<'a>>,
+    /// Symbol name -> indexes into `definitions`, in order
+    by_name: HashMap<&'a str, Vec<usize>>,
+    /// Every symbol name, named choices included
+    defined: HashSet<&'a str>,
+    /// Expressions of menus, `if` blocks, choices and comments
+    block_exprs: Vec<&'a Spanned<Expr>>,
+    /// Choices, whose `default` lines are checked too
+    choices: Vec<&'a Choice>,
+    selected: HashSet<&'a str>,
+    implied: HashSet<&'a str>,
+}
+
+type Report<'r> = dyn FnMut(LintRule, String, &Span) + 'r;
+
+impl<'a> Linter<'a> {
+    fn collect(&mut self, entries: &'a [Entry], parent_deps: Option<&Expr>) {
+        for entry in entries {
+            match entry {
+                Entry::Config(Config {
+                    name,
+                    symbol_type,
+                    properties,
+                    span,
+                })
+                | Entry::MenuConfig(MenuConfig {
+                    name,
+                    symbol_type,
+                    properties,
+                    span,
+                }) => self.define(name, symbol_type, properties, span, parent_deps, false),
+                Entry::Choice(choice) => {
+                    let deps = and_deps(parent_deps, choice.depends.as_deref());
+                    self.collect_options(&choice.entries, deps.as_ref());
+                    if let Some(name) = &choice.name {
+                        self.defined.insert(name);
+                    }
+                    self.block_exprs.extend(&choice.prompt_condition);
+                    self.block_exprs.extend(
+                        choice
+                            .defaults
+                            .iter()
+                            .flat_map(|(_, condition, _)| condition),
+                    );
+                    self.block_exprs.extend(&choice.depends);
+                    self.choices.push(choice);
+                }
+                Entry::Menu(menu) => {
+                    self.block_exprs.extend(&menu.depends);
+                    self.block_exprs.extend(&menu.visible);
+                    let deps = and_deps(parent_deps, menu.depends.as_deref());
+                    self.collect(&menu.entries, deps.as_ref());
+                }
+                Entry::If(if_block) => {
+                    self.block_exprs.push(&if_block.condition);
+                    let deps = and_deps(parent_deps, Some(&if_block.condition.node));
+                    self.collect(&if_block.entries, deps.as_ref());
+                }
+                Entry::Comment(comment) => self.block_exprs.extend(&comment.depends),
+                Entry::Source(_) | Entry::MainMenu(_) => {}
+            }
+        }
+    }
+
+    /// Like `collect`, for the body of a choice
+    fn collect_options(&mut self, entries: &'a [Entry], parent_deps: Option<&Expr>) {
+        for entry in entries {
+            match entry {
+                Entry::Config(Config {
+                    name,
+                    symbol_type,
+                    properties,
+                    span,
+                }) => self.define(name, symbol_type, properties, span, parent_deps, true),
+                Entry::If(if_block) => {
+                    self.block_exprs.push(&if_block.condition);
+                    let deps = and_deps(parent_deps, Some(&if_block.condition.node));
+                    self.collect_options(&if_block.entries, deps.as_ref());
+                }
+                _ => self.collect(std::slice::from_ref(entry), parent_deps),
+            }
+        }
+    }
+
+    fn define(
+        &mut self,
+        name: &'a str,
+        symbol_type: &'a SymbolType,
+        properties: &'a Property,
+        span: &'a Span,
+        parent_deps: Option<&Expr>,
+        in_choice: bool,
+    ) {
+        self.by_name
+            .entry(name)
+            .or_default()
+            .push(self.definitions.len());
+        self.defined.insert(name);
+        self.selected
+            .extend(properties.select.iter().map(|(s, _, _)| s.as_str()));
+        self.implied
+            .extend(properties.imply.iter().map(|(s, _, _)| s.as_str()));
+        self.definitions.push(Definition {
+            name,
+            symbol_type,
+            properties,
+            span,
+            depends: and_deps(parent_deps, properties.depends.as_deref()),
+            in_choice,
+        });
+    }
+
+    fn is_defined(&self, name: &str) -> bool {
+        matches!(name, "y" | "m" | "n") || self.defined.contains(name)
+    }
+
+    /// Definitions of each symbol, in declaration order of first definition
+    fn symbols(&self) -> Vec<(&'a str, Vec<&Definition<'a>>)> {
+        let mut seen = HashSet::new();
+        self.definitions
+            .iter()
+            .filter(|d| seen.insert(d.name))
+            .map(|d| {
+                (
+                    d.name,
+                    self.by_name[d.name]
+                        .iter()
+                        .map(|&i| &self.definitions[i])
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// The conjuncts every definition of `name` depends on; `None` if one
+    /// of them has no dependencies
+    fn required_deps(&self, name: &str) -> Option<Vec<Expr>> {
+        let mut required: Option<Vec<Expr>> = None;
+        for &i in self.by_name.get(name)? {
+            let conjuncts = conjuncts(self.definitions[i].depends.as_ref()?);
+            required = Some(match required {
+                None => conjuncts,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter(|c| conjuncts.contains(c))
+                    .collect(),
+            });
+        }
+        required.filter(|r| !r.is_empty())
+    }
+
+    fn undefined_symbols(&self, report: &mut Report) {
+        let exprs = self
+            .definitions
+            .iter()
+            .flat_map(|d| d.properties.exprs())
+            .chain(self.block_exprs.iter().copied());
+
+        for expr in exprs {
+            for (name, span) in expr.symbols() {
+                if !self.is_defined(name) {
+                    report(
+                        LintRule::UndefinedSymbol,
+                        format!("Symbol {} is not defined", name),
+                        span,
+                    );
+                }
+            }
+        }
+
+        for definition in &self.definitions {
+            let properties = definition.properties;
+            let targets = [
+                (&properties.select, "selects"),
+                (&properties.imply, "implies"),
+            ];
+            for (list, verb) in targets {
+                for (target, _, span) in list {
+                    if !self.is_defined(target) {
+                        let message = format!(
+                            "{} {} {}, which is not defined",
+                            definition.name, verb, target
+                        );
+                        report(LintRule::UndefinedSymbol, message, span);
+                    }
+                }
+            }
+        }
+    }
+
+    fn conflicting_types(&self, report: &mut Report) {
+        for (_, definitions) in self.symbols() {
+            let mut declared: Option<(&SymbolType, &Span)> = None;
+            for definition in definitions {
+                // Definitions without a type line take the symbol's type
+                let Some(span) = definition
+                    .spans(&[PropertyKind::Type, PropertyKind::DefType])
+                    .next()
+                else {
+                    continue;
+                };
+                let symbol_type = definition.symbol_type;
+                match &declared {
+                    None => declared = Some((symbol_type, span)),
+                    Some((first, first_span)) if *first != symbol_type => {
+                        let message = format!(
+                            "{} is declared {} here, but {} at {}",
+                            definition.name,
+                            type_name(symbol_type),
+                            type_name(first),
+                            first_span
+                        );
+                        report(LintRule::ConflictingType, message, span);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    fn select_unmet_dependencies(&self, report: &mut Report) {
+        for definition in &self.definitions {
+            for (target, condition, span) in &definition.properties.select {
+                let Some(required) = self.required_deps(target) else {
+                    continue;
+                };
+
+                // What holds whenever this select applies
+                let mut known = vec![Expr::Symbol(definition.name.to_string())];
+                known.extend(definition.depends.iter().flat_map(conjuncts));
+                known.extend(condition.as_deref().into_iter().flat_map(conjuncts));
+
+                let missing: Vec<String> = required
+                    .iter()
+                    .filter(|c| !known.contains(c) && **c != Expr::Symbol("y".to_string()))
+                    .map(|c| match c {
+                        Expr::Or(..) => format!("({})", c),
+                        _ => c.to_string(),
+                    })
+                    .collect();
+                if !missing.is_empty() {
+                    let message = format!(
+                        "{} selects {}, which also depends on {}; the select can force it on while that is unmet",
+                        definition.name,
+                        target,
+                        missing.join(" && ")
+                    );
+                    report(LintRule::SelectUnmetDependency, message, span);
+                }
+            }
+        }
+    }
+
+    fn missing_help(&self, report: &mut Report) {
+        for (name, definitions) in self.symbols() {
+            if definitions.iter().any(|d| d.properties.help.is_some()) {
+                continue;
+            }
+            let prompt = definitions.iter().find(|d| d.properties.prompt.is_some());
+            if let Some(definition) = prompt {
+                let span = definition
+                    .spans(&[PropertyKind::Type, PropertyKind::Prompt])
+                    .next()
+                    .unwrap_or(definition.span);
+                report(
+                    LintRule::MissingHelp,
+                    format!("{} has a prompt but no help text", name),
+                    span,
+                );
+            }
+        }
+    }
+
+    fn unreachable_symbols(&self, report: &mut Report) {
+        let mut reachable = HashMap::new();
+        for (name, definitions) in self.symbols() {
+            let first = definitions[0];
+            if !self.is_reachable(name, &mut reachable, &mut HashSet::new()) {
+                let message = format!("{} can never be set: its dependencies are never met", name);
+                report(LintRule::UnreachableSymbol, message, first.span);
+                continue;
+            }
+
+            let settable = definitions.iter().any(|d| {
+                d.in_choice
+                    || d.properties.prompt.is_some()
+                    || !d.properties.defaults.is_empty()
+                    || d.properties.env.is_some()
+                    || d.properties.transitional
+            });
+            if !settable && !self.selected.contains(name) && !self.implied.contains(name) {
+                let message = format!(
+                    "{} can never be set: it has no prompt or default, and nothing selects or implies it",
+                    name
+                );
+                report(LintRule::UnreachableSymbol, message, first.span);
+            }
+        }
+    }
+
+    /// Whether `name` can ever be enabled: it is selected, or some
+    /// definition's dependencies are not always `n`. Symbols on a
+    /// dependency cycle are assumed reachable.
+    fn is_reachable(
+        &self,
+        name: &'a str,
+        memo: &mut HashMap<&'a str, bool>,
+        visiting: &mut HashSet<&'a str>,
+    ) -> bool {
+        if let Some(&known) = memo.get(name) {
+            return known;
+        }
+        let Some(indexes) = self.by_name.get(name) else {
+            return false;
+        };
+        if self.selected.contains(name) || !visiting.insert(name) {
+            return true;
+        }
+
+        let reachable = indexes.iter().any(|&i| {
+            let definition = &self.definitions[i];
+            definition.depends.as_ref().is_none_or(|deps| {
+                conjuncts(deps).iter().all(|c| match c {
+                    Expr::Symbol(s) | Expr::Const(s) if s == "n" => false,
+                    Expr::Symbol(s) if matches!(s.as_str(), "y" | "m") => true,
+                    Expr::Symbol(s) => {
+                        let Some((&key, _)) = self.by_name.get_key_value(s.as_str()) else {
+                            return false;
+                        };
+                        self.is_reachable(key, memo, visiting)
+                    }
+                    _ => true,
+                })
+            })
+        });
+
+        visiting.remove(name);
+        memo.insert(name, reachable);
+        reachable
+    }
+
+    fn duplicate_defaults(&self, report: &mut Report) {
+        for (name, definitions) in self.symbols() {
+            let defaults = definitions.iter().flat_map(|d| {
+                d.properties
+                    .defaults
+                    .iter()
+                    .map(|(value, condition, span)| (&value.node, condition.as_deref(), span))
+            });
+            check_defaults(name, defaults, report);
+        }
+        for choice in &self.choices {
+            let name = choice.name.as_deref().unwrap_or("choice");
+            let options: Vec<Expr> = choice
+                .defaults
+                .iter()
+                .map(|(option, _, _)| Expr::Symbol(option.clone()))
+                .collect();
+            let defaults = options
+                .iter()
+                .zip(&choice.defaults)
+                .map(|(option, (_, condition, span))| (option, condition.as_deref(), span));
+            check_defaults(name, defaults, report);
+        }
+    }
+
+    fn defaults_out_of_range(&self, report: &mut Report) {
+        for (name, definitions) in self.symbols() {
+            let Some(symbol_type) = definitions
+                .iter()
+                .map(|d| d.symbol_type)
+                .find(|t| matches!(t, SymbolType::Int | SymbolType::Hex))
+            else {
+                continue;
+            };
+            // Constants are read as the engine reads them, so a hex range
+            // may be written without `0x`
+            let number = |expr: &Expr| match expr {
+                Expr::Const(value) => parse_number(symbol_type, value),
+                _ => None,
+            };
+            // The first unconditional range with constant bounds applies
+            let range = definitions
+                .iter()
+                .flat_map(|d| &d.properties.ranges)
+                .find_map(|(low, high, condition, _)| match condition {
+                    None => Some((number(low)?, number(high)?)),
+                    Some(_) => None,
+                });
+            let Some((low, high)) = range else {
+                continue;
+            };
+
+            for definition in definitions {
+                for (value, _, span) in &definition.properties.defaults {
+                    match number(value) {
+                        Some(n) if n < low || n > high => {
+                            let message = format!(
+                                "Default {} of {} is outside its range {} to {}",
+                                value,
+                                name,
+                                format_number(symbol_type, low),
+                                format_number(symbol_type, high)
+                            );
+                            report(LintRule::DefaultOutOfRange, message, span);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Report defaults that repeat an earlier one, or that come after a
+/// default without condition and so never apply
+fn check_defaults<'d>(
+    name: &str,
+    defaults: impl Iterator<Item = (&'d Expr, Option<&'d Expr>, &'d Span)>,
+    report: &mut Report,
+) {
+    let mut seen: Vec<(&Expr, Option<&Expr>, &Span)> = Vec::new();
+    let mut unconditional: Option<&Span> = None;
+
+    for (value, condition, span) in defaults {
+        if let Some(first) = unconditional {
+            let message = format!(
+                "Default {} of {} never applies: the default at {} always does",
+                value, name, first
+            );
+            report(LintRule::DuplicateDefault, message, span);
+        } else if let Some((_, _, first)) =
+            seen.iter().find(|(v, c, _)| *v == value && *c == condition)
+        {
+            let message = format!(
+                "Default {} of {} repeats the default at {}",
+                value, name, first
+            );
+            report(LintRule::DuplicateDefault, message, span);
+        }
+        if condition.is_none() && unconditional.is_none() {
+            unconditional = Some(span);
+        }
+        seen.push((value, condition, span));
+    }
+}
+
+/// The operands of a chain of `&&`
+fn conjuncts(expr: &Expr) -> Vec<Expr> {
+    match expr {
+        Expr::And(left, right) => {
+            let mut all = conjuncts(left);
+            all.extend(conjuncts(right));
+            all
+        }
+        _ => vec![expr.clone()],
+    }
+}
+
+fn type_name(symbol_type: &SymbolType) -> &'static str {
+    match symbol_type {
+        SymbolType::Bool => "bool",
+        SymbolType::Tristate => "tristate",
+        SymbolType::String => "string",
+        SymbolType::Int => "int",
+        SymbolType::Hex => "hex",
+    }
+}
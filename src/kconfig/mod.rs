@@ -1,11 +1,28 @@
 pub mod ast;
+pub mod cst;
+pub mod dependency_resolver;
+pub mod diagnostic;
 pub mod expr;
+pub mod format;
+mod glob;
+pub mod graph;
 pub mod lexer;
+pub mod lint;
 pub mod parser;
+pub mod preprocess;
 pub mod symbol;
+pub mod value;
 
 pub use ast::*;
+pub use cst::*;
+pub use dependency_resolver::*;
+pub use diagnostic::*;
 pub use expr::*;
+pub use format::*;
+pub use graph::*;
 pub use lexer::*;
+pub use lint::*;
 pub use parser::*;
+pub use preprocess::*;
 pub use symbol::*;
+pub use value::*;
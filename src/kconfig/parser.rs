@@ -1,16 +1,24 @@
 use crate::error::{KconfigError, Result};
 use crate::kconfig::ast::*;
+use crate::kconfig::diagnostic::{Diagnostic, DiagnosticCode};
+use crate::kconfig::glob;
 use crate::kconfig::lexer::{Lexer, Token};
-use std::collections::HashSet;
+use crate::kconfig::preprocess::Preprocessor;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub struct Parser {
     current_file: PathBuf,
     srctree: PathBuf,
     file_stack: Vec<FileContext>,
     parsed_files: HashSet<PathBuf>,
+    /// Directories listed to expand wildcard `source` paths
+    scanned_dirs: BTreeSet<PathBuf>,
     inclusion_chain: Vec<PathBuf>,
+    preprocessor: Preprocessor,
+    diagnostics: Vec<Diagnostic>,
+    first_error: Option<KconfigError>,
 }
 
 #[allow(dead_code)]
@@ -18,18 +26,24 @@ struct FileContext {
     file_path: PathBuf,
     lexer: Lexer,
     current_token: Token,
+    /// The line the last consumed token ends on
+    last_end_line: usize,
+    /// `menu` and `if` blocks of this file whose end is not reached yet
+    open_blocks: usize,
 }
 
 impl Parser {
     pub fn new(kconfig_path: impl AsRef<Path>, srctree: impl AsRef<Path>) -> Result<Self> {
-        let kconfig_path = kconfig_path.as_ref().to_path_buf();
+        let kconfig_path = normalize_path(kconfig_path.as_ref());
         let srctree = srctree.as_ref().to_path_buf();
-        
+
         if !kconfig_path.exists() {
             return Err(KconfigError::FileNotFound(kconfig_path));
         }
 
+        let mut preprocessor = Preprocessor::new();
         let content = fs::read_to_string(&kconfig_path)?;
+        let content = preprocessor.process(&content, &kconfig_path)?;
         let mut lexer = Lexer::new(content, kconfig_path.clone());
         let current_token = lexer.next_token()?;
 
@@ -43,12 +57,23 @@ impl Parser {
                 file_path: kconfig_path.clone(),
                 lexer,
                 current_token,
+                last_end_line: 1,
+                open_blocks: 0,
             }],
             parsed_files,
+            scanned_dirs: BTreeSet::new(),
             inclusion_chain: vec![kconfig_path],
+            preprocessor,
+            diagnostics: Vec::new(),
+            first_error: None,
         })
     }
 
+    /// The macro preprocessor, holding every variable defined so far
+    pub fn preprocessor(&self) -> &Preprocessor {
+        &self.preprocessor
+    }
+
     fn current_context(&self) -> &FileContext {
         self.file_stack.last().expect("File stack is empty")
     }
@@ -59,18 +84,64 @@ impl Parser {
 
     fn advance(&mut self) -> Result<()> {
         let ctx = self.current_context_mut();
+        if !matches!(ctx.current_token, Token::Newline | Token::Eof) {
+            ctx.last_end_line = ctx.lexer.token_end_line();
+        }
         ctx.current_token = ctx.lexer.next_token()?;
         Ok(())
     }
 
+    /// A syntax error at the current token
+    fn syntax_error(&self, code: DiagnosticCode, message: impl Into<String>) -> KconfigError {
+        KconfigError::Syntax {
+            file: self.current_file.clone(),
+            line: self.current_context().lexer.token_position().0,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// A span starting at the current token
+    fn start_span(&self) -> Span {
+        let (line, column) = self.current_context().lexer.token_position();
+        Span {
+            file: self.current_file.clone(),
+            line,
+            column,
+            end_line: line,
+        }
+    }
+
+    /// `start` extended to the last consumed token
+    fn end_span(&self, start: Span) -> Span {
+        let end_line = self.current_context().last_end_line.max(start.line);
+        Span { end_line, ..start }
+    }
+
+    /// An expression node built from `start` on
+    fn spanned(&self, expr: Expr, start: &Span) -> Spanned<Expr> {
+        Spanned::new(expr, self.end_span(start.clone()))
+    }
+
+    /// Close an attribute line started at `start`
+    fn property_span(&self, kind: PropertyKind, start: Span) -> PropertySpan {
+        PropertySpan {
+            kind,
+            span: self.end_span(start),
+        }
+    }
+
     fn expect(&mut self, expected: Token) -> Result<()> {
         let current = self.current_context().current_token.clone();
         if std::mem::discriminant(&current) != std::mem::discriminant(&expected) {
-            return Err(KconfigError::Syntax {
-                file: self.current_file.clone(),
-                line: self.current_context().lexer.current_line(),
-                message: format!("Expected {:?}, got {:?}", expected, current),
-            });
+            // A block cut short by the end of the file or another block
+            let code = match expected {
+                Token::EndMenu | Token::EndIf | Token::EndChoice => DiagnosticCode::UnclosedBlock,
+                _ => DiagnosticCode::UnexpectedToken,
+            };
+            return Err(
+                self.syntax_error(code, format!("Expected {:?}, got {:?}", expected, current))
+            );
         }
         self.advance()
     }
@@ -82,16 +153,41 @@ impl Parser {
         Ok(())
     }
 
-    // Handle source directive with recursion detection
-    fn handle_source(&mut self, path_expr: String) -> Result<Vec<Entry>> {
-        // Resolve the path relative to srctree
-        let source_path = self.srctree.join(&path_expr);
-        
-        // Check if file exists
-        if !source_path.exists() {
-            return Err(KconfigError::FileNotFound(source_path));
+    // Handle source directive: resolve the path, expand wildcards and
+    // parse every matching file in sorted order
+    fn handle_source(&mut self, source: &Source) -> Result<Vec<Entry>> {
+        // Relative to srctree, or to the including file for rsource
+        let base = if source.relative {
+            self.current_file
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        } else {
+            self.srctree.clone()
+        };
+        let pattern = base.join(&source.path);
+
+        let files = if glob::is_pattern(&source.path.to_string_lossy()) {
+            glob::expand(&pattern, &mut self.scanned_dirs)
+        } else if pattern.exists() {
+            vec![pattern.clone()]
+        } else {
+            Vec::new()
+        };
+
+        if files.is_empty() && !source.optional {
+            return Err(KconfigError::FileNotFound(pattern));
+        }
+
+        let mut entries = Vec::new();
+        for file in files {
+            entries.extend(self.parse_source_file(normalize_path(&file))?);
         }
+        Ok(entries)
+    }
 
+    // Parse one sourced file with recursion detection
+    fn parse_source_file(&mut self, source_path: PathBuf) -> Result<Vec<Entry>> {
         // Check for circular dependency
         if self.inclusion_chain.contains(&source_path) {
             let chain = self
@@ -113,20 +209,23 @@ impl Parser {
 
         // Mark as parsed
         self.parsed_files.insert(source_path.clone());
-        self.inclusion_chain.push(source_path.clone());
 
-        // Read the source file
+        // Read and preprocess the source file
         let content = fs::read_to_string(&source_path)?;
+        let content = self.preprocessor.process(&content, &source_path)?;
         let mut lexer = Lexer::new(content, source_path.clone());
         let current_token = lexer.next_token()?;
 
         // Push new file context
+        self.inclusion_chain.push(source_path.clone());
         let old_file = self.current_file.clone();
         self.current_file = source_path.clone();
         self.file_stack.push(FileContext {
             file_path: source_path.clone(),
             lexer,
             current_token,
+            last_end_line: 1,
+            open_blocks: 0,
         });
 
         // Parse the source file
@@ -140,94 +239,210 @@ impl Parser {
         Ok(entries)
     }
 
+    /// Parse the whole tree. Syntax errors do not stop the parser: it
+    /// records them, skips to the next entry and goes on, so `diagnostics`
+    /// lists every problem found. The first error is returned.
     pub fn parse(&mut self) -> Result<KconfigFile> {
         let entries = self.parse_entries()?;
+        if let Some(error) = self.first_error.take() {
+            return Err(error);
+        }
         Ok(KconfigFile {
             path: self.inclusion_chain[0].clone(),
             entries,
         })
     }
 
+    /// Every file read so far, the top-level Kconfig included, sorted
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.parsed_files.iter().cloned().collect();
+        files.sort();
+        files
+    }
+
+    /// Every directory listed so far to expand a wildcard `source`, sorted
+    pub fn scanned_dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.scanned_dirs.iter()
+    }
+
+    /// Errors and warnings found so far, in the order they were found
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
     fn parse_entries(&mut self) -> Result<Vec<Entry>> {
         let mut entries = Vec::new();
 
-        self.skip_newlines()?;
-
         while !matches!(self.current_context().current_token, Token::Eof) {
-            match &self.current_context().current_token.clone() {
-                Token::Config => {
-                    entries.push(Entry::Config(self.parse_config()?));
-                }
-                Token::MenuConfig => {
-                    entries.push(Entry::MenuConfig(self.parse_menuconfig()?));
-                }
-                Token::Choice => {
-                    entries.push(Entry::Choice(self.parse_choice()?));
-                }
-                Token::Menu => {
-                    entries.push(Entry::Menu(self.parse_menu()?));
-                }
-                Token::If => {
-                    entries.push(Entry::If(self.parse_if()?));
-                }
-                Token::Source => {
-                    self.advance()?; // consume 'source'
-                    let path = self.parse_string()?;
-                    self.skip_newlines()?;
-                    
-                    // Recursively parse the source file
-                    let source_entries = self.handle_source(path.clone())?;
-                    entries.extend(source_entries);
-                    
-                    // Also add the source entry itself
-                    entries.push(Entry::Source(Source {
-                        path: PathBuf::from(path),
-                    }));
-                }
-                Token::Comment => {
-                    entries.push(Entry::Comment(self.parse_comment()?));
+            let start = self.start_span();
+            match self.parse_entry(&mut entries) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => {
+                    self.report(error, &start);
+                    self.recover(&start);
                 }
-                Token::MainMenu => {
-                    self.advance()?; // consume 'mainmenu'
-                    let title = self.parse_string()?;
-                    self.skip_newlines()?;
-                    entries.push(Entry::MainMenu(title));
-                }
-                Token::EndMenu | Token::EndIf | Token::EndChoice => {
-                    // End of block
-                    break;
-                }
-                Token::Newline => {
-                    self.advance()?;
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Parse one entry into `entries`
+    /// Returns: false at the end of the enclosing block
+    fn parse_entry(&mut self, entries: &mut Vec<Entry>) -> Result<bool> {
+        match &self.current_context().current_token.clone() {
+            Token::Config => {
+                entries.push(Entry::Config(self.parse_config()?));
+            }
+            Token::MenuConfig => {
+                entries.push(Entry::MenuConfig(self.parse_menuconfig()?));
+            }
+            Token::Choice => {
+                entries.push(Entry::Choice(self.parse_choice()?));
+            }
+            Token::Menu => {
+                entries.push(Entry::Menu(self.parse_menu()?));
+            }
+            Token::If => {
+                entries.push(Entry::If(self.parse_if()?));
+            }
+            token @ (Token::Source | Token::OSource | Token::RSource | Token::ORSource) => {
+                let optional = matches!(token, Token::OSource | Token::ORSource);
+                let relative = matches!(token, Token::RSource | Token::ORSource);
+                let start = self.start_span();
+                self.advance()?; // consume the keyword
+                let path = PathBuf::from(self.parse_string()?);
+                let source = Source {
+                    path,
+                    optional,
+                    relative,
+                    span: self.end_span(start),
+                };
+                self.skip_newlines()?;
+
+                // Recursively parse the source file
+                let source_entries = self.handle_source(&source)?;
+                entries.extend(source_entries);
+
+                // Also add the source entry itself
+                entries.push(Entry::Source(source));
+            }
+            Token::Comment => {
+                entries.push(Entry::Comment(self.parse_comment()?));
+            }
+            Token::MainMenu => {
+                let start = self.start_span();
+                self.advance()?; // consume 'mainmenu'
+                let title = self.parse_string()?;
+                let span = self.end_span(start);
+                self.skip_newlines()?;
+                entries.push(Entry::MainMenu(MainMenu { title, span }));
+            }
+            token @ (Token::EndMenu | Token::EndIf | Token::EndChoice) => {
+                // End of block, unless no block of this file is open
+                if self.current_context().open_blocks > 0 {
+                    return Ok(false);
                 }
-                Token::Eof => break,
-                _ => {
-                    return Err(KconfigError::Syntax {
-                        file: self.current_file.clone(),
-                        line: self.current_context().lexer.current_line(),
-                        message: format!(
-                            "Unexpected token: {:?}",
-                            self.current_context().current_token
-                        ),
-                    });
+                let message = format!("{:?} without a block to close", token);
+                return Err(self.syntax_error(DiagnosticCode::UnmatchedEnd, message));
+            }
+            Token::Newline => {
+                self.advance()?;
+            }
+            Token::Eof => return Ok(false),
+            token => {
+                let message = format!("Unexpected token: {:?}", token);
+                return Err(self.syntax_error(DiagnosticCode::UnexpectedToken, message));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Record an error as a diagnostic; the first one is kept for `parse`
+    /// to return. `start` is where the failed entry began.
+    fn report(&mut self, error: KconfigError, start: &Span) {
+        let span = match &error {
+            KconfigError::Syntax { file, line, .. } | KconfigError::Macro { file, line, .. } => {
+                // Errors at the current token point at it, others at their line
+                let (token_line, token_column) = self.current_context().lexer.token_position();
+                let column = if *file == self.current_file && *line == token_line {
+                    token_column
+                } else {
+                    1
+                };
+                Span {
+                    file: file.clone(),
+                    line: *line,
+                    column,
+                    end_line: *line,
                 }
             }
+            _ => self.end_span(start.clone()),
+        };
+        self.diagnostics.push(Diagnostic::from_error(&error, span));
+        self.first_error.get_or_insert(error);
+    }
+
+    fn warn(&mut self, code: DiagnosticCode, message: impl Into<String>, span: Span) {
+        self.diagnostics
+            .push(Diagnostic::warning(code, message, span));
+    }
+
+    /// Skip to the next keyword that starts an entry at the beginning of a
+    /// line. At least one token is skipped if the failed entry, which
+    /// began at `start`, consumed nothing.
+    fn recover(&mut self, start: &Span) {
+        let (line, column) = self.current_context().lexer.token_position();
+        if start.file == self.current_file && (start.line, start.column) == (line, column) {
+            self.skip_token(start);
         }
 
-        Ok(entries)
+        loop {
+            let ctx = self.current_context();
+            let at_entry = ctx.lexer.token_starts_line() && starts_entry(&ctx.current_token);
+            if at_entry || matches!(ctx.current_token, Token::Eof) {
+                break;
+            }
+            self.skip_token(start);
+        }
+    }
+
+    /// Skip the current token, or the help text after `help`, reporting
+    /// lexer errors on the way
+    fn skip_token(&mut self, start: &Span) {
+        let skipped = if matches!(self.current_context().current_token, Token::Help) {
+            self.skip_help().map(|_| ())
+        } else {
+            self.advance()
+        };
+        if let Err(error) = skipped {
+            self.report(error, start);
+        }
+    }
+
+    /// Read the help text after the current `help` token
+    fn skip_help(&mut self) -> Result<String> {
+        // Don't advance yet - skip help text directly from lexer
+        let ctx = self.current_context_mut();
+        let help_text = ctx.lexer.skip_help_text();
+        ctx.last_end_line = ctx.lexer.token_end_line();
+        // Now get the next token after skipping help
+        ctx.current_token = ctx.lexer.next_token()?;
+        Ok(help_text)
     }
 
     fn parse_config(&mut self) -> Result<Config> {
+        let start = self.start_span();
         self.advance()?; // consume 'config'
-        
+
         let name = match &self.current_context().current_token {
             Token::Identifier(s) => s.clone(),
             _ => {
-                return Err(KconfigError::Syntax {
-                    file: self.current_file.clone(),
-                    line: self.current_context().lexer.current_line(),
-                    message: "Expected identifier after 'config'".to_string(),
-                });
+                return Err(self.syntax_error(
+                    DiagnosticCode::ExpectedIdentifier,
+                    "Expected identifier after 'config'",
+                ));
             }
         };
         self.advance()?;
@@ -239,20 +454,21 @@ impl Parser {
             name,
             symbol_type,
             properties,
+            span: self.end_span(start),
         })
     }
 
     fn parse_menuconfig(&mut self) -> Result<MenuConfig> {
+        let start = self.start_span();
         self.advance()?; // consume 'menuconfig'
-        
+
         let name = match &self.current_context().current_token {
             Token::Identifier(s) => s.clone(),
             _ => {
-                return Err(KconfigError::Syntax {
-                    file: self.current_file.clone(),
-                    line: self.current_context().lexer.current_line(),
-                    message: "Expected identifier after 'menuconfig'".to_string(),
-                });
+                return Err(self.syntax_error(
+                    DiagnosticCode::ExpectedIdentifier,
+                    "Expected identifier after 'menuconfig'",
+                ));
             }
         };
         self.advance()?;
@@ -264,6 +480,7 @@ impl Parser {
             name,
             symbol_type,
             properties,
+            span: self.end_span(start),
         })
     }
 
@@ -281,206 +498,343 @@ impl Parser {
                 | Token::If
                 | Token::EndIf
                 | Token::Source
+                | Token::OSource
+                | Token::RSource
+                | Token::ORSource
                 | Token::Comment
                 | Token::EndChoice
                 | Token::Eof
         ) {
-            match &self.current_context().current_token.clone() {
+            let start = self.start_span();
+            let kind = match &self.current_context().current_token.clone() {
                 Token::Bool => {
                     self.advance()?;
                     symbol_type = SymbolType::Bool;
-                    if let Ok(prompt) = self.try_parse_prompt() {
-                        properties.prompt = Some(prompt);
+                    if let Some(prompt) = self.try_parse_prompt()? {
+                        self.set_prompt(&mut properties, prompt, &start)?;
                     }
+                    PropertyKind::Type
                 }
                 Token::Tristate => {
                     self.advance()?;
                     symbol_type = SymbolType::Tristate;
-                    if let Ok(prompt) = self.try_parse_prompt() {
-                        properties.prompt = Some(prompt);
+                    if let Some(prompt) = self.try_parse_prompt()? {
+                        self.set_prompt(&mut properties, prompt, &start)?;
                     }
+                    PropertyKind::Type
                 }
                 Token::String => {
                     self.advance()?;
                     symbol_type = SymbolType::String;
-                    if let Ok(prompt) = self.try_parse_prompt() {
-                        properties.prompt = Some(prompt);
+                    if let Some(prompt) = self.try_parse_prompt()? {
+                        self.set_prompt(&mut properties, prompt, &start)?;
                     }
+                    PropertyKind::Type
                 }
                 Token::Int => {
                     self.advance()?;
                     symbol_type = SymbolType::Int;
-                    if let Ok(prompt) = self.try_parse_prompt() {
-                        properties.prompt = Some(prompt);
+                    if let Some(prompt) = self.try_parse_prompt()? {
+                        self.set_prompt(&mut properties, prompt, &start)?;
                     }
+                    PropertyKind::Type
                 }
                 Token::Hex => {
                     self.advance()?;
                     symbol_type = SymbolType::Hex;
-                    if let Ok(prompt) = self.try_parse_prompt() {
-                        properties.prompt = Some(prompt);
+                    if let Some(prompt) = self.try_parse_prompt()? {
+                        self.set_prompt(&mut properties, prompt, &start)?;
                     }
+                    PropertyKind::Type
+                }
+                token @ (Token::DefBool | Token::DefTristate) => {
+                    // def_bool/def_tristate: a type and a default in one line
+                    symbol_type = if *token == Token::DefBool {
+                        SymbolType::Bool
+                    } else {
+                        SymbolType::Tristate
+                    };
+                    self.advance()?;
+                    let value = self.parse_expr()?;
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    properties.defaults.push((value, cond, span));
+                    PropertyKind::DefType
                 }
                 Token::Prompt => {
                     self.advance()?;
-                    properties.prompt = Some(self.parse_string()?);
-                    if matches!(self.current_context().current_token, Token::If) {
-                        self.advance()?;
-                        // Parse if condition (simplified)
-                    }
+                    let prompt = self.parse_string()?;
+                    self.set_prompt(&mut properties, prompt, &start)?;
+                    PropertyKind::Prompt
                 }
                 Token::Default => {
                     self.advance()?;
-                    properties.default = Some(self.parse_expr()?);
+                    let value = self.parse_expr()?;
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    properties.defaults.push((value, cond, span));
+                    PropertyKind::Default
                 }
                 Token::Depends => {
-                    self.advance()?;
-                    self.expect(Token::On)?;
-                    properties.depends = Some(self.parse_expr()?);
+                    self.parse_depends(&mut properties.depends)?;
+                    PropertyKind::Depends
                 }
                 Token::Select => {
                     self.advance()?;
                     let sym = self.parse_identifier()?;
-                    let cond = if matches!(self.current_context().current_token, Token::If) {
-                        self.advance()?;
-                        Some(self.parse_expr()?)
-                    } else {
-                        None
-                    };
-                    properties.select.push((sym, cond));
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    properties.select.push((sym, cond, span));
+                    PropertyKind::Select
                 }
                 Token::Imply => {
                     self.advance()?;
                     let sym = self.parse_identifier()?;
-                    let cond = if matches!(self.current_context().current_token, Token::If) {
-                        self.advance()?;
-                        Some(self.parse_expr()?)
-                    } else {
-                        None
-                    };
-                    properties.imply.push((sym, cond));
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    properties.imply.push((sym, cond, span));
+                    PropertyKind::Imply
                 }
                 Token::Range => {
                     self.advance()?;
                     let min = self.parse_expr()?;
                     let max = self.parse_expr()?;
-                    let cond = if matches!(self.current_context().current_token, Token::If) {
-                        self.advance()?;
-                        Some(self.parse_expr()?)
-                    } else {
-                        None
-                    };
-                    properties.range = Some((min, max, cond));
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    properties.ranges.push((min, max, cond, span));
+                    PropertyKind::Range
+                }
+                Token::Option => {
+                    self.advance()?;
+                    match &self.current_context().current_token {
+                        Token::AllNoConfig => {
+                            self.advance()?;
+                            properties.allnoconfig_y = true;
+                        }
+                        Token::Modules => {
+                            self.advance()?;
+                            properties.modules = true;
+                        }
+                        // Obsolete: the defconfig to start from is not looked up
+                        Token::Defconfig => {
+                            self.advance()?;
+                            let span = self.end_span(start.clone());
+                            self.warn(
+                                DiagnosticCode::ObsoleteOption,
+                                "option defconfig_list is ignored",
+                                span,
+                            );
+                        }
+                        Token::Identifier(name) if name == "env" => {
+                            self.advance()?;
+                            self.expect(Token::Eq)?;
+                            properties.env = Some(self.parse_string()?);
+                        }
+                        other => {
+                            let message = format!("Unsupported option: {:?}", other);
+                            return Err(
+                                self.syntax_error(DiagnosticCode::UnsupportedOption, message)
+                            );
+                        }
+                    }
+                    PropertyKind::Option
+                }
+                Token::Modules => {
+                    self.advance()?;
+                    properties.modules = true;
+                    PropertyKind::Modules
+                }
+                Token::Transitional => {
+                    self.advance()?;
+                    properties.transitional = true;
+                    PropertyKind::Transitional
                 }
                 Token::Help => {
-                    // Don't advance yet - skip help text directly from lexer
-                    let ctx = self.current_context_mut();
-                    let help_text = ctx.lexer.skip_help_text();
-                    properties.help = Some(help_text);
-                    // Now get the next token after skipping help
-                    ctx.current_token = ctx.lexer.next_token()?;
+                    properties.help = Some(self.skip_help()?);
+                    PropertyKind::Help
                 }
                 Token::Newline => {
                     self.advance()?;
+                    continue;
                 }
                 _ => break,
-            }
+            };
+            properties.spans.push(self.property_span(kind, start));
         }
 
         Ok((symbol_type, properties))
     }
 
+    /// Set the prompt of a config entry and parse its `if` condition.
+    /// A second prompt replaces the first, with a warning.
+    fn set_prompt(
+        &mut self,
+        properties: &mut Property,
+        prompt: String,
+        start: &Span,
+    ) -> Result<()> {
+        if properties.prompt.is_some() {
+            let span = self.end_span(start.clone());
+            self.warn(DiagnosticCode::PromptRedefined, "prompt redefined", span);
+        }
+        properties.prompt = Some(prompt);
+        properties.prompt_condition = self.parse_condition()?;
+        Ok(())
+    }
+
     fn parse_choice(&mut self) -> Result<Choice> {
+        let choice_start = self.start_span();
         self.advance()?; // consume 'choice'
+
+        // Named choices: `choice NAME`
+        let name = match &self.current_context().current_token {
+            Token::Identifier(name) => {
+                let name = name.clone();
+                self.advance()?;
+                Some(name)
+            }
+            _ => None,
+        };
         self.skip_newlines()?;
 
-        let name = None;
         let mut prompt = None;
+        let mut prompt_condition = None;
         let mut symbol_type = SymbolType::Bool;
-        let mut default = None;
+        let mut defaults = Vec::new();
         let mut depends = None;
-        let mut options = Vec::new();
+        let mut optional = false;
+        let mut help = None;
+        let mut entries = Vec::new();
+        let mut spans = Vec::new();
 
-        // Parse choice options
+        // Parse the choice's attributes and options
         while !matches!(self.current_context().current_token, Token::EndChoice) {
-            match &self.current_context().current_token.clone() {
+            let start = self.start_span();
+            let kind = match &self.current_context().current_token.clone() {
                 Token::Prompt => {
                     self.advance()?;
                     prompt = Some(self.parse_string()?);
+                    prompt_condition = self.parse_condition()?;
+                    PropertyKind::Prompt
                 }
                 Token::Bool => {
                     self.advance()?;
                     symbol_type = SymbolType::Bool;
+                    if let Some(p) = self.try_parse_prompt()? {
+                        prompt = Some(p);
+                        prompt_condition = self.parse_condition()?;
+                    }
+                    PropertyKind::Type
                 }
                 Token::Tristate => {
                     self.advance()?;
                     symbol_type = SymbolType::Tristate;
+                    if let Some(p) = self.try_parse_prompt()? {
+                        prompt = Some(p);
+                        prompt_condition = self.parse_condition()?;
+                    }
+                    PropertyKind::Type
                 }
                 Token::Default => {
                     self.advance()?;
-                    default = Some(self.parse_identifier()?);
+                    let option = self.parse_identifier()?;
+                    let cond = self.parse_condition()?;
+                    let span = self.end_span(start.clone());
+                    defaults.push((option, cond, span));
+                    PropertyKind::Default
                 }
                 Token::Depends => {
+                    self.parse_depends(&mut depends)?;
+                    PropertyKind::Depends
+                }
+                Token::Optional => {
                     self.advance()?;
-                    self.expect(Token::On)?;
-                    depends = Some(self.parse_expr()?);
+                    optional = true;
+                    PropertyKind::Optional
+                }
+                Token::Help => {
+                    help = Some(self.skip_help()?);
+                    PropertyKind::Help
                 }
                 Token::Config => {
-                    options.push(self.parse_config()?);
+                    entries.push(Entry::Config(self.parse_config()?));
+                    continue;
+                }
+                Token::Comment => {
+                    entries.push(Entry::Comment(self.parse_comment()?));
+                    continue;
+                }
+                Token::If => {
+                    entries.push(Entry::If(self.parse_if()?));
+                    continue;
                 }
                 Token::Newline => {
                     self.advance()?;
+                    continue;
                 }
                 _ => break,
-            }
+            };
+            spans.push(self.property_span(kind, start));
         }
 
         self.expect(Token::EndChoice)?;
+        let span = self.end_span(choice_start);
         self.skip_newlines()?;
 
         Ok(Choice {
             name,
             prompt,
+            prompt_condition,
             symbol_type,
-            default,
+            defaults,
             depends,
-            options,
+            optional,
+            help,
+            entries,
+            span,
+            spans,
         })
     }
 
     fn parse_menu(&mut self) -> Result<Menu> {
+        let menu_start = self.start_span();
         self.advance()?; // consume 'menu'
         let title = self.parse_string()?;
         self.skip_newlines()?;
 
         let mut depends = None;
         let mut visible = None;
+        let mut spans = Vec::new();
 
         // Parse menu attributes
         while matches!(
             self.current_context().current_token,
             Token::Depends | Token::Visible
         ) {
-            match &self.current_context().current_token {
+            let start = self.start_span();
+            let kind = match &self.current_context().current_token {
                 Token::Depends => {
-                    self.advance()?;
-                    self.expect(Token::On)?;
-                    depends = Some(self.parse_expr()?);
-                    self.skip_newlines()?;
+                    self.parse_depends(&mut depends)?;
+                    PropertyKind::Depends
                 }
                 Token::Visible => {
                     self.advance()?;
                     self.expect(Token::If)?;
                     visible = Some(self.parse_expr()?);
-                    self.skip_newlines()?;
+                    PropertyKind::Visible
                 }
                 _ => break,
-            }
+            };
+            spans.push(self.property_span(kind, start));
+            self.skip_newlines()?;
         }
 
+        self.current_context_mut().open_blocks += 1;
         let entries = self.parse_entries()?;
+        self.current_context_mut().open_blocks -= 1;
 
         self.expect(Token::EndMenu)?;
+        let span = self.end_span(menu_start);
         self.skip_newlines()?;
 
         Ok(Menu {
@@ -488,130 +842,141 @@ impl Parser {
             depends,
             visible,
             entries,
+            span,
+            spans,
         })
     }
 
     fn parse_if(&mut self) -> Result<If> {
+        let if_start = self.start_span();
         self.advance()?; // consume 'if'
         let condition = self.parse_expr()?;
+        let spans = vec![self.property_span(PropertyKind::Condition, if_start.clone())];
         self.skip_newlines()?;
 
+        self.current_context_mut().open_blocks += 1;
         let entries = self.parse_entries()?;
+        self.current_context_mut().open_blocks -= 1;
 
         self.expect(Token::EndIf)?;
+        let span = self.end_span(if_start);
         self.skip_newlines()?;
 
-        Ok(If { condition, entries })
+        Ok(If {
+            condition,
+            entries,
+            span,
+            spans,
+        })
     }
 
     fn parse_comment(&mut self) -> Result<Comment> {
+        let comment_start = self.start_span();
         self.advance()?; // consume 'comment'
         let text = self.parse_string()?;
         self.skip_newlines()?;
 
         let mut depends = None;
-        if matches!(self.current_context().current_token, Token::Depends) {
-            self.advance()?;
-            self.expect(Token::On)?;
-            depends = Some(self.parse_expr()?);
+        let mut spans = Vec::new();
+        while matches!(self.current_context().current_token, Token::Depends) {
+            let start = self.start_span();
+            self.parse_depends(&mut depends)?;
+            spans.push(self.property_span(PropertyKind::Depends, start));
             self.skip_newlines()?;
         }
 
-        Ok(Comment { text, depends })
+        Ok(Comment {
+            text,
+            depends,
+            span: self.end_span(comment_start),
+            spans,
+        })
     }
 
-    fn parse_expr(&mut self) -> Result<Expr> {
+    fn parse_expr(&mut self) -> Result<Spanned<Expr>> {
         self.parse_or_expr()
     }
 
-    fn parse_or_expr(&mut self) -> Result<Expr> {
+    fn parse_or_expr(&mut self) -> Result<Spanned<Expr>> {
+        let start = self.start_span();
         let mut left = self.parse_and_expr()?;
 
         while matches!(self.current_context().current_token, Token::Or) {
             self.advance()?;
             let right = self.parse_and_expr()?;
-            left = Expr::Or(Box::new(left), Box::new(right));
+            left = self.spanned(Expr::Or(Box::new(left), Box::new(right)), &start);
         }
 
         Ok(left)
     }
 
-    fn parse_and_expr(&mut self) -> Result<Expr> {
+    fn parse_and_expr(&mut self) -> Result<Spanned<Expr>> {
+        let start = self.start_span();
         let mut left = self.parse_comparison_expr()?;
 
         while matches!(self.current_context().current_token, Token::And) {
             self.advance()?;
             let right = self.parse_comparison_expr()?;
-            left = Expr::And(Box::new(left), Box::new(right));
+            left = self.spanned(Expr::And(Box::new(left), Box::new(right)), &start);
         }
 
         Ok(left)
     }
 
-    fn parse_comparison_expr(&mut self) -> Result<Expr> {
+    fn parse_comparison_expr(&mut self) -> Result<Spanned<Expr>> {
+        let start = self.start_span();
         let left = self.parse_unary_expr()?;
 
-        match &self.current_context().current_token {
-            Token::Eq => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::Equal(Box::new(left), Box::new(right)))
-            }
-            Token::NotEq => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::NotEqual(Box::new(left), Box::new(right)))
-            }
-            Token::Less => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::Less(Box::new(left), Box::new(right)))
-            }
-            Token::LessEq => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::LessEqual(Box::new(left), Box::new(right)))
-            }
-            Token::Greater => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::Greater(Box::new(left), Box::new(right)))
-            }
-            Token::GreaterEq => {
-                self.advance()?;
-                let right = self.parse_unary_expr()?;
-                Ok(Expr::GreaterEqual(Box::new(left), Box::new(right)))
-            }
-            _ => Ok(left),
-        }
+        let op: fn(Box<Spanned<Expr>>, Box<Spanned<Expr>>) -> Expr =
+            match &self.current_context().current_token {
+                Token::Eq => Expr::Equal,
+                Token::NotEq => Expr::NotEqual,
+                Token::Less => Expr::Less,
+                Token::LessEq => Expr::LessEqual,
+                Token::Greater => Expr::Greater,
+                Token::GreaterEq => Expr::GreaterEqual,
+                _ => return Ok(left),
+            };
+        self.advance()?;
+        let right = self.parse_unary_expr()?;
+        Ok(self.spanned(op(Box::new(left), Box::new(right)), &start))
     }
 
-    fn parse_unary_expr(&mut self) -> Result<Expr> {
+    fn parse_unary_expr(&mut self) -> Result<Spanned<Expr>> {
         if matches!(self.current_context().current_token, Token::Not) {
+            let start = self.start_span();
             self.advance()?;
             let expr = self.parse_unary_expr()?;
-            return Ok(Expr::Not(Box::new(expr)));
+            return Ok(self.spanned(Expr::Not(Box::new(expr)), &start));
         }
 
         self.parse_primary_expr()
     }
 
-    fn parse_primary_expr(&mut self) -> Result<Expr> {
+    fn parse_primary_expr(&mut self) -> Result<Spanned<Expr>> {
+        let start = self.start_span();
         match &self.current_context().current_token.clone() {
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance()?;
-                Ok(Expr::Symbol(name))
+                Ok(self.spanned(Expr::Symbol(name), &start))
             }
             Token::StringLit(val) => {
                 let val = val.clone();
                 self.advance()?;
-                Ok(Expr::Const(val))
+                Ok(self.spanned(Expr::Const(val), &start))
             }
             Token::Number(n) => {
-                let n = *n;
+                // Hex numbers keep their `0x` form, so hex symbols read
+                // them as written
+                let text = self.current_context().lexer.token_text();
+                let value = if text.starts_with("0x") {
+                    text.to_string()
+                } else {
+                    n.to_string()
+                };
                 self.advance()?;
-                Ok(Expr::Const(n.to_string()))
+                Ok(self.spanned(Expr::Const(value), &start))
             }
             Token::LParen => {
                 self.advance()?;
@@ -619,17 +984,41 @@ impl Parser {
                 self.expect(Token::RParen)?;
                 Ok(expr)
             }
-            _ => Err(KconfigError::Syntax {
-                file: self.current_file.clone(),
-                line: self.current_context().lexer.current_line(),
-                message: format!(
-                    "Expected expression, got {:?}",
-                    self.current_context().current_token
-                ),
-            }),
+            token => Err(self.syntax_error(
+                DiagnosticCode::ExpectedExpression,
+                format!("Expected expression, got {:?}", token),
+            )),
         }
     }
 
+    /// Parse `depends on <expr>`, ANDing it with earlier `depends on`
+    /// lines; the AND spans from the first line to this one
+    fn parse_depends(&mut self, depends: &mut Option<Spanned<Expr>>) -> Result<()> {
+        self.advance()?; // consume 'depends'
+        self.expect(Token::On)?;
+        let expr = self.parse_expr()?;
+        *depends = Some(match depends.take() {
+            Some(previous) => {
+                let span = Span {
+                    end_line: expr.span.end_line,
+                    ..previous.span.clone()
+                };
+                Spanned::new(Expr::And(Box::new(previous), Box::new(expr)), span)
+            }
+            None => expr,
+        });
+        Ok(())
+    }
+
+    /// Parse an optional `if <expr>` suffix
+    fn parse_condition(&mut self) -> Result<Option<Spanned<Expr>>> {
+        if !matches!(self.current_context().current_token, Token::If) {
+            return Ok(None);
+        }
+        self.advance()?;
+        Ok(Some(self.parse_expr()?))
+    }
+
     fn parse_string(&mut self) -> Result<String> {
         match &self.current_context().current_token {
             Token::StringLit(s) => {
@@ -642,14 +1031,10 @@ impl Parser {
                 self.advance()?;
                 Ok(s)
             }
-            _ => Err(KconfigError::Syntax {
-                file: self.current_file.clone(),
-                line: self.current_context().lexer.current_line(),
-                message: format!(
-                    "Expected string, got {:?}",
-                    self.current_context().current_token
-                ),
-            }),
+            token => Err(self.syntax_error(
+                DiagnosticCode::ExpectedString,
+                format!("Expected string, got {:?}", token),
+            )),
         }
     }
 
@@ -660,26 +1045,66 @@ impl Parser {
                 self.advance()?;
                 Ok(s)
             }
-            _ => Err(KconfigError::Syntax {
-                file: self.current_file.clone(),
-                line: self.current_context().lexer.current_line(),
-                message: format!(
-                    "Expected identifier, got {:?}",
-                    self.current_context().current_token
-                ),
-            }),
+            token => Err(self.syntax_error(
+                DiagnosticCode::ExpectedIdentifier,
+                format!("Expected identifier, got {:?}", token),
+            )),
         }
     }
 
-    fn try_parse_prompt(&mut self) -> Result<String> {
+    /// Parse the prompt after a type keyword, if there is one
+    fn try_parse_prompt(&mut self) -> Result<Option<String>> {
         if matches!(
             self.current_context().current_token,
             Token::StringLit(_) | Token::Identifier(_)
         ) {
-            self.parse_string()
+            self.parse_string().map(Some)
         } else {
-            Err(KconfigError::Parse("No prompt found".to_string()))
+            Ok(None)
         }
     }
+}
 
+/// Whether a token at the start of a line begins an entry, or ends a block
+fn starts_entry(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Config
+            | Token::MenuConfig
+            | Token::Choice
+            | Token::EndChoice
+            | Token::Menu
+            | Token::EndMenu
+            | Token::If
+            | Token::EndIf
+            | Token::Source
+            | Token::OSource
+            | Token::RSource
+            | Token::ORSource
+            | Token::Comment
+            | Token::MainMenu
+    )
+}
+
+/// Resolve `.` and `..` lexically, so one file always has the same path
+/// for recursion detection and de-duplication
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) {
+                    normalized.pop();
+                } else {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
 }
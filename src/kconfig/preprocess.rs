@@ -0,0 +1,463 @@
+use crate::error::{KconfigError, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Maximum nesting depth for macro expansion, guards against runaway recursion
+const MAX_EXPANSION_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableFlavor {
+    /// `VAR = value`, expanded every time it is referenced
+    Recursive,
+    /// `VAR := value`, expanded once at assignment
+    Simple,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub flavor: VariableFlavor,
+    pub value: String,
+}
+
+/// Kconfig macro language preprocessor.
+///
+/// Expands `$(VAR)` references, user-defined variables, environment
+/// variables and the built-in functions (`shell`, `if`, `success`, ...)
+/// before the text reaches the lexer. Variables are global, so a single
+/// instance is shared across every file pulled in through `source`.
+pub struct Preprocessor {
+    variables: HashMap<String, Variable>,
+    env_vars: BTreeSet<String>,
+    expanding: Vec<String>,
+    file: PathBuf,
+    line: usize,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            env_vars: BTreeSet::new(),
+            expanding: Vec::new(),
+            file: PathBuf::new(),
+            line: 0,
+        }
+    }
+
+    /// Preprocess the contents of a Kconfig file.
+    ///
+    /// Assignment lines are consumed and replaced by empty lines so that
+    /// line numbers reported by the lexer still match the source. Help
+    /// text and comments are passed through unexpanded.
+    pub fn process(&mut self, content: &str, file: &Path) -> Result<String> {
+        let saved_file = std::mem::replace(&mut self.file, file.to_path_buf());
+        let saved_line = self.line;
+
+        let result = self.process_lines(content);
+
+        self.file = saved_file;
+        self.line = saved_line;
+        result
+    }
+
+    fn process_lines(&mut self, content: &str) -> Result<String> {
+        let mut output = String::with_capacity(content.len());
+        let mut in_help = false;
+
+        for (index, line) in content.split_inclusive('\n').enumerate() {
+            self.line = index + 1;
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            let newline = if line.ends_with('\n') { "\n" } else { "" };
+
+            // Help text mirrors Lexer::skip_help_text: blank and indented
+            // lines after `help` are taken verbatim
+            if in_help {
+                if text.trim().is_empty() || text.starts_with(' ') || text.starts_with('\t') {
+                    output.push_str(line);
+                    continue;
+                }
+                in_help = false;
+            }
+
+            let first_word = text.split_whitespace().next();
+            if matches!(first_word, Some("help") | Some("---help---")) {
+                in_help = true;
+                output.push_str(line);
+                continue;
+            }
+
+            if let Some((name, flavor, append, value)) = parse_assignment(text) {
+                let name = self.expand(name)?;
+                let name = name.trim().to_string();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(self.error(format!("Invalid variable name '{}'", name)));
+                }
+                self.assign(name, flavor, append, value)?;
+                output.push_str(newline);
+                continue;
+            }
+
+            output.push_str(&self.expand_line(text)?);
+            output.push_str(newline);
+        }
+
+        Ok(output)
+    }
+
+    /// Expand every macro reference in `text`.
+    pub fn expand(&mut self, text: &str) -> Result<String> {
+        let mut result = String::new();
+        let mut rest = text;
+
+        while let Some(pos) = rest.find("$(") {
+            result.push_str(&rest[..pos]);
+            let (expanded, remainder) = self.expand_reference(&rest[pos + 2..])?;
+            result.push_str(&expanded);
+            rest = remainder;
+        }
+        result.push_str(rest);
+
+        Ok(result)
+    }
+
+    /// Define or overwrite a variable, as a `NAME = value` line would.
+    pub fn set_variable(&mut self, name: &str, flavor: VariableFlavor, value: &str) {
+        self.variables.insert(
+            name.to_string(),
+            Variable {
+                flavor,
+                value: value.to_string(),
+            },
+        );
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.get(name)
+    }
+
+    /// Environment variables referenced so far, so callers can track them
+    /// as build inputs
+    pub fn env_vars(&self) -> impl Iterator<Item = &String> {
+        self.env_vars.iter()
+    }
+
+    fn assign(
+        &mut self,
+        name: String,
+        flavor: VariableFlavor,
+        append: bool,
+        value: &str,
+    ) -> Result<()> {
+        let value = value.trim();
+
+        if append {
+            if let Some(existing) = self.variables.get(&name).cloned() {
+                let addition = match existing.flavor {
+                    VariableFlavor::Simple => self.expand(value)?,
+                    VariableFlavor::Recursive => value.to_string(),
+                };
+                let joined = if existing.value.is_empty() {
+                    addition
+                } else {
+                    format!("{} {}", existing.value, addition)
+                };
+                self.set_variable(&name, existing.flavor, &joined);
+                return Ok(());
+            }
+            // Appending to an undefined variable behaves like `=`
+        }
+
+        let value = match flavor {
+            VariableFlavor::Simple => self.expand(value)?,
+            VariableFlavor::Recursive => value.to_string(),
+        };
+        self.set_variable(&name, flavor, &value);
+        Ok(())
+    }
+
+    /// Expand a line of Kconfig source, leaving `#` comments untouched.
+    fn expand_line(&mut self, text: &str) -> Result<String> {
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut depth = 0usize;
+
+        for (i, ch) in text.char_indices() {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '(' if depth > 0 || text[..i].ends_with('$') => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                '#' if !in_string && depth == 0 => {
+                    let mut expanded = self.expand(&text[..i])?;
+                    expanded.push_str(&text[i..]);
+                    return Ok(expanded);
+                }
+                _ => {}
+            }
+        }
+
+        self.expand(text)
+    }
+
+    /// Expand the reference starting right after `$(`, returning the
+    /// expansion and the remaining unprocessed text.
+    fn expand_reference<'a>(&mut self, text: &'a str) -> Result<(String, &'a str)> {
+        let mut depth = 1usize;
+        let mut args = Vec::new();
+        let mut arg_start = 0;
+        let mut end = None;
+        let bytes = text.as_bytes();
+
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'$' if bytes.get(i + 1) == Some(&b'(') => {
+                    depth += 1;
+                    i += 1;
+                }
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        args.push(&text[arg_start..i]);
+                        end = Some(i);
+                        break;
+                    }
+                }
+                b',' if depth == 1 => {
+                    args.push(&text[arg_start..i]);
+                    arg_start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let end = end.ok_or_else(|| self.error("Unterminated reference to '$('".to_string()))?;
+
+        if self.expanding.len() >= MAX_EXPANSION_DEPTH {
+            return Err(self.error("Macro expansion nested too deeply".to_string()));
+        }
+
+        // The name is always expanded; arguments are expanded here, before
+        // the call, matching the Linux preprocessor
+        let name = self.expand(args[0])?.trim().to_string();
+        let mut expanded_args = Vec::with_capacity(args.len() - 1);
+        for arg in &args[1..] {
+            expanded_args.push(self.expand(arg)?);
+        }
+
+        let value = self.call(&name, &expanded_args)?;
+        Ok((value, &text[end + 1..]))
+    }
+
+    fn call(&mut self, name: &str, args: &[String]) -> Result<String> {
+        if name.is_empty() {
+            return Err(self.error("Empty macro name".to_string()));
+        }
+
+        if let Some(result) = self.call_builtin(name, args)? {
+            return Ok(result);
+        }
+
+        if let Some(variable) = self.variables.get(name).cloned() {
+            return match variable.flavor {
+                VariableFlavor::Simple if args.is_empty() => Ok(variable.value),
+                _ => self.expand_variable(name, &variable.value, args),
+            };
+        }
+
+        if !args.is_empty() {
+            return Err(self.error(format!("Undefined function '{}'", name)));
+        }
+
+        // Fall back to the environment; unset variables expand to nothing
+        self.env_vars.insert(name.to_string());
+        Ok(std::env::var(name).unwrap_or_default())
+    }
+
+    fn expand_variable(&mut self, name: &str, value: &str, args: &[String]) -> Result<String> {
+        if self.expanding.iter().any(|n| n == name) {
+            return Err(self.error(format!(
+                "Recursive variable '{}' references itself (eventually)",
+                name
+            )));
+        }
+
+        // Positional parameters $(1), $(2), ... are scoped to this call
+        let mut saved = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let key = (i + 1).to_string();
+            saved.push((key.clone(), self.variables.remove(&key)));
+            self.set_variable(&key, VariableFlavor::Simple, arg);
+        }
+
+        self.expanding.push(name.to_string());
+        let result = self.expand(value);
+        self.expanding.pop();
+
+        for (key, previous) in saved {
+            match previous {
+                Some(variable) => {
+                    self.variables.insert(key, variable);
+                }
+                None => {
+                    self.variables.remove(&key);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn call_builtin(&mut self, name: &str, args: &[String]) -> Result<Option<String>> {
+        let (min, max) = match name {
+            "shell" | "success" | "failure" | "info" => (1, 1),
+            "warning-if" | "error-if" => (2, 2),
+            "if" => (2, 3),
+            "filename" | "lineno" => (0, 0),
+            _ => return Ok(None),
+        };
+
+        if args.len() < min || args.len() > max {
+            return Err(self.error(format!(
+                "Function '{}' takes {} argument(s), got {}",
+                name,
+                if min == max {
+                    min.to_string()
+                } else {
+                    format!("{}-{}", min, max)
+                },
+                args.len()
+            )));
+        }
+
+        let result = match name {
+            "shell" => self.run_shell(&args[0])?.0,
+            "success" => if self.run_shell(&args[0])?.1 {
+                "y"
+            } else {
+                "n"
+            }
+            .to_string(),
+            "failure" => if self.run_shell(&args[0])?.1 {
+                "n"
+            } else {
+                "y"
+            }
+            .to_string(),
+            "info" => {
+                println!("{}", args[0]);
+                String::new()
+            }
+            "warning-if" => {
+                if args[0] == "y" {
+                    eprintln!("{}:{}: {}", self.file.display(), self.line, args[1]);
+                }
+                String::new()
+            }
+            "error-if" => {
+                if args[0] == "y" {
+                    return Err(self.error(args[1].clone()));
+                }
+                String::new()
+            }
+            "if" => {
+                if !args[0].is_empty() {
+                    args[1].clone()
+                } else {
+                    args.get(2).cloned().unwrap_or_default()
+                }
+            }
+            "filename" => self.file.display().to_string(),
+            "lineno" => self.line.to_string(),
+            _ => unreachable!(),
+        };
+
+        Ok(Some(result))
+    }
+
+    /// Run a command through `sh -c`, returning its output with newlines
+    /// folded into spaces and whether it exited successfully.
+    fn run_shell(&self, command: &str) -> Result<(String, bool)> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .map_err(|e| self.error(format!("Failed to execute '{}': {}", command, e)))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let text = stdout.trim_end_matches('\n').replace('\n', " ");
+        Ok((text, output.status.success()))
+    }
+
+    fn error(&self, message: String) -> KconfigError {
+        KconfigError::Macro {
+            file: self.file.clone(),
+            line: self.line,
+            message,
+        }
+    }
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `line` assigns a macro variable rather than being Kconfig syntax
+pub(crate) fn is_assignment(line: &str) -> bool {
+    parse_assignment(line).is_some()
+}
+
+/// Split `NAME := value`, `NAME = value` or `NAME += value` into its parts.
+fn parse_assignment(line: &str) -> Option<(&str, VariableFlavor, bool, &str)> {
+    let trimmed = line.trim_start();
+    let name_len = name_length(trimmed);
+    if name_len == 0 {
+        return None;
+    }
+
+    let name = &trimmed[..name_len];
+    let rest = trimmed[name_len..].trim_start();
+
+    if let Some(value) = rest.strip_prefix(":=") {
+        Some((name, VariableFlavor::Simple, false, value))
+    } else if let Some(value) = rest.strip_prefix("+=") {
+        Some((name, VariableFlavor::Recursive, true, value))
+    } else if let Some(value) = rest.strip_prefix('=') {
+        Some((name, VariableFlavor::Recursive, false, value))
+    } else {
+        None
+    }
+}
+
+/// Length of a variable name at the start of `text`. Names may contain
+/// references such as `$(ARCH)-flags`, which are expanded later.
+fn name_length(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let mut depth = 0usize;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'$' if bytes.get(i + 1) == Some(&b'(') => {
+                depth += 1;
+                i += 1;
+            }
+            b')' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            b if b.is_ascii_alphanumeric() || b == b'_' || b == b'-' => {}
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if depth > 0 {
+        return 0;
+    }
+    i
+}
@@ -1,19 +1,76 @@
-use crate::kconfig::ast::SymbolType;
-use std::collections::HashMap;
+use crate::kconfig::ast::{Span, SymbolType};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub struct Symbol {
     pub name: String,
     pub symbol_type: SymbolType,
     pub value: Option<String>,
-    pub is_choice: bool,
-    pub is_new: bool,           // Mark as new symbol
-    pub from_config: bool,      // Loaded from .config
+    pub is_choice: bool,             // A named choice, valued by its mode
+    pub is_new: bool,                // Mark as new symbol
+    pub from_config: bool,           // Loaded from .config
+    pub is_transitional: bool,       // Read from .config but never written
+    pub origin: Option<ValueOrigin>, // Where the value came from
+    pub visible: bool,               // Has a prompt the user can see
 }
 
+/// One step of a configuration in Kconfig declaration order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutItem {
+    /// A symbol, at its first definition
+    Symbol(String),
+    /// The start of a menu, with its title
+    MenuStart(String),
+    /// The end of the menu with this title
+    MenuEnd(String),
+}
+
+/// Where a symbol's current value comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueOrigin {
+    /// The first `default` (or `def_bool`) whose condition holds
+    Default { span: Span },
+    /// No default applies
+    NoDefault,
+    /// Set by the user, from the given line of a .config or defconfig
+    /// or interactively
+    User { line: Option<usize> },
+    /// Forced on by a `select` in `by`
+    Selected { by: String },
+    /// Raised by an `imply` in `by`
+    Implied { by: String },
+    /// The option its choice picks when the user has not picked one
+    ChoiceDefault,
+    /// Another option of its choice is picked
+    ChoiceOther { selected: String },
+    /// Forced to `n`, or left unset, because its dependencies are not met
+    UnmetDependencies,
+}
+
+impl fmt::Display for ValueOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueOrigin::Default { span } => write!(f, "default at {}", span),
+            ValueOrigin::NoDefault => write!(f, "no default applies"),
+            ValueOrigin::User { line: Some(line) } => write!(f, "set by the user at line {}", line),
+            ValueOrigin::User { line: None } => write!(f, "set by the user"),
+            ValueOrigin::Selected { by } => write!(f, "selected by {}", by),
+            ValueOrigin::Implied { by } => write!(f, "implied by {}", by),
+            ValueOrigin::ChoiceDefault => write!(f, "default of its choice"),
+            ValueOrigin::ChoiceOther { selected } => {
+                write!(f, "{} is picked in its choice", selected)
+            }
+            ValueOrigin::UnmetDependencies => write!(f, "dependencies not met"),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct SymbolTable {
     symbols: HashMap<String, Symbol>,
-    changed_symbols: Vec<String>,  // Track modified symbols
+    changed_symbols: Vec<String>, // Track modified symbols
+    layout: Vec<LayoutItem>,      // Symbols and menus in Kconfig order
 }
 
 impl SymbolTable {
@@ -21,6 +78,7 @@ impl SymbolTable {
         Self {
             symbols: HashMap::new(),
             changed_symbols: Vec::new(),
+            layout: Vec::new(),
         }
     }
 
@@ -32,15 +90,87 @@ impl SymbolTable {
             is_choice: false,
             is_new: false,
             from_config: false,
+            is_transitional: false,
+            origin: None,
+            visible: true,
         });
     }
 
+    /// Store `value` as it is. Choices are not kept to one selected
+    /// option here: only `ConfigEngine` computes them, so a table bound for
+    /// the writers should come from the engine, not be filled by hand.
     pub fn set_value(&mut self, name: &str, value: String) {
         if let Some(symbol) = self.symbols.get_mut(name) {
             symbol.value = Some(value);
         }
     }
 
+    pub fn clear_value(&mut self, name: &str) {
+        if let Some(symbol) = self.symbols.get_mut(name) {
+            symbol.value = None;
+        }
+    }
+
+    /// Record where the value of `name` came from
+    pub fn set_origin(&mut self, name: &str, origin: Option<ValueOrigin>) {
+        if let Some(symbol) = self.symbols.get_mut(name) {
+            symbol.origin = origin;
+        }
+    }
+
+    pub fn get_origin(&self, name: &str) -> Option<&ValueOrigin> {
+        self.symbols.get(name).and_then(|s| s.origin.as_ref())
+    }
+
+    /// Record whether the prompt of `name` is visible; symbols are taken
+    /// as visible until this is known
+    pub fn set_visible(&mut self, name: &str, visible: bool) {
+        if let Some(symbol) = self.symbols.get_mut(name) {
+            symbol.visible = visible;
+        }
+    }
+
+    /// Record the order of the Kconfig the symbols are declared in
+    pub fn set_layout(&mut self, layout: Vec<LayoutItem>) {
+        self.layout = layout;
+    }
+
+    /// The Kconfig layout, followed by the symbols it does not declare,
+    /// sorted by name, so that output written from it is deterministic
+    pub fn layout(&self) -> Vec<LayoutItem> {
+        let mut layout = self.layout.clone();
+        let declared: HashSet<&String> = self
+            .layout
+            .iter()
+            .filter_map(|item| match item {
+                LayoutItem::Symbol(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let mut rest: Vec<&String> = self
+            .symbols
+            .keys()
+            .filter(|name| !declared.contains(name))
+            .collect();
+        rest.sort();
+        layout.extend(
+            rest.into_iter()
+                .map(|name| LayoutItem::Symbol(name.clone())),
+        );
+        layout
+    }
+
+    /// Every symbol in the order of `layout`
+    pub fn ordered_symbols(&self) -> Vec<&Symbol> {
+        self.layout()
+            .iter()
+            .filter_map(|item| match item {
+                LayoutItem::Symbol(name) => self.symbols.get(name),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn get_value(&self, name: &str) -> Option<String> {
         self.symbols.get(name).and_then(|s| s.value.clone())
     }
@@ -56,7 +186,7 @@ impl SymbolTable {
     pub fn get_symbol(&self, name: &str) -> Option<&Symbol> {
         self.symbols.get(name)
     }
-    
+
     pub fn get_symbol_mut(&mut self, name: &str) -> Option<&mut Symbol> {
         self.symbols.get_mut(name)
     }
@@ -64,44 +194,39 @@ impl SymbolTable {
     pub fn all_symbols(&self) -> impl Iterator<Item = (&String, &Symbol)> {
         self.symbols.iter()
     }
-    
+
     /// Mark a symbol as newly added
     pub fn mark_as_new(&mut self, name: &str) {
         if let Some(symbol) = self.symbols.get_mut(name) {
             symbol.is_new = true;
         }
     }
-    
+
     /// Mark a symbol as loaded from config file
     pub fn mark_from_config(&mut self, name: &str) {
         if let Some(symbol) = self.symbols.get_mut(name) {
             symbol.from_config = true;
         }
     }
-    
+
     /// Get all new symbols
     pub fn get_new_symbols(&self) -> Vec<&Symbol> {
-        self.symbols
-            .values()
-            .filter(|s| s.is_new)
-            .collect()
+        self.symbols.values().filter(|s| s.is_new).collect()
     }
-    
+
     /// Set value and track the change
     pub fn set_value_tracked(&mut self, name: &str, value: String) {
         if let Some(symbol) = self.symbols.get_mut(name) {
             let old_value = symbol.value.clone();
             symbol.value = Some(value.clone());
-            
+
             // Track if value actually changed
-            if old_value != Some(value) {
-                if !self.changed_symbols.contains(&name.to_string()) {
-                    self.changed_symbols.push(name.to_string());
-                }
+            if old_value != Some(value) && !self.changed_symbols.contains(&name.to_string()) {
+                self.changed_symbols.push(name.to_string());
             }
         }
     }
-    
+
     /// Get all changed symbols
     pub fn get_changed_symbols(&self) -> &[String] {
         &self.changed_symbols
@@ -0,0 +1,102 @@
+//! Symbol values by type: what each type accepts, and the form values are
+//! kept in.
+//!
+//! As in Linux, bool values are `y` or `n`, tristate values add `m`, int
+//! values are decimal numbers with an optional sign, and hex values are
+//! hexadecimal digits, kept with a `0x` prefix whether or not they were
+//! written with one. Any text is a valid string; in a `.config` it is
+//! double-quoted, with `"` and `\` escaped by a backslash.
+
+use crate::kconfig::ast::SymbolType;
+
+/// `value` in canonical form for `symbol_type`
+/// Returns: Err with what the type expects if `value` is not valid
+pub fn normalize_value(symbol_type: &SymbolType, value: &str) -> Result<String, String> {
+    match symbol_type {
+        SymbolType::Bool if matches!(value, "y" | "n") => Ok(value.to_string()),
+        SymbolType::Bool => Err("expected y or n".to_string()),
+        SymbolType::Tristate if matches!(value, "y" | "m" | "n") => Ok(value.to_string()),
+        SymbolType::Tristate => Err("expected y, m or n".to_string()),
+        SymbolType::Int => parse_int(value)
+            .map(|n| n.to_string())
+            .ok_or_else(|| "expected a decimal number".to_string()),
+        SymbolType::Hex => match parse_hex(value) {
+            Some(_) => Ok(format!("0x{}", strip_hex_prefix(value))),
+            None => Err("expected a hexadecimal number".to_string()),
+        },
+        SymbolType::String => Ok(value.to_string()),
+    }
+}
+
+/// A decimal number with an optional sign
+pub fn parse_int(value: &str) -> Option<i64> {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Hexadecimal digits, with or without a `0x` prefix
+pub fn parse_hex(value: &str) -> Option<u64> {
+    let digits = strip_hex_prefix(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u64::from_str_radix(digits, 16).ok()
+}
+
+fn strip_hex_prefix(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+/// The numeric value of an int or hex value, wide enough for both
+pub fn parse_number(symbol_type: &SymbolType, value: &str) -> Option<i128> {
+    match symbol_type {
+        SymbolType::Int => parse_int(value).map(i128::from),
+        SymbolType::Hex => parse_hex(value).map(i128::from),
+        _ => None,
+    }
+}
+
+/// `number` written the way `symbol_type` keeps it
+pub fn format_number(symbol_type: &SymbolType, number: i128) -> String {
+    match symbol_type {
+        SymbolType::Hex => format!("0x{:x}", number),
+        _ => number.to_string(),
+    }
+}
+
+/// `value` double-quoted, escaping `"` and `\`
+pub fn quote_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        if ch == '"' || ch == '\\' {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('"');
+    out
+}
+
+/// The content of a double-quoted string, undoing `quote_string`
+/// Returns: None if `quoted` is not a single well-formed string
+pub fn unquote_string(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => out.push(chars.next()?),
+            '"' => return chars.as_str().is_empty().then_some(out),
+            _ => out.push(ch),
+        }
+    }
+    None
+}
@@ -1,9 +1,14 @@
+use crate::config::{ConfigEngine, Solution};
 use crate::error::Result;
-use crate::kconfig::{SymbolTable, SymbolType};
-use crate::ui::dependency_resolver::{DependencyResolver, DependencyError};
+use crate::kconfig::dependency_resolver::DependencyError;
+use crate::kconfig::{
+    format_number, normalize_value, parse_number, Expr, SymbolTable, SymbolType, Tristate,
+};
 use crate::ui::events::EventResult;
 use crate::ui::rendering::Theme;
-use crate::ui::state::{ConfigState, ConfigValue, MenuItem, MenuItemKind, NavigationState, TristateValue};
+use crate::ui::state::{
+    ConfigState, ConfigValue, MenuItem, MenuItemKind, NavigationState, TristateValue,
+};
 use crate::ui::utils::FuzzySearcher;
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{
@@ -27,91 +32,137 @@ pub enum PanelFocus {
 pub enum DialogType {
     Help,
     Save,
-    DependencyError(DependencyError),
-    CascadeWarning { symbol: String, affected: Vec<String> },
-    ImplySuggestion { implied: Vec<String> },
+    /// `fix` holds a minimal set of changes that would allow the action, when
+    /// some were found
+    DependencyError {
+        error: DependencyError,
+        fix: Option<Solution>,
+    },
+    CascadeWarning {
+        symbol: String,
+        affected: Vec<String>,
+    },
+    ImplySuggestion {
+        implied: Vec<String>,
+    },
+    /// Typing a new int, hex or string value; `error` explains why the
+    /// last attempt was refused
+    EditValue {
+        symbol: String,
+        symbol_type: SymbolType,
+        input: String,
+        error: Option<String>,
+    },
 }
 
 pub struct MenuConfigApp {
     config_state: ConfigState,
-    symbol_table: SymbolTable,
+    engine: ConfigEngine,
     navigation: NavigationState,
-    dependency_resolver: DependencyResolver,
-    
+
     // Search state
     search_active: bool,
     search_query: String,
-    
+
     // UI state
     focus: PanelFocus,
     dialog_type: Option<DialogType>,
-    
+
     // Theme
     theme: Theme,
-    
+
     // Status message
     status_message: Option<String>,
 }
 
 impl MenuConfigApp {
-    pub fn new(entries: Vec<crate::kconfig::ast::Entry>, symbol_table: SymbolTable) -> Result<Self> {
-        // Build dependency maps
-        let mut dependency_resolver = DependencyResolver::new();
-        dependency_resolver.build_from_entries(&entries);
-        
+    /// Values already in `symbol_table` (e.g. loaded from .config) are
+    /// taken as user values; every other symbol starts at its default.
+    pub fn new(
+        entries: Vec<crate::kconfig::ast::Entry>,
+        symbol_table: SymbolTable,
+    ) -> Result<Self> {
+        let mut engine = ConfigEngine::from_entries(&entries);
+        engine.set_user_values(
+            symbol_table.all_symbols().filter_map(|(name, symbol)| {
+                symbol.value.clone().map(|value| (name.clone(), value))
+            }),
+        );
+        Self::with_engine(entries, engine)
+    }
+
+    /// Start from an engine that already holds the user's values
+    pub fn with_engine(
+        entries: Vec<crate::kconfig::ast::Entry>,
+        engine: ConfigEngine,
+    ) -> Result<Self> {
+        let symbol_table = engine.symbols();
+
         let mut config_state = ConfigState::build_from_entries(&entries);
-        
+
         // Initialize values from symbol table
         for item in &mut config_state.all_items {
-            if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } = &item.kind {
+            if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } =
+                &item.kind
+            {
                 let symbol_type = symbol_type.clone();
-                let had_value = Self::initialize_item_value(item, &symbol_type, &symbol_table);
+                let had_value = Self::initialize_item_value(item, &symbol_type, symbol_table);
                 // Store original value for tracking modifications
                 if had_value {
                     if let Some(value) = symbol_table.get_value(&item.id) {
-                        config_state.original_values.insert(item.id.clone(), value.clone());
+                        config_state
+                            .original_values
+                            .insert(item.id.clone(), value.clone());
                     }
                 }
             }
         }
-        
+
         // Also initialize values in menu_tree (critical fix for checkbox display)
         for (_, items) in config_state.menu_tree.iter_mut() {
             for item in items {
-                if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } = &item.kind {
+                if let MenuItemKind::Config { symbol_type }
+                | MenuItemKind::MenuConfig { symbol_type } = &item.kind
+                {
                     let symbol_type = symbol_type.clone();
-                    Self::initialize_item_value(item, &symbol_type, &symbol_table);
+                    Self::initialize_item_value(item, &symbol_type, symbol_table);
                 }
             }
         }
-        
-        Ok(Self {
+
+        let mut app = Self {
             config_state,
-            symbol_table,
+            engine,
             navigation: NavigationState::new(),
-            dependency_resolver,
             search_active: false,
             search_query: String::new(),
             focus: PanelFocus::MenuTree,
             dialog_type: None,
             theme: Theme::default(),
             status_message: None,
-        })
+        };
+        app.update_enabled_states()?;
+
+        Ok(app)
     }
-    
+
     /// Initialize the value for a menu item from the symbol table or set a default value.
-    /// 
+    ///
     /// This method looks up the item's value in the symbol table and updates the item's value field.
     /// If no value is found in the symbol table, it sets a default value based on the symbol type.
-    /// 
+    ///
     /// # Arguments
     /// * `item` - The menu item to initialize
     /// * `symbol_type` - The type of the symbol (Bool, Tristate, String, Int, or Hex)
     /// * `symbol_table` - The symbol table containing configuration values
-    /// 
+    ///
     /// # Returns
     /// `true` if a value was found in the symbol table, `false` if a default was used
-    fn initialize_item_value(item: &mut MenuItem, symbol_type: &SymbolType, symbol_table: &SymbolTable) -> bool {
+    fn initialize_item_value(
+        item: &mut MenuItem,
+        symbol_type: &SymbolType,
+        symbol_table: &SymbolTable,
+    ) -> bool {
         if let Some(value) = symbol_table.get_value(&item.id) {
             item.value = Some(Self::parse_value(&value, symbol_type));
             true
@@ -128,7 +179,7 @@ impl MenuConfigApp {
             false
         }
     }
-    
+
     fn parse_value(value: &str, symbol_type: &SymbolType) -> ConfigValue {
         match symbol_type {
             SymbolType::Bool => ConfigValue::Bool(value == "y"),
@@ -137,16 +188,16 @@ impl MenuConfigApp {
                 "m" => ConfigValue::Tristate(TristateValue::Module),
                 _ => ConfigValue::Tristate(TristateValue::No),
             },
-            SymbolType::String => ConfigValue::String(value.trim_matches('"').to_string()),
+            SymbolType::String => ConfigValue::String(value.to_string()),
             SymbolType::Int => ConfigValue::Int(value.parse().unwrap_or(0)),
             SymbolType::Hex => ConfigValue::Hex(value.to_string()),
         }
     }
-    
+
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         loop {
             terminal.draw(|f| self.render(f))?;
-            
+
             if event::poll(Duration::from_millis(100))? {
                 if let Event::Key(key) = event::read()? {
                     match self.handle_key(key)? {
@@ -156,42 +207,56 @@ impl MenuConfigApp {
                 }
             }
         }
-        
+
         Ok(())
     }
-    
+
     fn render(&mut self, frame: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3),  // Header
-                Constraint::Length(3),  // Search bar
-                Constraint::Min(0),     // Main content
-                Constraint::Length(3),  // Status bar
+                Constraint::Length(3), // Header
+                Constraint::Length(3), // Search bar
+                Constraint::Min(0),    // Main content
+                Constraint::Length(3), // Status bar
             ])
             .split(frame.size());
-        
+
         self.render_header(frame, chunks[0]);
         self.render_search_bar(frame, chunks[1]);
         self.render_main_content(frame, chunks[2]);
         self.render_status_bar(frame, chunks[3]);
-        
+
         // Render dialogs
         if let Some(dialog) = &self.dialog_type {
             match dialog {
                 DialogType::Help => self.render_help_modal(frame),
                 DialogType::Save => self.render_save_dialog(frame),
-                DialogType::DependencyError(error) => self.render_dependency_error_dialog(frame, error),
+                DialogType::DependencyError { error, fix } => {
+                    self.render_dependency_error_dialog(frame, error, fix.as_ref())
+                }
                 DialogType::CascadeWarning { symbol, affected } => {
                     self.render_cascade_warning_dialog(frame, symbol, affected)
                 }
                 DialogType::ImplySuggestion { implied } => {
                     self.render_imply_suggestion_dialog(frame, implied)
                 }
+                DialogType::EditValue {
+                    symbol,
+                    symbol_type,
+                    input,
+                    error,
+                } => self.render_edit_value_dialog(
+                    frame,
+                    symbol,
+                    symbol_type,
+                    input,
+                    error.as_deref(),
+                ),
             }
         }
     }
-    
+
     fn render_header(&self, frame: &mut Frame, area: Rect) {
         let modified_count = self.config_state.modified_symbols.len();
         let title = format!(
@@ -203,67 +268,69 @@ impl MenuConfigApp {
             },
             "  [S]ave [Q]uit "
         );
-        
+
         let header = Paragraph::new(title)
             .style(self.theme.get_info_style().add_modifier(Modifier::BOLD))
             .block(Block::default().borders(Borders::ALL));
-        
+
         frame.render_widget(header, area);
     }
-    
+
     fn render_search_bar(&self, frame: &mut Frame, area: Rect) {
         let search_text = if self.search_active {
             format!(" 🔍 Search: {}_", self.search_query)
         } else {
             " 🔍 Press / to search".to_string()
         };
-        
+
         let style = if self.search_active {
             self.theme.get_selected_style()
         } else {
             Style::default()
         };
-        
+
         let search = Paragraph::new(search_text)
             .style(style)
             .block(Block::default().borders(Borders::ALL));
-        
+
         frame.render_widget(search, area);
     }
-    
+
     fn render_main_content(&mut self, frame: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
             .split(area);
-        
+
         self.render_menu_tree(frame, chunks[0]);
         self.render_detail_panel(frame, chunks[1]);
     }
-    
+
     fn render_menu_tree(&mut self, frame: &mut Frame, area: Rect) {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if items.is_empty() {
-            let empty = Paragraph::new("No items found")
-                .block(Block::default()
+            let empty = Paragraph::new("No items found").block(
+                Block::default()
                     .borders(Borders::ALL)
-                    .title(" Configuration Menu "));
+                    .title(" Configuration Menu "),
+            );
             frame.render_widget(empty, area);
             return;
         }
-        
+
         // Ensure selected index is valid
         if self.navigation.selected_index >= items.len() {
             self.navigation.selected_index = items.len().saturating_sub(1);
         }
-        
+
         let list_items: Vec<ListItem> = items
             .iter()
             .enumerate()
@@ -272,27 +339,28 @@ impl MenuConfigApp {
                 self.create_list_item(item, is_selected)
             })
             .collect();
-        
-        let list = List::new(list_items)
-            .block(Block::default()
+
+        let list = List::new(list_items).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Configuration Menu ")
                 .border_style(if self.focus == PanelFocus::MenuTree {
                     self.theme.get_selected_style()
                 } else {
                     self.theme.get_border_style()
-                }));
-        
+                }),
+        );
+
         frame.render_widget(list, area);
     }
-    
+
     fn create_list_item(&self, item: &MenuItem, is_selected: bool) -> ListItem<'_> {
         let indent = "  ".repeat(item.depth);
         let icon = self.get_item_icon(item);
         let checkbox = self.get_checkbox_symbol(item);
         let label = &item.label;
         let value_display = self.format_value_display(item);
-        
+
         let style = if is_selected {
             self.theme.get_selected_style()
         } else if !item.is_enabled {
@@ -300,22 +368,29 @@ impl MenuConfigApp {
         } else {
             Style::default()
         };
-        
-        let text = format!("{}{} {} {} {}", indent, icon, checkbox, label, value_display);
+
+        let text = format!(
+            "{}{} {} {} {}",
+            indent, icon, checkbox, label, value_display
+        );
         ListItem::new(text).style(style)
     }
-    
+
     fn get_item_icon(&self, item: &MenuItem) -> &str {
         match &item.kind {
             MenuItemKind::Menu { .. } => {
-                if item.has_children { "📁" } else { "📂" }
+                if item.has_children {
+                    "📁"
+                } else {
+                    "📂"
+                }
             }
             MenuItemKind::Config { .. } | MenuItemKind::MenuConfig { .. } => "⚙️ ",
             MenuItemKind::Choice { .. } => "◉",
             MenuItemKind::Comment { .. } => "💬",
         }
     }
-    
+
     fn get_checkbox_symbol(&self, item: &MenuItem) -> &str {
         match &item.value {
             Some(ConfigValue::Bool(true)) => "[✓]",
@@ -327,7 +402,7 @@ impl MenuConfigApp {
             _ => "   ",
         }
     }
-    
+
     fn format_value_display(&self, item: &MenuItem) -> String {
         match &item.value {
             Some(ConfigValue::String(s)) if !s.is_empty() => format!("= \"{}\"", s),
@@ -336,36 +411,38 @@ impl MenuConfigApp {
             _ => String::new(),
         }
     }
-    
+
     fn render_detail_panel(&self, frame: &mut Frame, area: Rect) {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if items.is_empty() || self.navigation.selected_index >= items.len() {
-            let empty = Paragraph::new("No item selected")
-                .block(Block::default()
+            let empty = Paragraph::new("No item selected").block(
+                Block::default()
                     .borders(Borders::ALL)
-                    .title(" 📖 Help & Details "));
+                    .title(" 📖 Help & Details "),
+            );
             frame.render_widget(empty, area);
             return;
         }
-        
+
         let item = &items[self.navigation.selected_index];
-        
+
         let mut text_lines = vec![];
-        
+
         // Title
         text_lines.push(Line::from(vec![
             Span::styled("📖 ", self.theme.get_info_style()),
             Span::styled(&item.label, Style::default().add_modifier(Modifier::BOLD)),
         ]));
         text_lines.push(Line::from(""));
-        
+
         // Type and ID
         let type_str = match &item.kind {
             MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } => {
@@ -377,8 +454,17 @@ impl MenuConfigApp {
         };
         text_lines.push(Line::from(type_str));
         text_lines.push(Line::from(format!("ID: {}", item.id)));
+
+        // Every definition of a symbol, or the entry's own location
+        let definitions = self.engine.resolver().defined_at(&item.id);
+        if definitions.is_empty() {
+            text_lines.push(Line::from(format!("Defined at {}", item.defined_at)));
+        }
+        for span in definitions {
+            text_lines.push(Line::from(format!("Defined at {}", span)));
+        }
         text_lines.push(Line::from(""));
-        
+
         // Current value
         if let Some(value) = &item.value {
             let value_str = match value {
@@ -394,7 +480,7 @@ impl MenuConfigApp {
             text_lines.push(Line::from(value_str));
             text_lines.push(Line::from(""));
         }
-        
+
         // Help text
         if let Some(help) = &item.help_text {
             text_lines.push(Line::from("Description:"));
@@ -405,7 +491,7 @@ impl MenuConfigApp {
             }
             text_lines.push(Line::from(""));
         }
-        
+
         // Dependencies
         if !item.selects.is_empty() {
             text_lines.push(Line::from("⚡ Enables:"));
@@ -413,32 +499,31 @@ impl MenuConfigApp {
                 text_lines.push(Line::from(format!("  • {}", select)));
             }
         }
-        
-        let detail = Paragraph::new(text_lines)
-            .wrap(Wrap { trim: true })
-            .block(Block::default()
+
+        let detail = Paragraph::new(text_lines).wrap(Wrap { trim: true }).block(
+            Block::default()
                 .borders(Borders::ALL)
-                .title(" 📖 Help & Details "));
-        
+                .title(" 📖 Help & Details "),
+        );
+
         frame.render_widget(detail, area);
     }
-    
+
     fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
         let status_text = if let Some(msg) = &self.status_message {
             msg.clone()
         } else {
             " ↑↓:Navigate │ Space:Toggle │ Enter:Open │ /:Search │ ?:Help │ ESC:Back".to_string()
         };
-        
-        let status = Paragraph::new(status_text)
-            .block(Block::default().borders(Borders::ALL));
-        
+
+        let status = Paragraph::new(status_text).block(Block::default().borders(Borders::ALL));
+
         frame.render_widget(status, area);
     }
-    
+
     fn render_help_modal(&self, frame: &mut Frame) {
         let area = self.centered_rect(60, 70, frame.size());
-        
+
         let help_text = vec![
             "Keyboard Shortcuts",
             "══════════════════",
@@ -454,7 +539,8 @@ impl MenuConfigApp {
             "  End        - Jump to last",
             "",
             "Actions:",
-            "  Space      - Toggle option",
+            "  Space      - Toggle option, or edit a value",
+            "  Enter      - Edit an int, hex or string value",
             "  s/S        - Save configuration",
             "  q/Q        - Quit",
             "  /          - Search",
@@ -462,21 +548,22 @@ impl MenuConfigApp {
             "",
             "Press any key to close",
         ];
-        
+
         let text: Vec<Line> = help_text.into_iter().map(Line::from).collect();
-        
-        let help = Paragraph::new(text)
-            .block(Block::default()
+
+        let help = Paragraph::new(text).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Help ")
-                .style(self.theme.get_info_style()));
-        
+                .style(self.theme.get_info_style()),
+        );
+
         frame.render_widget(help, area);
     }
-    
+
     fn render_save_dialog(&self, frame: &mut Frame) {
         let area = self.centered_rect(50, 30, frame.size());
-        
+
         let text = vec![
             "Save Configuration?",
             "",
@@ -486,18 +573,19 @@ impl MenuConfigApp {
             "  n - Quit without saving",
             "  ESC - Cancel",
         ];
-        
+
         let lines: Vec<Line> = text.into_iter().map(Line::from).collect();
-        
-        let dialog = Paragraph::new(lines)
-            .block(Block::default()
+
+        let dialog = Paragraph::new(lines).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Confirm ")
-                .style(self.theme.get_warning_style()));
-        
+                .style(self.theme.get_warning_style()),
+        );
+
         frame.render_widget(dialog, area);
     }
-    
+
     fn centered_rect(&self, percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         let popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -507,7 +595,7 @@ impl MenuConfigApp {
                 Constraint::Percentage((100 - percent_y) / 2),
             ])
             .split(r);
-        
+
         Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
@@ -517,8 +605,14 @@ impl MenuConfigApp {
             ])
             .split(popup_layout[1])[1]
     }
-    
-    fn handle_key(&mut self, key: KeyEvent) -> Result<EventResult> {
+
+    /// The dialog currently open, if any
+    pub fn dialog(&self) -> Option<&DialogType> {
+        self.dialog_type.as_ref()
+    }
+
+    /// Handle one key press, as the event loop does
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<EventResult> {
         // Handle dialogs first - check type without moving
         let has_dialog = self.dialog_type.is_some();
         if has_dialog {
@@ -528,18 +622,25 @@ impl MenuConfigApp {
                     Ok(EventResult::Continue)
                 }
                 Some(DialogType::Save) => self.handle_save_dialog_key(key),
-                Some(DialogType::DependencyError(_)) => self.handle_dependency_error_dialog_key(key),
-                Some(DialogType::CascadeWarning { .. }) => self.handle_cascade_warning_dialog_key(key),
-                Some(DialogType::ImplySuggestion { .. }) => self.handle_imply_suggestion_dialog_key(key),
+                Some(DialogType::DependencyError { .. }) => {
+                    self.handle_dependency_error_dialog_key(key)
+                }
+                Some(DialogType::CascadeWarning { .. }) => {
+                    self.handle_cascade_warning_dialog_key(key)
+                }
+                Some(DialogType::ImplySuggestion { .. }) => {
+                    self.handle_imply_suggestion_dialog_key(key)
+                }
+                Some(DialogType::EditValue { .. }) => self.handle_edit_value_dialog_key(key),
                 None => Ok(EventResult::Continue),
             };
         }
-        
+
         // Handle search mode
         if self.search_active {
             return self.handle_search_key(key);
         }
-        
+
         // Main navigation
         match key.code {
             KeyCode::Char('q') | KeyCode::Char('Q') => {
@@ -603,7 +704,7 @@ impl MenuConfigApp {
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
     fn handle_save_dialog_key(&mut self, key: KeyEvent) -> Result<EventResult> {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
@@ -622,7 +723,7 @@ impl MenuConfigApp {
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
     fn handle_search_key(&mut self, key: KeyEvent) -> Result<EventResult> {
         match key.code {
             KeyCode::Esc => {
@@ -650,17 +751,59 @@ impl MenuConfigApp {
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
     fn handle_dependency_error_dialog_key(&mut self, key: KeyEvent) -> Result<EventResult> {
-        match key.code {
-            KeyCode::Esc => {
+        let fix = match &self.dialog_type {
+            Some(DialogType::DependencyError { fix, .. }) => fix.clone(),
+            _ => return Ok(EventResult::Continue),
+        };
+
+        match (key.code, fix) {
+            (KeyCode::Char('y') | KeyCode::Char('Y'), Some(fix)) => {
+                for (name, value) in &fix.assignments {
+                    self.apply_user_value(name, value.clone());
+                }
+                self.apply_user_value(&fix.symbol, fix.value.clone());
+                self.sync_ui_state_from_symbol_table()?;
+                self.update_enabled_states()?;
+
+                let changes: Vec<String> = fix
+                    .assignments
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect();
+                self.status_message = Some(format!(
+                    " {}={} (also set: {})",
+                    fix.symbol,
+                    fix.value,
+                    changes.join(", ")
+                ));
+                self.dialog_type = None;
+                Ok(EventResult::Continue)
+            }
+            (KeyCode::Char('n') | KeyCode::Char('N'), Some(_)) | (KeyCode::Esc, _) => {
                 self.dialog_type = None;
                 Ok(EventResult::Continue)
             }
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
+    /// The error dialog for a refused change of `symbol` to `value`, with
+    /// the changes that would allow it
+    fn dependency_error_dialog(
+        &self,
+        error: DependencyError,
+        symbol: &str,
+        value: &str,
+    ) -> DialogType {
+        let fix = self
+            .engine
+            .solve(symbol, value)
+            .filter(|solution| !solution.assignments.is_empty());
+        DialogType::DependencyError { error, fix }
+    }
+
     fn handle_cascade_warning_dialog_key(&mut self, key: KeyEvent) -> Result<EventResult> {
         // Extract symbol before any mutable operations
         let symbol = if let Some(DialogType::CascadeWarning { symbol, .. }) = &self.dialog_type {
@@ -668,7 +811,7 @@ impl MenuConfigApp {
         } else {
             return Ok(EventResult::Continue);
         };
-        
+
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
                 // Proceed with disabling
@@ -687,7 +830,7 @@ impl MenuConfigApp {
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
     fn handle_imply_suggestion_dialog_key(&mut self, key: KeyEvent) -> Result<EventResult> {
         // Extract implied list before any mutable operations
         let implied = if let Some(DialogType::ImplySuggestion { implied }) = &self.dialog_type {
@@ -695,12 +838,12 @@ impl MenuConfigApp {
         } else {
             return Ok(EventResult::Continue);
         };
-        
+
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
                 // Enable implied symbols
                 for symbol in &implied {
-                    self.symbol_table.set_value(symbol, "y".to_string());
+                    self.apply_user_value(symbol, "y".to_string());
                 }
                 self.sync_ui_state_from_symbol_table()?;
                 self.update_enabled_states()?;
@@ -715,41 +858,180 @@ impl MenuConfigApp {
             _ => Ok(EventResult::Continue),
         }
     }
-    
+
+    fn handle_edit_value_dialog_key(&mut self, key: KeyEvent) -> Result<EventResult> {
+        let Some(DialogType::EditValue {
+            symbol,
+            symbol_type,
+            input,
+            error,
+        }) = &mut self.dialog_type
+        else {
+            return Ok(EventResult::Continue);
+        };
+
+        match key.code {
+            KeyCode::Char(c) => {
+                input.push(c);
+                *error = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                *error = None;
+            }
+            KeyCode::Esc => self.dialog_type = None,
+            KeyCode::Enter => {
+                let (symbol, symbol_type, input) =
+                    (symbol.clone(), symbol_type.clone(), input.clone());
+                match self.validate_input(&symbol, &symbol_type, &input) {
+                    Ok(value) => {
+                        self.dialog_type = None;
+                        self.apply_value_change(&symbol, value)?;
+                        self.sync_ui_state_from_symbol_table()?;
+                        self.update_enabled_states()?;
+                        let value = self.engine.value(&symbol).unwrap_or_default();
+                        self.status_message = Some(format!(" {} set to {}", symbol, value));
+                    }
+                    Err(message) => {
+                        if let Some(DialogType::EditValue { error, .. }) = &mut self.dialog_type {
+                            *error = Some(message);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(EventResult::Continue)
+    }
+
+    /// The typed value, if its type accepts it and it lies in the active range
+    fn validate_input(
+        &self,
+        symbol: &str,
+        symbol_type: &SymbolType,
+        input: &str,
+    ) -> std::result::Result<ConfigValue, String> {
+        let value = normalize_value(symbol_type, input.trim())
+            .map_err(|expected| format!("Invalid value: {}", expected))?;
+
+        if let (Some(number), Some((low, high))) = (
+            parse_number(symbol_type, &value),
+            self.engine
+                .resolver()
+                .active_range(symbol, self.engine.symbols()),
+        ) {
+            if number < low || number > high {
+                return Err(format!(
+                    "Must be between {} and {}",
+                    format_number(symbol_type, low),
+                    format_number(symbol_type, high)
+                ));
+            }
+        }
+
+        Ok(match symbol_type {
+            SymbolType::Int => ConfigValue::Int(value.parse().unwrap_or_default()),
+            SymbolType::Hex => ConfigValue::Hex(value),
+            _ => ConfigValue::String(value),
+        })
+    }
+
+    /// Open the value editor for an int, hex or string item the user can
+    /// change, or explain why it cannot be changed
+    /// Returns: false if the item holds no such value
+    fn edit_value(&mut self, item: &MenuItem) -> bool {
+        let symbol_type = match &item.kind {
+            MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } => {
+                symbol_type.clone()
+            }
+            _ => return false,
+        };
+        if matches!(symbol_type, SymbolType::Bool | SymbolType::Tristate) {
+            return false;
+        }
+
+        if let Err(e) = self
+            .engine
+            .resolver()
+            .can_enable(&item.id, self.engine.symbols())
+        {
+            // Offer the changes that let it keep its value or default
+            let value = self
+                .engine
+                .user_value(&item.id)
+                .cloned()
+                .or_else(|| {
+                    let default = self
+                        .engine
+                        .resolver()
+                        .active_default(&item.id, self.engine.symbols())?;
+                    match default.expr {
+                        Expr::Const(value) => Some(value.clone()),
+                        _ => None,
+                    }
+                })
+                .unwrap_or_else(|| match symbol_type {
+                    SymbolType::Int => "0".to_string(),
+                    SymbolType::Hex => "0x0".to_string(),
+                    _ => String::new(),
+                });
+            self.dialog_type = Some(self.dependency_error_dialog(e, &item.id, &value));
+        } else if !self.engine.visibility(&item.id).is_enabled() {
+            self.status_message = Some(format!(
+                " {} has no visible prompt and cannot be changed",
+                item.id
+            ));
+        } else {
+            self.dialog_type = Some(DialogType::EditValue {
+                symbol: item.id.clone(),
+                symbol_type,
+                input: self.engine.value(&item.id).unwrap_or_default(),
+                error: None,
+            });
+        }
+        true
+    }
+
     fn move_up(&mut self) {
         if self.navigation.selected_index > 0 {
             self.navigation.selected_index -= 1;
         }
     }
-    
+
     fn move_down(&mut self) {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect::<Vec<_>>()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if !items.is_empty() && self.navigation.selected_index < items.len() - 1 {
             self.navigation.selected_index += 1;
         }
     }
-    
+
     fn enter_submenu(&mut self) {
-        let items = self.config_state.get_items_for_path(&self.navigation.current_path);
+        let items = self
+            .config_state
+            .get_items_for_path(&self.navigation.current_path);
         if items.is_empty() || self.navigation.selected_index >= items.len() {
             return;
         }
-        
+
         let item = &items[self.navigation.selected_index];
+        if !item.has_children && self.edit_value(item) {
+            return;
+        }
         if item.has_children {
             self.navigation.current_path.push(item.id.clone());
             self.navigation.selected_index = 0;
             self.navigation.scroll_offset = 0;
         }
     }
-    
+
     fn go_back(&mut self) {
         if !self.navigation.current_path.is_empty() {
             self.navigation.current_path.pop();
@@ -757,59 +1039,66 @@ impl MenuConfigApp {
             self.navigation.scroll_offset = 0;
         }
     }
-    
+
     fn page_up(&mut self) {
         self.navigation.selected_index = self.navigation.selected_index.saturating_sub(10);
     }
-    
+
     fn page_down(&mut self) {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect::<Vec<_>>()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if !items.is_empty() {
-            self.navigation.selected_index = (self.navigation.selected_index + 10).min(items.len() - 1);
+            self.navigation.selected_index =
+                (self.navigation.selected_index + 10).min(items.len() - 1);
         }
     }
-    
+
     fn jump_to_first(&mut self) {
         self.navigation.selected_index = 0;
     }
-    
+
     fn jump_to_last(&mut self) {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect::<Vec<_>>()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if !items.is_empty() {
             self.navigation.selected_index = items.len() - 1;
         }
     }
-    
+
     fn toggle_current_item(&mut self) -> Result<()> {
         let items = if self.search_active && !self.search_query.is_empty() {
             let searcher = FuzzySearcher::new(self.search_query.clone());
             let results = searcher.search(&self.config_state.all_items);
             results.into_iter().map(|r| r.item).collect::<Vec<_>>()
         } else {
-            self.config_state.get_items_for_path(&self.navigation.current_path)
+            self.config_state
+                .get_items_for_path(&self.navigation.current_path)
         };
-        
+
         if items.is_empty() || self.navigation.selected_index >= items.len() {
             return Ok(());
         }
-        
+
         let item = &items[self.navigation.selected_index];
         let item_id = item.id.clone();
-        
+        if self.edit_value(item) {
+            return Ok(());
+        }
+
         // Toggle value
         let new_value = match &item.value {
             Some(ConfigValue::Bool(b)) => Some(ConfigValue::Bool(!b)),
@@ -820,22 +1109,58 @@ impl MenuConfigApp {
             })),
             _ => None,
         };
-        
+
+        // Without modules a tristate toggles between y and n only
+        let new_value = match new_value {
+            Some(ConfigValue::Tristate(TristateValue::Module))
+                if !self
+                    .engine
+                    .resolver()
+                    .modules_enabled(self.engine.symbols()) =>
+            {
+                Some(ConfigValue::Tristate(TristateValue::No))
+            }
+            other => other,
+        };
+
+        // Dependencies at m only allow a tristate to be m
+        let new_value = match new_value {
+            Some(ConfigValue::Tristate(TristateValue::Yes))
+                if self
+                    .engine
+                    .resolver()
+                    .max_value(&item_id, self.engine.symbols())
+                    == Tristate::Module =>
+            {
+                Some(ConfigValue::Tristate(TristateValue::Module))
+            }
+            other => other,
+        };
+
         if let Some(new_val) = new_value {
             let is_enabling = matches!(
                 new_val,
-                ConfigValue::Bool(true) | ConfigValue::Tristate(TristateValue::Yes | TristateValue::Module)
+                ConfigValue::Bool(true)
+                    | ConfigValue::Tristate(TristateValue::Yes | TristateValue::Module)
             );
-            
+
             if is_enabling {
                 // Check dependencies before enabling
-                match self.dependency_resolver.can_enable(&item_id, &self.symbol_table) {
+                match self
+                    .engine
+                    .resolver()
+                    .can_enable(&item_id, self.engine.symbols())
+                {
                     Ok(_) => {
-                        // Apply the change
+                        // Apply the change; the engine cascades selects
+                        let was_enabled = self.enabled_symbols();
                         self.apply_value_change(&item_id, new_val.clone())?;
-                        
-                        // Apply select cascade
-                        let selected = self.dependency_resolver.apply_selects(&item_id, &mut self.symbol_table);
+
+                        let selected: Vec<String> = self
+                            .enabled_symbols()
+                            .into_iter()
+                            .filter(|name| *name != item_id && !was_enabled.contains(name))
+                            .collect();
                         if !selected.is_empty() {
                             self.status_message = Some(format!(
                                 " {} enabled (also enabled: {})",
@@ -845,27 +1170,42 @@ impl MenuConfigApp {
                         } else {
                             self.status_message = Some(format!(" {} enabled", item_id));
                         }
-                        
+
                         // Check for implied symbols
-                        let implied = self.dependency_resolver.get_implied_symbols(&item_id, &self.symbol_table);
+                        let implied = self
+                            .engine
+                            .resolver()
+                            .get_implied_symbols(&item_id, self.engine.symbols());
                         if !implied.is_empty() {
                             // Show suggestion dialog
                             self.dialog_type = Some(DialogType::ImplySuggestion { implied });
                         }
                     }
                     Err(e) => {
-                        // Show error dialog
-                        self.dialog_type = Some(DialogType::DependencyError(e));
+                        // Show error dialog, offering the changes that would help
+                        let value = if new_val == ConfigValue::Tristate(TristateValue::Module) {
+                            "m"
+                        } else {
+                            "y"
+                        };
+                        self.dialog_type = Some(self.dependency_error_dialog(e, &item_id, value));
                         return Ok(());
                     }
                 }
             } else {
                 // Disabling
-                match self.dependency_resolver.can_disable(&item_id, &self.symbol_table) {
+                match self
+                    .engine
+                    .resolver()
+                    .can_disable(&item_id, self.engine.symbols())
+                {
                     Ok(_) => {
                         // Check what will be affected
-                        let affected = self.dependency_resolver.check_disable_cascade(&item_id, &self.symbol_table);
-                        
+                        let affected = self
+                            .engine
+                            .resolver()
+                            .check_disable_cascade(&item_id, self.engine.symbols());
+
                         if !affected.is_empty() {
                             // Warn user
                             self.dialog_type = Some(DialogType::CascadeWarning {
@@ -878,20 +1218,20 @@ impl MenuConfigApp {
                         }
                     }
                     Err(e) => {
-                        self.dialog_type = Some(DialogType::DependencyError(e));
+                        self.dialog_type = Some(self.dependency_error_dialog(e, &item_id, "n"));
                         return Ok(());
                     }
                 }
             }
-            
+
             // Force UI refresh
             self.sync_ui_state_from_symbol_table()?;
             self.update_enabled_states()?;
         }
-        
+
         Ok(())
     }
-    
+
     fn apply_value_change(&mut self, item_id: &str, new_val: ConfigValue) -> Result<()> {
         // Update symbol table
         let value_str = match new_val {
@@ -900,99 +1240,146 @@ impl MenuConfigApp {
             ConfigValue::Tristate(TristateValue::Yes) => "y".to_string(),
             ConfigValue::Tristate(TristateValue::No) => "n".to_string(),
             ConfigValue::Tristate(TristateValue::Module) => "m".to_string(),
-            ConfigValue::String(s) => format!("\"{}\"", s),
+            ConfigValue::String(s) => s,
             ConfigValue::Int(i) => i.to_string(),
             ConfigValue::Hex(h) => h,
         };
-        
-        self.symbol_table.set_value_tracked(item_id, value_str.clone());
-        
+
+        self.apply_user_value(item_id, value_str);
+        Ok(())
+    }
+
+    /// Set a user value, tracking it as modified unless it is the original
+    fn apply_user_value(&mut self, item_id: &str, value_str: String) {
+        self.engine.set_user_value(item_id, &value_str);
+
         // Track modification
         let original = self.config_state.original_values.get(item_id).cloned();
         if original.as_deref() != Some(value_str.as_str()) {
-            self.config_state.modified_symbols.insert(item_id.to_string(), value_str);
+            self.config_state
+                .modified_symbols
+                .insert(item_id.to_string(), value_str);
         } else {
             self.config_state.modified_symbols.remove(item_id);
         }
-        
-        Ok(())
     }
-    
-    /// Update enabled states based on dependencies
+
+    /// Enabled symbols in Kconfig declaration order
+    fn enabled_symbols(&self) -> Vec<String> {
+        self.engine
+            .resolver()
+            .symbols()
+            .iter()
+            .filter(|name| self.engine.symbols().is_enabled(name))
+            .cloned()
+            .collect()
+    }
+
+    /// Update enabled states based on dependencies, and hide transitional
+    /// symbols and symbols whose prompt condition is not met
     fn update_enabled_states(&mut self) -> Result<()> {
         for item in &mut self.config_state.all_items {
             if let MenuItemKind::Config { .. } | MenuItemKind::MenuConfig { .. } = &item.kind {
                 // Check if dependencies are met
-                item.is_enabled = self.dependency_resolver
-                    .can_enable(&item.id, &self.symbol_table)
+                item.is_enabled = self
+                    .engine
+                    .resolver()
+                    .can_enable(&item.id, self.engine.symbols())
                     .is_ok();
+                item.is_visible = !self.engine.resolver().is_transitional(&item.id)
+                    && !self
+                        .engine
+                        .resolver()
+                        .is_prompt_hidden(&item.id, self.engine.symbols());
             }
         }
-        
+
         // Also update menu_tree
         for (_key, items) in self.config_state.menu_tree.iter_mut() {
             for item in items {
                 if let MenuItemKind::Config { .. } | MenuItemKind::MenuConfig { .. } = &item.kind {
-                    item.is_enabled = self.dependency_resolver
-                        .can_enable(&item.id, &self.symbol_table)
+                    item.is_enabled = self
+                        .engine
+                        .resolver()
+                        .can_enable(&item.id, self.engine.symbols())
                         .is_ok();
+                    item.is_visible = !self.engine.resolver().is_transitional(&item.id)
+                        && !self
+                            .engine
+                            .resolver()
+                            .is_prompt_hidden(&item.id, self.engine.symbols());
                 }
             }
         }
-        
+
         Ok(())
     }
-    
+
     /// Synchronize UI state from symbol table
     /// This ensures the UI always shows current symbol values
     fn sync_ui_state_from_symbol_table(&mut self) -> Result<()> {
         // Update all_items
         for item in &mut self.config_state.all_items {
-            if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } = &item.kind {
-                if let Some(value) = self.symbol_table.get_value(&item.id) {
+            if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } =
+                &item.kind
+            {
+                if let Some(value) = self.engine.value(&item.id) {
                     item.value = Some(Self::parse_value(&value, symbol_type));
                 }
             }
         }
-        
+
         // Update menu_tree
         for (_key, items) in self.config_state.menu_tree.iter_mut() {
             for item in items {
-                if let MenuItemKind::Config { symbol_type } | MenuItemKind::MenuConfig { symbol_type } = &item.kind {
-                    if let Some(value) = self.symbol_table.get_value(&item.id) {
+                if let MenuItemKind::Config { symbol_type }
+                | MenuItemKind::MenuConfig { symbol_type } = &item.kind
+                {
+                    if let Some(value) = self.engine.value(&item.id) {
                         item.value = Some(Self::parse_value(&value, symbol_type));
                     }
                 }
             }
         }
-        
+
         Ok(())
     }
-    
+
     fn save_config(&mut self) -> Result<()> {
         use crate::config::ConfigWriter;
         use std::path::Path;
-        
-        ConfigWriter::write(Path::new(".config"), &self.symbol_table)?;
-        
+
+        let updated = ConfigWriter::write(Path::new(".config"), self.engine.symbols())?;
+
         // Clear modified symbols after save
         self.config_state.modified_symbols.clear();
-        
+
         // Update original values
-        for (name, symbol) in self.symbol_table.all_symbols() {
+        for (name, symbol) in self.engine.symbols().all_symbols() {
             if let Some(value) = &symbol.value {
-                self.config_state.original_values.insert(name.clone(), value.clone());
+                self.config_state
+                    .original_values
+                    .insert(name.clone(), value.clone());
             }
         }
-        
-        self.status_message = Some(" Configuration saved to .config".to_string());
+
+        self.status_message = Some(if updated {
+            " Configuration saved to .config".to_string()
+        } else {
+            " .config is unchanged".to_string()
+        });
         Ok(())
     }
-    
-    fn render_dependency_error_dialog(&self, frame: &mut Frame, error: &DependencyError) {
-        let area = self.centered_rect(60, 40, frame.size());
-        
-        let message = match error {
+
+    fn render_dependency_error_dialog(
+        &self,
+        frame: &mut Frame,
+        error: &DependencyError,
+        fix: Option<&Solution>,
+    ) {
+        let area = self.centered_rect(60, if fix.is_some() { 60 } else { 40 }, frame.size());
+
+        let mut message = match error {
             DependencyError::DependencyNotMet { symbol, required } => {
                 vec![
                     Line::from("⚠️  Dependency Not Met"),
@@ -1000,8 +1387,6 @@ impl MenuConfigApp {
                     Line::from(format!("Cannot enable: {}", symbol)),
                     Line::from(""),
                     Line::from(format!("Requires: {} (currently disabled)", required)),
-                    Line::from(""),
-                    Line::from("Press ESC to close"),
                 ]
             }
             DependencyError::SelectedBy { symbol, selector } => {
@@ -1011,8 +1396,6 @@ impl MenuConfigApp {
                     Line::from(format!("Cannot disable: {}", symbol)),
                     Line::from(""),
                     Line::from(format!("Selected by: {} (currently enabled)", selector)),
-                    Line::from(""),
-                    Line::from("Press ESC to close"),
                 ]
             }
             DependencyError::ConditionNotMet { symbol, condition } => {
@@ -1022,71 +1405,161 @@ impl MenuConfigApp {
                     Line::from(format!("Cannot enable: {}", symbol)),
                     Line::from(""),
                     Line::from(format!("Condition: {}", condition)),
+                ]
+            }
+            DependencyError::CircularDependency { chain } => {
+                vec![
+                    Line::from("⚠️  Recursive Dependency"),
+                    Line::from(""),
+                    Line::from(format!(
+                        "Cannot enable: {}",
+                        chain.first().map_or("", String::as_str)
+                    )),
                     Line::from(""),
-                    Line::from("Press ESC to close"),
+                    Line::from(format!("Cycle: {}", chain.join(" -> "))),
+                    Line::from(""),
+                    Line::from("Run `rkconf lint` for the locations"),
                 ]
             }
-            _ => vec![Line::from(format!("Error: {}", error))],
         };
-        
-        let dialog = Paragraph::new(message)
-            .block(Block::default()
+
+        message.push(Line::from(""));
+        match fix {
+            Some(fix) => {
+                let count = fix.assignments.len();
+                let plural = if count == 1 { "" } else { "s" };
+                message.push(Line::from(if fix.only_enables() {
+                    format!("Enable these {} symbol{}?", count, plural)
+                } else {
+                    format!("Apply these {} change{}?", count, plural)
+                }));
+                for (name, value) in &fix.assignments {
+                    message.push(Line::from(format!("  • {}={}", name, value)));
+                }
+                if !fix.side_effects.is_empty() {
+                    let names: Vec<&str> = fix
+                        .side_effects
+                        .iter()
+                        .map(|(name, _, _)| name.as_str())
+                        .collect();
+                    message.push(Line::from(format!("Also changes: {}", names.join(", "))));
+                }
+                message.push(Line::from(""));
+                message.push(Line::from("[Y/n/ESC]"));
+            }
+            None => message.push(Line::from("Press ESC to close")),
+        }
+
+        let dialog = Paragraph::new(message).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Dependency Error ")
-                .style(self.theme.get_warning_style()));
-        
+                .style(self.theme.get_warning_style()),
+        );
+
         frame.render_widget(dialog, area);
     }
-    
+
+    fn render_edit_value_dialog(
+        &self,
+        frame: &mut Frame,
+        symbol: &str,
+        symbol_type: &SymbolType,
+        input: &str,
+        error: Option<&str>,
+    ) {
+        let area = self.centered_rect(60, 40, frame.size());
+
+        let type_name = match symbol_type {
+            SymbolType::Int => "Decimal number",
+            SymbolType::Hex => "Hexadecimal number",
+            _ => "Text",
+        };
+        let mut lines = vec![Line::from(format!("{} for {}", type_name, symbol))];
+        if let Some((low, high)) = self
+            .engine
+            .resolver()
+            .active_range(symbol, self.engine.symbols())
+        {
+            lines.push(Line::from(format!(
+                "Range: {} to {}",
+                format_number(symbol_type, low),
+                format_number(symbol_type, high)
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(format!("> {}_", input)));
+        lines.push(Line::from(""));
+        if let Some(error) = error {
+            lines.push(Line::styled(
+                format!("⚠️  {}", error),
+                self.theme.get_error_style(),
+            ));
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from("Enter:Accept │ ESC:Cancel"));
+
+        let dialog = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Edit Value ")
+                .style(self.theme.get_info_style()),
+        );
+
+        frame.render_widget(dialog, area);
+    }
+
     fn render_cascade_warning_dialog(&self, frame: &mut Frame, symbol: &str, affected: &[String]) {
         let area = self.centered_rect(60, 50, frame.size());
-        
+
         let mut lines = vec![
             Line::from("⚠️  Cascade Warning"),
             Line::from(""),
             Line::from(format!("Disabling {} will also affect:", symbol)),
             Line::from(""),
         ];
-        
+
         for affected_symbol in affected {
             lines.push(Line::from(format!("  • {}", affected_symbol)));
         }
-        
+
         lines.push(Line::from(""));
         lines.push(Line::from("Continue? [Y/n/ESC]"));
-        
-        let dialog = Paragraph::new(lines)
-            .block(Block::default()
+
+        let dialog = Paragraph::new(lines).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Warning ")
-                .style(self.theme.get_warning_style()));
-        
+                .style(self.theme.get_warning_style()),
+        );
+
         frame.render_widget(dialog, area);
     }
-    
+
     fn render_imply_suggestion_dialog(&self, frame: &mut Frame, implied: &[String]) {
         let area = self.centered_rect(60, 40, frame.size());
-        
+
         let mut lines = vec![
             Line::from("💡 Suggestion"),
             Line::from(""),
             Line::from("The following options are recommended:"),
             Line::from(""),
         ];
-        
+
         for symbol in implied {
             lines.push(Line::from(format!("  • {}", symbol)));
         }
-        
+
         lines.push(Line::from(""));
         lines.push(Line::from("Enable them? [Y/n/ESC]"));
-        
-        let dialog = Paragraph::new(lines)
-            .block(Block::default()
+
+        let dialog = Paragraph::new(lines).block(
+            Block::default()
                 .borders(Borders::ALL)
                 .title(" Suggestion ")
-                .style(self.theme.get_info_style()));
-        
+                .style(self.theme.get_info_style()),
+        );
+
         frame.render_widget(dialog, area);
     }
 }
@@ -1,12 +1,11 @@
 pub mod app;
-pub mod dependency_resolver;
 pub mod events;
 pub mod rendering;
 pub mod state;
 pub mod utils;
 
+pub use crate::kconfig::dependency_resolver::{DependencyError, DependencyResolver};
 pub use app::MenuConfigApp;
-pub use dependency_resolver::{DependencyResolver, DependencyError};
 pub use events::{EventHandler, EventResult};
 pub use rendering::Theme;
 pub use state::{ConfigState, MenuItem, NavigationState};
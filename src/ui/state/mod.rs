@@ -1,5 +1,5 @@
-use crate::kconfig::ast::{Entry, Menu, Config, MenuConfig, Choice, Comment};
-use crate::kconfig::{SymbolType, Expr};
+use crate::kconfig::ast::{Choice, Comment, Config, Entry, Menu, MenuConfig, Span};
+use crate::kconfig::{Expr, SymbolType};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -15,6 +15,8 @@ pub struct MenuItem {
     pub help_text: Option<String>,
     pub depends_on: Option<Expr>,
     pub selects: Vec<String>,
+    /// Where this entry is written in the Kconfig sources
+    pub defined_at: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -49,36 +51,56 @@ impl MenuItem {
             kind: MenuItemKind::Config {
                 symbol_type: config.symbol_type.clone(),
             },
-            label: config.properties.prompt.clone().unwrap_or_else(|| config.name.clone()),
+            label: config
+                .properties
+                .prompt
+                .clone()
+                .unwrap_or_else(|| config.name.clone()),
             value: None,
             is_visible: true,
             is_enabled: true,
             has_children: false,
             depth,
             help_text: config.properties.help.clone(),
-            depends_on: config.properties.depends.clone(),
-            selects: config.properties.select.iter().map(|(s, _)| s.clone()).collect(),
+            depends_on: config.properties.depends.as_deref().cloned(),
+            selects: config
+                .properties
+                .select
+                .iter()
+                .map(|(s, _, _)| s.clone())
+                .collect(),
+            defined_at: config.span.clone(),
         }
     }
-    
+
     pub fn from_menuconfig(config: &MenuConfig, depth: usize) -> Self {
         Self {
             id: config.name.clone(),
             kind: MenuItemKind::MenuConfig {
                 symbol_type: config.symbol_type.clone(),
             },
-            label: config.properties.prompt.clone().unwrap_or_else(|| config.name.clone()),
+            label: config
+                .properties
+                .prompt
+                .clone()
+                .unwrap_or_else(|| config.name.clone()),
             value: None,
             is_visible: true,
             is_enabled: true,
             has_children: true,
             depth,
             help_text: config.properties.help.clone(),
-            depends_on: config.properties.depends.clone(),
-            selects: config.properties.select.iter().map(|(s, _)| s.clone()).collect(),
+            depends_on: config.properties.depends.as_deref().cloned(),
+            selects: config
+                .properties
+                .select
+                .iter()
+                .map(|(s, _, _)| s.clone())
+                .collect(),
+            defined_at: config.span.clone(),
         }
     }
-    
+
     pub fn from_menu(menu: &Menu, depth: usize) -> Self {
         Self {
             id: format!("menu_{}", menu.title),
@@ -92,30 +114,35 @@ impl MenuItem {
             has_children: true,
             depth,
             help_text: None,
-            depends_on: menu.depends.clone(),
+            depends_on: menu.depends.as_deref().cloned(),
             selects: Vec::new(),
+            defined_at: menu.span.clone(),
         }
     }
-    
+
     pub fn from_choice(choice: &Choice, depth: usize) -> Self {
-        let options: Vec<String> = choice.options.iter().map(|c| c.name.clone()).collect();
+        let options: Vec<String> = choice.options().iter().map(|c| c.name.clone()).collect();
         Self {
             id: choice.name.clone().unwrap_or_else(|| "choice".to_string()),
             kind: MenuItemKind::Choice {
                 options: options.clone(),
             },
-            label: choice.prompt.clone().unwrap_or_else(|| "Choice".to_string()),
+            label: choice
+                .prompt
+                .clone()
+                .unwrap_or_else(|| "Choice".to_string()),
             value: None,
             is_visible: true,
             is_enabled: true,
             has_children: !options.is_empty(),
             depth,
-            help_text: None,
-            depends_on: choice.depends.clone(),
+            help_text: choice.help.clone(),
+            depends_on: choice.depends.as_deref().cloned(),
             selects: Vec::new(),
+            defined_at: choice.span.clone(),
         }
     }
-    
+
     pub fn from_comment(comment: &Comment, depth: usize) -> Self {
         Self {
             id: format!("comment_{}", comment.text),
@@ -129,8 +156,9 @@ impl MenuItem {
             has_children: false,
             depth,
             help_text: None,
-            depends_on: comment.depends.clone(),
+            depends_on: comment.depends.as_deref().cloned(),
             selects: Vec::new(),
+            defined_at: comment.span.clone(),
         }
     }
 }
@@ -173,16 +201,16 @@ impl ConfigState {
             original_values: HashMap::new(),
         }
     }
-    
+
     pub fn build_from_entries(entries: &[Entry]) -> Self {
         let mut state = Self::new();
         state.process_entries(entries, 0, "root");
         state
     }
-    
+
     fn process_entries(&mut self, entries: &[Entry], depth: usize, parent_id: &str) {
         let mut items = Vec::new();
-        
+
         for entry in entries {
             match entry {
                 Entry::Config(config) => {
@@ -192,7 +220,7 @@ impl ConfigState {
                 Entry::MenuConfig(menuconfig) => {
                     let item = MenuItem::from_menuconfig(menuconfig, depth);
                     items.push(item.clone());
-                    
+
                     // MenuConfig can have sub-items (not in this simple version)
                     // In a full implementation, we'd recursively process
                 }
@@ -200,19 +228,16 @@ impl ConfigState {
                     let item = MenuItem::from_menu(menu, depth);
                     let menu_id = item.id.clone();
                     items.push(item);
-                    
+
                     // Process menu children
                     self.process_entries(&menu.entries, depth + 1, &menu_id);
                 }
                 Entry::Choice(choice) => {
                     let item = MenuItem::from_choice(choice, depth);
                     items.push(item);
-                    
-                    // Add choice options as children
-                    for option in &choice.options {
-                        let opt_item = MenuItem::from_config(option, depth + 1);
-                        items.push(opt_item);
-                    }
+
+                    // Add choice options and comments as children
+                    Self::choice_items(&choice.entries, depth + 1, &mut items);
                 }
                 Entry::Comment(comment) => {
                     let item = MenuItem::from_comment(comment, depth);
@@ -230,19 +255,39 @@ impl ConfigState {
                 }
             }
         }
-        
+
         self.menu_tree.insert(parent_id.to_string(), items.clone());
         self.all_items.extend(items);
     }
-    
+
+    fn choice_items(entries: &[Entry], depth: usize, items: &mut Vec<MenuItem>) {
+        for entry in entries {
+            match entry {
+                Entry::Config(config) => items.push(MenuItem::from_config(config, depth)),
+                Entry::Comment(comment) => items.push(MenuItem::from_comment(comment, depth)),
+                Entry::If(if_entry) => Self::choice_items(&if_entry.entries, depth, items),
+                _ => {}
+            }
+        }
+    }
+
     pub fn get_items_for_path(&self, path: &[String]) -> Vec<MenuItem> {
         let key = if path.is_empty() {
             "root".to_string()
         } else {
             path.last().unwrap().clone()
         };
-        
-        self.menu_tree.get(&key).cloned().unwrap_or_default()
+
+        self.menu_tree
+            .get(&key)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|item| item.is_visible)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 }
 
@@ -0,0 +1,166 @@
+use rust_kbuild::config::{AllConfig, Probability, RandomConfig};
+use rust_kbuild::kconfig::SymbolTable;
+
+const KCONFIG: &str = "tests/fixtures/allconfig/Kconfig";
+const SRCTREE: &str = "tests/fixtures/allconfig";
+
+const SYMBOLS: &[&str] = &[
+    "EXPERT",
+    "NET",
+    "NET_DRIVER",
+    "CRYPTO",
+    "CRYPTO_AES",
+    "BUFFER_SIZE",
+    "ALLOC_SLAB",
+    "ALLOC_SLUB",
+    "ALLOC_SLOB",
+];
+
+fn snapshot(symbols: &SymbolTable) -> Vec<Option<String>> {
+    SYMBOLS.iter().map(|name| symbols.get_value(name)).collect()
+}
+
+#[test]
+fn test_allnoconfig() {
+    let symbols = AllConfig::new(KCONFIG, SRCTREE).allnoconfig().unwrap();
+
+    assert_eq!(symbols.get_value("EXPERT"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("NET"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("NET_DRIVER"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("CRYPTO"), Some("n".to_string()));
+
+    // Choices still pick their default, non-bool options keep theirs
+    assert_eq!(symbols.get_value("ALLOC_SLUB"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("BUFFER_SIZE"), Some("4096".to_string()));
+}
+
+#[test]
+fn test_allyesconfig() {
+    let symbols = AllConfig::new(KCONFIG, SRCTREE).allyesconfig().unwrap();
+
+    for name in ["EXPERT", "NET", "NET_DRIVER", "CRYPTO", "CRYPTO_AES"] {
+        assert_eq!(symbols.get_value(name), Some("y".to_string()), "{}", name);
+    }
+
+    let enabled = ["ALLOC_SLAB", "ALLOC_SLUB", "ALLOC_SLOB"]
+        .iter()
+        .filter(|name| symbols.get_value(name).as_deref() == Some("y"))
+        .count();
+    assert_eq!(enabled, 1);
+}
+
+#[test]
+fn test_allmodconfig() {
+    let symbols = AllConfig::new(KCONFIG, SRCTREE).allmodconfig().unwrap();
+
+    assert_eq!(symbols.get_value("NET"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("NET_DRIVER"), Some("m".to_string()));
+    assert_eq!(symbols.get_value("CRYPTO"), Some("m".to_string()));
+    assert_eq!(symbols.get_value("CRYPTO_AES"), Some("m".to_string()));
+}
+
+#[test]
+fn test_alldefconfig() {
+    let symbols = AllConfig::new(KCONFIG, SRCTREE).alldefconfig().unwrap();
+
+    assert_eq!(symbols.get_value("EXPERT"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("NET"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("CRYPTO"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("ALLOC_SLUB"), Some("y".to_string()));
+}
+
+#[test]
+fn test_randconfig_reproducible_from_seed() {
+    let allconfig = AllConfig::new(KCONFIG, SRCTREE);
+
+    for seed in 0..32 {
+        let config = RandomConfig::new(seed, Probability::default());
+        let first = allconfig.randconfig(config).unwrap();
+        let second = allconfig.randconfig(config).unwrap();
+        assert_eq!(snapshot(&first), snapshot(&second), "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_randconfig_respects_constraints() {
+    let allconfig = AllConfig::new(KCONFIG, SRCTREE);
+
+    for seed in 0..64 {
+        let symbols = allconfig
+            .randconfig(RandomConfig::new(seed, Probability::default()))
+            .unwrap();
+
+        if symbols.get_value("NET").as_deref() == Some("n") {
+            assert_eq!(
+                symbols.get_value("NET_DRIVER"),
+                Some("n".to_string()),
+                "seed {}",
+                seed
+            );
+        }
+        if symbols.get_value("CRYPTO").as_deref() == Some("n") {
+            assert_eq!(
+                symbols.get_value("CRYPTO_AES"),
+                Some("n".to_string()),
+                "seed {}",
+                seed
+            );
+        }
+
+        let enabled = ["ALLOC_SLAB", "ALLOC_SLUB", "ALLOC_SLOB"]
+            .iter()
+            .filter(|name| symbols.get_value(name).as_deref() == Some("y"))
+            .count();
+        assert_eq!(enabled, 1, "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_randconfig_probability_extremes() {
+    let allconfig = AllConfig::new(KCONFIG, SRCTREE);
+
+    let none = allconfig
+        .randconfig(RandomConfig::new(7, "0".parse().unwrap()))
+        .unwrap();
+    assert_eq!(none.get_value("NET"), Some("n".to_string()));
+    assert_eq!(none.get_value("CRYPTO"), Some("n".to_string()));
+
+    let all = allconfig
+        .randconfig(RandomConfig::new(7, "100:100:0".parse().unwrap()))
+        .unwrap();
+    assert_eq!(all.get_value("NET"), Some("y".to_string()));
+    assert_eq!(all.get_value("CRYPTO"), Some("y".to_string()));
+}
+
+#[test]
+fn test_probability_parsing() {
+    assert_eq!(
+        "20".parse::<Probability>().unwrap(),
+        Probability {
+            bool_y: 20,
+            tristate_y: 10,
+            tristate_m: 10
+        }
+    );
+    assert_eq!(
+        "30:20".parse::<Probability>().unwrap(),
+        Probability {
+            bool_y: 50,
+            tristate_y: 30,
+            tristate_m: 20
+        }
+    );
+    assert_eq!(
+        "10:20:30".parse::<Probability>().unwrap(),
+        Probability {
+            bool_y: 10,
+            tristate_y: 20,
+            tristate_m: 30
+        }
+    );
+
+    assert!("101".parse::<Probability>().is_err());
+    assert!("60:60".parse::<Probability>().is_err());
+    assert!("1:2:3:4".parse::<Probability>().is_err());
+    assert!("abc".parse::<Probability>().is_err());
+}
@@ -0,0 +1,128 @@
+use rust_kbuild::build::BuildScript;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_directives_for_configuration() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+    fs::write(
+        &config,
+        "CONFIG_DEBUG=y\nCONFIG_LOG_LEVEL=5\n# CONFIG_E1000 is not set\n",
+    )
+    .unwrap();
+
+    let build = BuildScript::new(
+        "tests/fixtures/generator/Kconfig",
+        "tests/fixtures/generator",
+    );
+    let directives = build.directives(&config).unwrap();
+
+    assert_eq!(
+        directives[0],
+        format!("cargo:rerun-if-changed={}", config.display())
+    );
+    assert!(
+        directives.contains(&"cargo:rerun-if-changed=tests/fixtures/generator/Kconfig".to_string())
+    );
+
+    // Every symbol is declared, valued ones with any value
+    assert!(directives.contains(&"cargo:rustc-check-cfg=cfg(E1000)".to_string()));
+    assert!(directives.contains(&"cargo:rustc-check-cfg=cfg(LOG_LEVEL, values(any()))".to_string()));
+    assert!(directives.contains(&"cargo:rustc-check-cfg=cfg(FOO_2)".to_string()));
+
+    assert!(directives.contains(&"cargo:rustc-cfg=DEBUG".to_string()));
+    assert!(!directives.contains(&"cargo:rustc-cfg=E1000".to_string()));
+    assert!(directives.contains(&"cargo:rustc-cfg=LOG_LEVEL=\"5\"".to_string()));
+    assert!(directives.contains(&"cargo:rustc-cfg=PHYS_BASE=\"0x80000000\"".to_string()));
+    assert!(directives.contains(&r#"cargo:rustc-cfg=BANNER="say \"hi\" \\o/""#.to_string()));
+
+    // Declarations come before the cfgs that use them
+    let check = directives
+        .iter()
+        .position(|d| d.ends_with("check-cfg=cfg(DEBUG)"))
+        .unwrap();
+    let cfg = directives
+        .iter()
+        .position(|d| d == "cargo:rustc-cfg=DEBUG")
+        .unwrap();
+    assert!(check < cfg);
+}
+
+#[test]
+fn test_directives_rerun_on_sourced_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+
+    let build = BuildScript::new("tests/fixtures/source/Kconfig", "tests/fixtures/source");
+    let directives = build.directives(&config).unwrap();
+
+    let reruns: Vec<&String> = directives
+        .iter()
+        .filter(|d| d.contains("rerun-if-changed"))
+        .collect();
+    assert_eq!(reruns.len(), 3, "{:?}", reruns);
+    assert!(reruns.iter().any(|d| d.ends_with("sub/Kconfig")));
+    // Without a .config, defaults apply
+    assert!(directives.contains(&"cargo:rustc-cfg=MAIN_CONFIG".to_string()));
+}
+
+#[test]
+fn test_directives_rerun_on_globbed_dirs_and_env() {
+    let temp_dir = TempDir::new().unwrap();
+    let plugin = temp_dir.path().join("plugins/alpha");
+    fs::create_dir_all(&plugin).unwrap();
+    fs::write(plugin.join("Kconfig"), "config ALPHA\n\tbool \"Alpha\"\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Kconfig"),
+        "config ARCH_NAME\n\tstring \"Architecture\"\n\tdefault \"$(RKCONF_BUILD_TEST_ARCH)\"\n\nsource \"plugins/*/Kconfig\"\n",
+    )
+    .unwrap();
+
+    let build = BuildScript::new(temp_dir.path().join("Kconfig"), temp_dir.path());
+    let directives = build.directives(temp_dir.path().join(".config")).unwrap();
+
+    // A new plugin directory changes the listing of `plugins`
+    let plugins = format!(
+        "cargo:rerun-if-changed={}",
+        temp_dir.path().join("plugins").display()
+    );
+    assert!(directives.contains(&plugins), "{:?}", directives);
+    assert!(directives.contains(&"cargo:rerun-if-env-changed=RKCONF_BUILD_TEST_ARCH".to_string()));
+}
+
+#[test]
+fn test_cfg_names_match_config_rs() {
+    use rust_kbuild::config::{ConfigEngine, ConfigGenerator};
+    use rust_kbuild::kconfig::Parser;
+
+    // LOG-LEVEL has no value while DEBUG is off, yet still takes the
+    // plain name, so LOG_LEVEL is LOG_LEVEL_2 in both files
+    let temp_dir = TempDir::new().unwrap();
+    let kconfig = temp_dir.path().join("Kconfig");
+    fs::write(
+        &kconfig,
+        "config DEBUG\n\tbool \"Debugging\"\n\nconfig LOG-LEVEL\n\tint \"Log level\"\n\tdepends on DEBUG\n\tdefault 3\n\nconfig LOG_LEVEL\n\tint \"Log level\"\n\tdefault 5\n",
+    )
+    .unwrap();
+
+    let ast = Parser::new(&kconfig, temp_dir.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let engine = ConfigEngine::from_entries(&ast.entries);
+    let config_rs = temp_dir.path().join("config.rs");
+    ConfigGenerator::generate_config_rs(&config_rs, engine.symbols(), &ast.entries).unwrap();
+    let config_rs = fs::read_to_string(&config_rs).unwrap();
+    assert!(
+        config_rs.contains("pub const LOG_LEVEL_2: i64 = 5;"),
+        "{}",
+        config_rs
+    );
+    assert!(!config_rs.contains("pub const LOG_LEVEL:"));
+
+    let build = BuildScript::new(&kconfig, temp_dir.path());
+    let directives = build.directives(temp_dir.path().join(".config")).unwrap();
+    assert!(directives.contains(&"cargo:rustc-check-cfg=cfg(LOG_LEVEL, values(any()))".to_string()));
+    assert!(directives.contains(&"cargo:rustc-cfg=LOG_LEVEL_2=\"5\"".to_string()));
+}
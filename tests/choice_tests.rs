@@ -0,0 +1,241 @@
+mod common;
+
+use common::load_fixture;
+use rust_kbuild::config::{ConfigGenerator, ConfigWriter, DefconfigLoader, OldConfigLoader};
+use rust_kbuild::kconfig::{Entry, Parser, SymbolType};
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/choice/Kconfig";
+const SRCTREE: &str = "tests/fixtures/choice";
+
+#[test]
+fn test_parse_named_and_optional_choices() {
+    let mut parser = Parser::new(KCONFIG, SRCTREE).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let choices: Vec<_> = ast
+        .entries
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Choice(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(choices[0].name.as_deref(), Some("BOOT_MODE"));
+    assert_eq!(choices[1].name.as_deref(), Some("CODECS"));
+    assert_eq!(choices[1].symbol_type, SymbolType::Tristate);
+    assert_eq!(choices[2].name, None);
+    assert!(choices[2].optional);
+}
+
+#[test]
+fn test_bool_choice_default_and_exclusivity() {
+    let mut engine = load_fixture("choice");
+
+    assert_eq!(engine.value("BOOT_FAST"), Some("y".to_string()));
+    assert_eq!(engine.value("BOOT_SAFE"), Some("n".to_string()));
+    // The named choice holds its mode and can be used in expressions
+    assert_eq!(engine.value("BOOT_MODE"), Some("y".to_string()));
+
+    engine.set_user_value("BOOT_SAFE", "y");
+    assert_eq!(engine.value("BOOT_SAFE"), Some("y".to_string()));
+    assert_eq!(engine.value("BOOT_FAST"), Some("n".to_string()));
+    assert!(engine
+        .resolver()
+        .can_enable("BOOT_LOG", engine.symbols())
+        .is_ok());
+
+    // The latest pick wins
+    engine.set_user_value("BOOT_FAST", "y");
+    assert_eq!(engine.value("BOOT_SAFE"), Some("n".to_string()));
+    assert_eq!(engine.value("BOOT_FAST"), Some("y".to_string()));
+}
+
+#[test]
+fn test_tristate_choice_modes() {
+    let mut engine = load_fixture("choice");
+
+    // `y` mode by default, with the first option selected
+    assert_eq!(engine.value("CODECS"), Some("y".to_string()));
+    assert_eq!(engine.value("CODEC_A"), Some("y".to_string()));
+
+    // In `m` mode several options can be modules
+    engine.set_user_value("CODEC_A", "m");
+    engine.set_user_value("CODEC_B", "m");
+    assert_eq!(engine.value("CODECS"), Some("m".to_string()));
+    assert_eq!(engine.value("CODEC_A"), Some("m".to_string()));
+    assert_eq!(engine.value("CODEC_B"), Some("m".to_string()));
+
+    // Back to `y` mode: only one option
+    engine.set_user_value("CODEC_B", "y");
+    assert_eq!(engine.value("CODECS"), Some("y".to_string()));
+    assert_eq!(engine.value("CODEC_A"), Some("n".to_string()));
+    assert_eq!(engine.value("CODEC_B"), Some("y".to_string()));
+
+    // Without modules there is no `m` mode
+    engine.set_user_value("CODEC_A", "m");
+    engine.set_user_value("MODULES", "n");
+    assert_eq!(engine.value("CODECS"), Some("y".to_string()));
+    assert_eq!(engine.value("CODEC_A"), Some("y".to_string()));
+    assert_eq!(engine.value("CODEC_B"), Some("n".to_string()));
+}
+
+#[test]
+fn test_optional_choice() {
+    let mut engine = load_fixture("choice");
+
+    assert_eq!(engine.value("TRACER_SIMPLE"), Some("n".to_string()));
+    assert_eq!(engine.value("TRACER_FULL"), Some("n".to_string()));
+
+    engine.set_user_value("TRACER_FULL", "y");
+    assert_eq!(engine.value("TRACER_FULL"), Some("y".to_string()));
+    assert_eq!(engine.value("TRACER_SIMPLE"), Some("n".to_string()));
+}
+
+#[test]
+fn test_choice_with_help_comment_and_if() {
+    let mut parser = Parser::new(KCONFIG, SRCTREE).unwrap();
+    let ast = parser.parse().unwrap();
+    let console = ast
+        .entries
+        .iter()
+        .find_map(|e| match e {
+            Entry::Choice(c) if c.prompt.as_deref() == Some("Console") => Some(c),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        console.help.as_deref(),
+        Some("Where the kernel prints its messages.\n")
+    );
+    assert!(matches!(console.entries[0], Entry::Comment(_)));
+    let options: Vec<&str> = console.options().iter().map(|o| o.name.as_str()).collect();
+    assert_eq!(options, ["CONSOLE_VGA", "CONSOLE_SERIAL"]);
+
+    // The option inside `if SERIAL` depends on it
+    let mut engine = load_fixture("choice");
+    engine.set_user_value("CONSOLE_SERIAL", "y");
+    assert_eq!(engine.value("CONSOLE_SERIAL"), Some("n".to_string()));
+    assert_eq!(engine.value("CONSOLE_VGA"), Some("y".to_string()));
+
+    engine.set_user_value("SERIAL", "y");
+    assert_eq!(engine.value("CONSOLE_SERIAL"), Some("y".to_string()));
+    assert_eq!(engine.value("CONSOLE_VGA"), Some("n".to_string()));
+}
+
+#[test]
+fn test_named_choice_in_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+    fs::write(
+        &config_path,
+        "CONFIG_CODECS=m\nCONFIG_CODEC_B=m\nCONFIG_BOOT_SAFE=y\nCONFIG_BOOT_FAST=y\n",
+    )
+    .unwrap();
+
+    let loader = OldConfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, _) = loader.load_and_merge(&config_path).unwrap();
+
+    assert_eq!(symbols.get_value("CODECS"), Some("m".to_string()));
+    assert_eq!(symbols.get_value("CODEC_A"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("CODEC_B"), Some("m".to_string()));
+
+    // Two `y` options in the .config: only one survives
+    let boot: Vec<_> = ["BOOT_SAFE", "BOOT_FAST"]
+        .iter()
+        .filter(|name| symbols.get_value(name).as_deref() == Some("y"))
+        .collect();
+    assert_eq!(boot.len(), 1);
+
+    ConfigWriter::write(&config_path, &symbols).unwrap();
+    let written = fs::read_to_string(&config_path).unwrap();
+    assert!(written.contains("CODECS=m"));
+    assert_eq!(
+        written.matches("BOOT_SAFE=y").count() + written.matches("BOOT_FAST=y").count(),
+        1
+    );
+}
+
+#[test]
+fn test_engine_keeps_choice_exclusive() {
+    let mut engine = load_fixture("choice");
+
+    engine.set_user_value("CODEC_A", "m");
+    engine.set_user_value("CODEC_B", "m");
+    assert_eq!(engine.value("CODEC_A"), Some("m".to_string()));
+
+    engine.set_user_value("CODEC_B", "y");
+    assert_eq!(engine.value("CODEC_A"), Some("n".to_string()));
+
+    engine.set_user_value("CODEC_A", "y");
+    assert_eq!(engine.value("CODEC_B"), Some("n".to_string()));
+
+    let temp_dir = TempDir::new().unwrap();
+    let header = temp_dir.path().join("autoconf.h");
+    ConfigGenerator::generate_autoconf_h(&header, engine.symbols()).unwrap();
+    let header = fs::read_to_string(&header).unwrap();
+    assert!(header.contains("#define CODEC_A 1"));
+    assert!(!header.contains("#define CODEC_B"));
+}
+
+#[test]
+fn test_writers_emit_one_selected_option() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+    fs::write(
+        &config_path,
+        "CONFIG_BOOT_SAFE=y\nCONFIG_BOOT_FAST=y\nCONFIG_CODEC_A=y\nCONFIG_CODEC_B=y\n",
+    )
+    .unwrap();
+
+    let mut engine = load_fixture("choice");
+    engine.read_user_values(&config_path).unwrap();
+
+    let auto_conf = temp_dir.path().join("auto.conf");
+    let header = temp_dir.path().join("autoconf.h");
+    ConfigWriter::write(&config_path, engine.symbols()).unwrap();
+    ConfigGenerator::generate_auto_conf(&auto_conf, engine.symbols()).unwrap();
+    ConfigGenerator::generate_autoconf_h(&header, engine.symbols()).unwrap();
+
+    let selected = |text: &str, options: [&str; 2], suffix: &str| {
+        options
+            .iter()
+            .filter(|option| text.contains(&format!("{}{}", option, suffix)))
+            .count()
+    };
+    for path in [&config_path, &auto_conf] {
+        let text = fs::read_to_string(path).unwrap();
+        assert_eq!(
+            selected(&text, ["BOOT_SAFE", "BOOT_FAST"], "=y"),
+            1,
+            "{}",
+            text
+        );
+        assert_eq!(selected(&text, ["CODEC_A", "CODEC_B"], "=y"), 1, "{}", text);
+    }
+    let header = fs::read_to_string(&header).unwrap();
+    assert_eq!(selected(&header, ["BOOT_SAFE", "BOOT_FAST"], " 1"), 1);
+    assert_eq!(selected(&header, ["CODEC_A", "CODEC_B"], " 1"), 1);
+}
+
+#[test]
+fn test_savedefconfig_keeps_module_mode() {
+    let temp_dir = TempDir::new().unwrap();
+    let defconfig = temp_dir.path().join("defconfig");
+    fs::write(&defconfig, "CONFIG_CODEC_A=m\nCONFIG_CODEC_B=m\n").unwrap();
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, warnings) = loader.load(&defconfig).unwrap();
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+
+    let config = temp_dir.path().join(".config");
+    ConfigWriter::write(&config, &symbols).unwrap();
+    let minimal = loader.savedefconfig(&config).unwrap();
+
+    assert!(minimal.contains(&("CODEC_A".to_string(), "m".to_string())));
+    assert!(minimal.contains(&("CODEC_B".to_string(), "m".to_string())));
+    assert!(!minimal.iter().any(|(name, _)| name == "CODECS"));
+}
@@ -0,0 +1,8 @@
+use rust_kbuild::config::ConfigEngine;
+
+/// The engine for `tests/fixtures/<fixture>/Kconfig`, with every symbol at
+/// its default
+pub fn load_fixture(fixture: &str) -> ConfigEngine {
+    let srctree = format!("tests/fixtures/{}", fixture);
+    ConfigEngine::load(format!("{}/Kconfig", srctree), &srctree).unwrap()
+}
@@ -1,4 +1,4 @@
-use rust_kbuild::config::{ConfigReader, ConfigWriter};
+use rust_kbuild::config::{ConfigEngine, ConfigGenerator, ConfigReader, ConfigWriter};
 use rust_kbuild::kconfig::{SymbolTable, SymbolType};
 use std::fs;
 use tempfile::TempDir;
@@ -18,9 +18,13 @@ fn test_config_reader_backward_compat() {
     // Test reading config with CONFIG_ prefix (backward compatibility)
     let temp_dir = TempDir::new().unwrap();
     let config_path = temp_dir.path().join("test.config");
-    
-    fs::write(&config_path, "CONFIG_TEST1=y\nCONFIG_TEST2=\"value\"\n# CONFIG_TEST3 is not set\n").unwrap();
-    
+
+    fs::write(
+        &config_path,
+        "CONFIG_TEST1=y\nCONFIG_TEST2=\"value\"\n# CONFIG_TEST3 is not set\n",
+    )
+    .unwrap();
+
     let config = ConfigReader::read(&config_path).unwrap();
     assert_eq!(config.get("TEST1"), Some(&"y".to_string()));
     assert_eq!(config.get("TEST2"), Some(&"value".to_string()));
@@ -66,3 +70,84 @@ fn test_config_roundtrip() {
     assert_eq!(config.get("A"), Some(&"y".to_string()));
     assert_eq!(config.get("B"), Some(&"n".to_string()));
 }
+
+#[test]
+fn test_config_writer_follows_kconfig_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+
+    let engine =
+        ConfigEngine::load("tests/fixtures/layout/Kconfig", "tests/fixtures/layout").unwrap();
+    ConfigWriter::write(&config_path, engine.symbols()).unwrap();
+
+    // Hidden symbols at n, and the menu holding only one, are left out
+    let expected = r#"#
+# Automatically generated file; DO NOT EDIT.
+# Rust Kbuild Configuration
+#
+NET=y
+ARCH_NAME="x86"
+
+#
+# Drivers
+#
+E1000=y
+
+#
+# USB
+#
+# USB is not set
+# end of USB
+# end of Drivers
+
+LOG_LEVEL=4
+"#;
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), expected);
+}
+
+#[test]
+fn test_config_output_is_deterministic() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let outputs: Vec<_> = (0..2)
+        .map(|run| {
+            // Loaded afresh each run, so no HashMap order is shared
+            let engine =
+                ConfigEngine::load("tests/fixtures/layout/Kconfig", "tests/fixtures/layout")
+                    .unwrap();
+            let symbols = engine.into_symbols();
+            let config = temp_dir.path().join(format!("config.{}", run));
+            let auto_conf = temp_dir.path().join(format!("auto.conf.{}", run));
+            let autoconf_h = temp_dir.path().join(format!("autoconf.h.{}", run));
+            ConfigWriter::write(&config, &symbols).unwrap();
+            ConfigGenerator::generate_auto_conf(&auto_conf, &symbols).unwrap();
+            ConfigGenerator::generate_autoconf_h(&autoconf_h, &symbols).unwrap();
+            [config, auto_conf, autoconf_h].map(|path| fs::read_to_string(path).unwrap())
+        })
+        .collect();
+    assert_eq!(outputs[0], outputs[1]);
+    assert_eq!(
+        outputs[0][1],
+        "#\n# Automatically generated file; DO NOT EDIT.\n#\nNET=y\nARCH_NAME=x86\nE1000=y\nLOG_LEVEL=4\n"
+    );
+}
+
+#[test]
+fn test_config_writer_sorts_undeclared_symbols() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+
+    let mut symbols = SymbolTable::new();
+    for name in ["ZETA", "ALPHA", "MID"] {
+        symbols.add_symbol(name.to_string(), SymbolType::Bool);
+        symbols.set_value(name, "y".to_string());
+    }
+    ConfigWriter::write(&config_path, &symbols).unwrap();
+
+    let content = fs::read_to_string(&config_path).unwrap();
+    assert!(
+        content.ends_with("ALPHA=y\nMID=y\nZETA=y\n"),
+        "Unexpected order:\n{}",
+        content
+    );
+}
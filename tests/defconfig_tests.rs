@@ -0,0 +1,215 @@
+use rust_kbuild::config::{ConfigWriter, DefconfigLoader};
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/defconfig/Kconfig";
+const SRCTREE: &str = "tests/fixtures/defconfig";
+
+#[test]
+fn test_defconfig_applies_values_and_defaults() {
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, warnings) = loader
+        .load("tests/fixtures/defconfig/board_defconfig")
+        .unwrap();
+
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+
+    // Values from the defconfig, even when listed before their dependency
+    assert_eq!(symbols.get_value("NET"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("NET_TLS"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("DEBUG"), Some("n".to_string()));
+
+    // Selected by NET_TLS
+    assert_eq!(symbols.get_value("CRYPTO"), Some("y".to_string()));
+
+    // Everything else from defaults
+    assert_eq!(symbols.get_value("NET_PORT"), Some("8080".to_string()));
+    assert_eq!(symbols.get_value("LOG_LEVEL"), Some("info".to_string()));
+}
+
+#[test]
+fn test_defconfig_choice_exclusive() {
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, _) = loader
+        .load("tests/fixtures/defconfig/board_defconfig")
+        .unwrap();
+
+    assert_eq!(symbols.get_value("SCHED_RT"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("SCHED_FAIR"), Some("n".to_string()));
+}
+
+#[test]
+fn test_defconfig_choice_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let defconfig = temp_dir.path().join("defconfig");
+    fs::write(&defconfig, "").unwrap();
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, warnings) = loader.load(&defconfig).unwrap();
+
+    assert!(warnings.is_empty());
+    assert_eq!(symbols.get_value("SCHED_FAIR"), Some("y".to_string()));
+    assert_eq!(symbols.get_value("SCHED_RT"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("NET"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("DEBUG"), Some("y".to_string()));
+}
+
+#[test]
+fn test_defconfig_unmet_dependency_warning() {
+    let temp_dir = TempDir::new().unwrap();
+    let defconfig = temp_dir.path().join("defconfig");
+    fs::write(&defconfig, "NET_TLS=y\nUNKNOWN_OPTION=y\n").unwrap();
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, warnings) = loader.load(&defconfig).unwrap();
+
+    // NET_TLS depends on NET, which stays at its default of n
+    assert_eq!(symbols.get_value("NET_TLS"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("CRYPTO"), Some("n".to_string()));
+
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings
+        .iter()
+        .any(|w| w.symbol == "NET_TLS" && w.reason.contains("NET")));
+    assert!(warnings.iter().any(|w| w.symbol == "UNKNOWN_OPTION"));
+}
+
+fn write_full_config(temp_dir: &TempDir) -> std::path::PathBuf {
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, _) = loader
+        .load("tests/fixtures/defconfig/board_defconfig")
+        .unwrap();
+
+    let config_path = temp_dir.path().join(".config");
+    ConfigWriter::write(&config_path, &symbols).unwrap();
+    config_path
+}
+
+#[test]
+fn test_savedefconfig_minimal_in_declaration_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_full_config(&temp_dir);
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let assignments = loader.savedefconfig(&config_path).unwrap();
+
+    // CRYPTO is selected and NET_PORT/LOG_LEVEL keep their defaults
+    let expected: Vec<(String, String)> = [
+        ("NET", "y"),
+        ("NET_TLS", "y"),
+        ("DEBUG", "n"),
+        ("SCHED_RT", "y"),
+    ]
+    .iter()
+    .map(|(n, v)| (n.to_string(), v.to_string()))
+    .collect();
+    assert_eq!(assignments, expected);
+}
+
+#[test]
+fn test_savedefconfig_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_full_config(&temp_dir);
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let engine = loader.load_config(&config_path).unwrap();
+    let assignments = DefconfigLoader::minimize(&engine);
+
+    let defconfig_path = temp_dir.path().join("defconfig");
+    ConfigWriter::write_defconfig(&defconfig_path, &assignments, engine.symbols()).unwrap();
+
+    let content = fs::read_to_string(&defconfig_path).unwrap();
+    assert_eq!(
+        content,
+        "NET=y\nNET_TLS=y\n# DEBUG is not set\nSCHED_RT=y\n"
+    );
+
+    let (original, _) = loader.load(&config_path).unwrap();
+    let (reproduced, warnings) = loader.load(&defconfig_path).unwrap();
+    assert!(warnings.is_empty());
+    for (name, symbol) in original.all_symbols() {
+        assert_eq!(
+            reproduced.get_value(name),
+            symbol.value,
+            "Mismatch for {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_savedefconfig_all_defaults_is_empty() {
+    let temp_dir = TempDir::new().unwrap();
+    let empty = temp_dir.path().join("empty");
+    fs::write(&empty, "").unwrap();
+
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, _) = loader.load(&empty).unwrap();
+    let config_path = temp_dir.path().join(".config");
+    ConfigWriter::write(&config_path, &symbols).unwrap();
+
+    assert!(loader.savedefconfig(&config_path).unwrap().is_empty());
+}
+
+#[test]
+fn test_savedefconfig_roundtrip_typed_values() {
+    let temp_dir = TempDir::new().unwrap();
+    let kconfig = temp_dir.path().join("Kconfig");
+    fs::write(
+        &kconfig,
+        r#"config NAME
+	string "Name"
+	default "n"
+
+config MODE
+	string "Mode"
+	default "auto"
+
+config COUNT
+	int "Count"
+	default 4
+
+config BASE
+	hex "Base address"
+	default 0x1000
+"#,
+    )
+    .unwrap();
+    let input = temp_dir.path().join("input_defconfig");
+    fs::write(&input, "MODE=\"y\"\nCOUNT=10\nBASE=0x2000\n").unwrap();
+
+    let loader = DefconfigLoader::new(&kconfig, temp_dir.path());
+    let (symbols, warnings) = loader.load(&input).unwrap();
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+
+    // Strings are quoted whatever their value; numbers are bare
+    let config_path = temp_dir.path().join(".config");
+    ConfigWriter::write(&config_path, &symbols).unwrap();
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(
+        config.contains("NAME=\"n\"\nMODE=\"y\"\nCOUNT=10\nBASE=0x2000\n"),
+        "{}",
+        config
+    );
+
+    let engine = loader.load_config(&config_path).unwrap();
+    let assignments = DefconfigLoader::minimize(&engine);
+    let defconfig_path = temp_dir.path().join("defconfig");
+    ConfigWriter::write_defconfig(&defconfig_path, &assignments, engine.symbols()).unwrap();
+    assert_eq!(
+        fs::read_to_string(&defconfig_path).unwrap(),
+        "MODE=\"y\"\nCOUNT=10\nBASE=0x2000\n"
+    );
+
+    let (reproduced, warnings) = loader.load(&defconfig_path).unwrap();
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+    for (name, symbol) in symbols.all_symbols() {
+        assert_eq!(
+            reproduced.get_value(name),
+            symbol.value,
+            "Mismatch for {}",
+            name
+        );
+    }
+    assert_eq!(reproduced.get_value("NAME"), Some("n".to_string()));
+}
@@ -1,18 +1,23 @@
-use rust_kbuild::kconfig::{Parser, SymbolTable, SymbolType};
-use rust_kbuild::ui::dependency_resolver::DependencyResolver;
+use rust_kbuild::config::ConfigEngine;
+use rust_kbuild::kconfig::dependency_resolver::{DependencyError, DependencyResolver};
+use rust_kbuild::kconfig::{
+    recursive_dependencies, DiagnosticCode, Parser, SymbolTable, SymbolType,
+};
+use std::fs;
 use std::path::PathBuf;
+use tempfile::TempDir;
 
 #[test]
 fn test_dependency_resolver_initialization() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     // Resolver should be initialized without errors
 }
 
@@ -20,169 +25,468 @@ fn test_dependency_resolver_initialization() {
 fn test_depends_on_blocks_enable() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("BASE_LIB".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("FEATURE_A".to_string(), SymbolType::Bool);
-    
+
     // BASE_LIB is disabled by default
     symbol_table.set_value("BASE_LIB", "n".to_string());
-    
+
     // Try to enable FEATURE_A which depends on BASE_LIB
     let result = resolver.can_enable("FEATURE_A", &symbol_table);
-    assert!(result.is_err(), "Should not be able to enable FEATURE_A when BASE_LIB is disabled");
-    
+    assert!(
+        result.is_err(),
+        "Should not be able to enable FEATURE_A when BASE_LIB is disabled"
+    );
+
     // Enable BASE_LIB first
     symbol_table.set_value("BASE_LIB", "y".to_string());
-    
+
     // Now FEATURE_A should be enableable
     let result = resolver.can_enable("FEATURE_A", &symbol_table);
-    assert!(result.is_ok(), "Should be able to enable FEATURE_A when BASE_LIB is enabled");
+    assert!(
+        result.is_ok(),
+        "Should be able to enable FEATURE_A when BASE_LIB is enabled"
+    );
 }
 
 #[test]
 fn test_select_cascade() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("BASE_LIB".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("FEATURE_A".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("HELPER_MODULE".to_string(), SymbolType::Bool);
-    
+
     // Enable BASE_LIB and FEATURE_A
     symbol_table.set_value("BASE_LIB", "y".to_string());
     symbol_table.set_value("FEATURE_A", "y".to_string());
-    
+
     // Apply selects
     let selected = resolver.apply_selects("FEATURE_A", &mut symbol_table);
-    
+
     // HELPER_MODULE should be automatically enabled
-    assert!(selected.contains(&"HELPER_MODULE".to_string()), 
-            "HELPER_MODULE should be in the selected list");
-    assert!(symbol_table.is_enabled("HELPER_MODULE"),
-            "HELPER_MODULE should be enabled");
+    assert!(
+        selected.contains(&"HELPER_MODULE".to_string()),
+        "HELPER_MODULE should be in the selected list"
+    );
+    assert!(
+        symbol_table.is_enabled("HELPER_MODULE"),
+        "HELPER_MODULE should be enabled"
+    );
 }
 
 #[test]
 fn test_reverse_select_blocks_disable() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("BASE_LIB".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("FEATURE_A".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("HELPER_MODULE".to_string(), SymbolType::Bool);
-    
+
     // Enable all
     symbol_table.set_value("BASE_LIB", "y".to_string());
     symbol_table.set_value("FEATURE_A", "y".to_string());
     symbol_table.set_value("HELPER_MODULE", "y".to_string());
-    
+
     // Try to disable HELPER_MODULE while FEATURE_A is enabled
     let result = resolver.can_disable("HELPER_MODULE", &symbol_table);
-    assert!(result.is_err(), "Should not be able to disable HELPER_MODULE when FEATURE_A selects it");
+    assert!(
+        result.is_err(),
+        "Should not be able to disable HELPER_MODULE when FEATURE_A selects it"
+    );
 }
 
 #[test]
 fn test_imply_suggests() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("FEATURE_B".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("OPTIONAL_FEATURE".to_string(), SymbolType::Bool);
-    
+
     // Enable FEATURE_B
     symbol_table.set_value("FEATURE_B", "y".to_string());
-    
+
     // Get implied symbols
     let implied = resolver.get_implied_symbols("FEATURE_B", &symbol_table);
-    
+
     // OPTIONAL_FEATURE should be implied
-    assert!(implied.contains(&"OPTIONAL_FEATURE".to_string()),
-            "OPTIONAL_FEATURE should be implied by FEATURE_B");
+    assert!(
+        implied.contains(&"OPTIONAL_FEATURE".to_string()),
+        "OPTIONAL_FEATURE should be implied by FEATURE_B"
+    );
 }
 
 #[test]
 fn test_disable_cascade_check() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("BASE_LIB".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("FEATURE_A".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("TRISTATE_OPTION".to_string(), SymbolType::Tristate);
-    
+
     // Enable all
     symbol_table.set_value("BASE_LIB", "y".to_string());
     symbol_table.set_value("FEATURE_A", "y".to_string());
     symbol_table.set_value("TRISTATE_OPTION", "m".to_string());
-    
+
     // Check what will be affected if BASE_LIB is disabled
     let affected = resolver.check_disable_cascade("BASE_LIB", &symbol_table);
-    
+
     // FEATURE_A and TRISTATE_OPTION should be affected
-    assert!(affected.contains(&"FEATURE_A".to_string()) || affected.contains(&"TRISTATE_OPTION".to_string()),
-            "Disabling BASE_LIB should affect dependent symbols");
+    assert!(
+        affected.contains(&"FEATURE_A".to_string())
+            || affected.contains(&"TRISTATE_OPTION".to_string()),
+        "Disabling BASE_LIB should affect dependent symbols"
+    );
+    // In declaration order, so the list is the same on every run
+    assert_eq!(affected, vec!["FEATURE_A", "TRISTATE_OPTION"]);
 }
 
 #[test]
 fn test_tristate_dependency() {
     let kconfig_path = PathBuf::from("tests/fixtures/dependency/Kconfig");
     let srctree = PathBuf::from("tests/fixtures/dependency");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     let mut resolver = DependencyResolver::new();
     resolver.build_from_entries(&ast.entries);
-    
+
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("BASE_LIB".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("TRISTATE_OPTION".to_string(), SymbolType::Tristate);
-    
+
     // BASE_LIB is disabled
     symbol_table.set_value("BASE_LIB", "n".to_string());
-    
+
     // Try to enable TRISTATE_OPTION
     let result = resolver.can_enable("TRISTATE_OPTION", &symbol_table);
-    assert!(result.is_err(), "Should not be able to enable TRISTATE_OPTION when BASE_LIB is disabled");
-    
+    assert!(
+        result.is_err(),
+        "Should not be able to enable TRISTATE_OPTION when BASE_LIB is disabled"
+    );
+
     // Enable BASE_LIB
     symbol_table.set_value("BASE_LIB", "y".to_string());
-    
+
     // Now should be able to enable
     let result = resolver.can_enable("TRISTATE_OPTION", &symbol_table);
-    assert!(result.is_ok(), "Should be able to enable TRISTATE_OPTION when BASE_LIB is enabled");
+    assert!(
+        result.is_ok(),
+        "Should be able to enable TRISTATE_OPTION when BASE_LIB is enabled"
+    );
+}
+
+#[test]
+fn test_defined_at_lists_every_definition() {
+    let kconfig_path = PathBuf::from("tests/fixtures/spans/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/spans");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let mut resolver = DependencyResolver::new();
+    resolver.build_from_entries(&ast.entries);
+
+    let eth: Vec<String> = resolver
+        .defined_at("ETH")
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(
+        eth,
+        vec![
+            "tests/fixtures/spans/Kconfig:14:1".to_string(),
+            "tests/fixtures/spans/Kconfig.extra:2:1".to_string(),
+        ]
+    );
+    assert_eq!(resolver.defined_at("SLIP").len(), 1);
+    assert!(resolver.defined_at("UNKNOWN").is_empty());
+}
+
+fn cycles_resolver() -> DependencyResolver {
+    let mut parser = Parser::new("tests/fixtures/cycles/Kconfig", "tests/fixtures/cycles").unwrap();
+    let ast = parser.parse().unwrap();
+
+    let mut resolver = DependencyResolver::new();
+    resolver.build_from_entries(&ast.entries);
+    resolver
+}
+
+#[test]
+fn test_recursive_dependencies_are_detected() {
+    let resolver = cycles_resolver();
+
+    let cycles: Vec<Vec<String>> = resolver
+        .dependency_cycles()
+        .iter()
+        .map(|cycle| {
+            cycle
+                .edges
+                .iter()
+                .map(|e| format!("{}: {}", e.span, e))
+                .collect()
+        })
+        .collect();
+    let file = "tests/fixtures/cycles/Kconfig";
+    assert_eq!(
+        cycles,
+        vec![
+            vec![
+                format!("{}:9:2: symbol LOOP_A is selected by LOOP_B", file),
+                format!("{}:5:2: symbol LOOP_B is selected by LOOP_A", file),
+            ],
+            vec![
+                format!("{}:25:13: symbol BAR depends on BAZ", file),
+                format!("{}:26:2: symbol BAZ is implied by BAR", file),
+            ],
+            vec![
+                format!("{}:34:2: symbol LOOP_A is selected by DRIVER", file),
+                format!("{}:33:13: symbol DRIVER depends on BAR", file),
+                format!("{}:25:20: symbol BAR depends on LOOP_A", file),
+            ],
+            // The menu's condition is inherited by its entries
+            vec![
+                format!("{}:19:2: symbol ETH is selected by NET", file),
+                format!("{}:15:13: symbol NET depends on ETH", file),
+            ],
+        ]
+    );
+
+    let acyclic = Parser::new(
+        "tests/fixtures/dependency/Kconfig",
+        "tests/fixtures/dependency",
+    )
+    .unwrap()
+    .parse()
+    .unwrap();
+    assert!(recursive_dependencies(&acyclic.entries).is_empty());
+}
+
+#[test]
+fn test_recursive_dependency_diagnostic() {
+    let ast = Parser::new("tests/fixtures/cycles/Kconfig", "tests/fixtures/cycles")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let diagnostics = recursive_dependencies(&ast.entries);
+    assert_eq!(diagnostics.len(), 4);
+
+    let first = &diagnostics[0];
+    assert!(first.is_error());
+    assert_eq!(first.code, DiagnosticCode::RecursiveDependency);
+    assert_eq!(
+        first.message,
+        "Recursive dependency detected: LOOP_A -> LOOP_B -> LOOP_A"
+    );
+    assert_eq!(first.span.line, 9);
+    assert!(first.render(None).ends_with(
+        "  = note: tests/fixtures/cycles/Kconfig:5:2: symbol LOOP_B is selected by LOOP_A\n"
+    ));
+}
+
+#[test]
+fn test_can_enable_reports_cycle() {
+    let resolver = cycles_resolver();
+    let mut symbols = SymbolTable::new();
+    resolver.populate(&mut symbols);
+
+    match resolver.can_enable("BAZ", &symbols) {
+        Ok(()) => {}
+        Err(e) => panic!("BAZ has no dependencies: {}", e),
+    }
+    match resolver.can_enable("BAR", &symbols) {
+        Err(DependencyError::CircularDependency { chain }) => {
+            assert_eq!(chain, ["BAR", "BAZ", "BAR"])
+        }
+        other => panic!("expected a circular dependency, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_select_loop_terminates() {
+    let resolver = cycles_resolver();
+    let mut symbols = SymbolTable::new();
+    resolver.populate(&mut symbols);
+
+    symbols.set_value("LOOP_A", "y".to_string());
+    assert_eq!(resolver.apply_selects("LOOP_A", &mut symbols), ["LOOP_B"]);
+    assert!(symbols.is_enabled("LOOP_B"));
+
+    let mut engine =
+        ConfigEngine::load("tests/fixtures/cycles/Kconfig", "tests/fixtures/cycles").unwrap();
+    engine.set_user_value("LOOP_B", "y");
+    assert_eq!(engine.value("LOOP_A").as_deref(), Some("y"));
+    engine.set_user_value("ETH", "y");
+    engine.set_user_value("NET", "y");
+    // NET selects ETH, which its menu depends on
+    engine.set_user_value("ETH", "n");
+    assert_eq!(engine.value("ETH").as_deref(), Some("y"));
+    assert_eq!(engine.value("NET").as_deref(), Some("y"));
+}
+
+#[test]
+fn test_long_select_ring() {
+    const COUNT: usize = 5000;
+    let temp = TempDir::new().unwrap();
+    let kconfig: String = (0..COUNT)
+        .map(|i| {
+            format!(
+                "config S{}\n\tbool \"S{}\"\n\tselect S{}\n\n",
+                i,
+                i,
+                (i + 1) % COUNT
+            )
+        })
+        .collect();
+    fs::write(temp.path().join("Kconfig"), kconfig).unwrap();
+
+    let ast = Parser::new(temp.path().join("Kconfig"), temp.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut resolver = DependencyResolver::new();
+    resolver.build_from_entries(&ast.entries);
+
+    let cycles = resolver.dependency_cycles();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].edges.len(), COUNT);
+    assert_eq!(cycles[0].edges[0].to, format!("S{}", COUNT - 1));
+
+    let mut symbols = SymbolTable::new();
+    resolver.populate(&mut symbols);
+    symbols.set_value("S0", "y".to_string());
+    assert_eq!(resolver.apply_selects("S0", &mut symbols).len(), COUNT - 1);
+    assert!(symbols.is_enabled(&format!("S{}", COUNT - 1)));
+}
+
+#[test]
+fn test_selects_merge_across_definitions() {
+    let temp = TempDir::new().unwrap();
+    fs::write(
+        temp.path().join("Kconfig"),
+        r#"config NET
+	bool "Networking"
+	select CRYPTO
+	imply HW_RANDOM
+
+config CRYPTO
+	bool
+
+config ZLIB
+	bool
+
+config HW_RANDOM
+	bool "Hardware RNG"
+
+config TPM
+	bool "TPM"
+
+config NET
+	bool
+	select ZLIB
+	imply TPM
+"#,
+    )
+    .unwrap();
+
+    let ast = Parser::new(temp.path().join("Kconfig"), temp.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut resolver = DependencyResolver::new();
+    resolver.build_from_entries(&ast.entries);
+
+    let selected: Vec<&str> = resolver
+        .selections("NET")
+        .iter()
+        .map(|s| s.symbol.as_str())
+        .collect();
+    assert_eq!(selected, vec!["CRYPTO", "ZLIB"]);
+    assert_eq!(resolver.selectors("ZLIB"), ["NET".to_string()]);
+    assert_eq!(resolver.impliers("TPM"), ["NET".to_string()]);
+
+    let mut engine = ConfigEngine::from_entries(&ast.entries);
+    engine.set_user_value("NET", "y");
+    for name in ["CRYPTO", "ZLIB", "HW_RANDOM", "TPM"] {
+        assert_eq!(
+            engine.value(name),
+            Some("y".to_string()),
+            "{} should follow NET",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_untyped_redefinition_keeps_type() {
+    let temp = TempDir::new().unwrap();
+    fs::write(
+        temp.path().join("Kconfig"),
+        r#"config FOO
+	bool "Foo"
+
+config NUM
+	int "Number"
+	default 5
+
+config NUM
+	default 7 if FOO
+"#,
+    )
+    .unwrap();
+
+    let ast = Parser::new(temp.path().join("Kconfig"), temp.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut engine = ConfigEngine::from_entries(&ast.entries);
+
+    assert_eq!(engine.resolver().symbol_type("NUM"), Some(&SymbolType::Int));
+    assert_eq!(engine.resolver().defined_at("NUM").len(), 2);
+    assert_eq!(engine.value("NUM"), Some("5".to_string()));
+    // The first definition's default comes first
+    engine.set_user_value("FOO", "y");
+    assert_eq!(engine.value("NUM"), Some("5".to_string()));
 }
@@ -0,0 +1,70 @@
+use rust_kbuild::kconfig::{render_diagnostics, DiagnosticCode, Parser, Severity};
+use std::path::PathBuf;
+
+fn parse_fixture() -> Parser {
+    let kconfig_path = PathBuf::from("tests/fixtures/diagnostics/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/diagnostics");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let err = parser.parse().unwrap_err();
+    assert!(matches!(
+        err,
+        rust_kbuild::KconfigError::Syntax { line: 7, .. }
+    ));
+    parser
+}
+
+#[test]
+fn test_parser_recovers_and_reports_every_error() {
+    let parser = parse_fixture();
+
+    let found: Vec<_> = parser
+        .diagnostics()
+        .iter()
+        .map(|d| (d.severity, d.code, d.span.line, d.span.column))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Severity::Warning, DiagnosticCode::PromptRedefined, 3, 2),
+            (Severity::Error, DiagnosticCode::ExpectedIdentifier, 7, 7),
+            (Severity::Error, DiagnosticCode::ExpectedExpression, 11, 34),
+            (Severity::Error, DiagnosticCode::InvalidCharacter, 16, 22),
+            (Severity::Error, DiagnosticCode::UnmatchedEnd, 20, 1),
+        ]
+    );
+    assert_eq!(DiagnosticCode::UnmatchedEnd.as_str(), "E0009");
+}
+
+#[test]
+fn test_diagnostics_render_with_snippet_and_caret() {
+    let parser = parse_fixture();
+
+    let rendered = render_diagnostics(parser.diagnostics());
+    let expected = "\
+error[E0002]: Expected identifier after 'config'
+ --> tests/fixtures/diagnostics/Kconfig:7:7
+  |
+7 | config
+  |       ^
+";
+    assert!(rendered.contains(expected), "{}", rendered);
+    assert!(
+        rendered.contains("20 | endif\n   | ^^^^^\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.ends_with("4 errors, 1 warning\n"), "{}", rendered);
+}
+
+#[test]
+fn test_warnings_do_not_fail_parsing() {
+    let kconfig_path = PathBuf::from("tests/fixtures/keywords/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/keywords");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    assert!(parser.parse().is_ok());
+
+    let codes: Vec<_> = parser.diagnostics().iter().map(|d| d.code).collect();
+    assert_eq!(codes, vec![DiagnosticCode::ObsoleteOption]);
+}
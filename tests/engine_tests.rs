@@ -0,0 +1,235 @@
+mod common;
+
+use common::load_fixture;
+use rust_kbuild::config::{DefconfigLoader, OldConfigLoader};
+use rust_kbuild::kconfig::Tristate;
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/engine/Kconfig";
+const SRCTREE: &str = "tests/fixtures/engine";
+
+#[test]
+fn test_defaults() {
+    let engine = load_fixture("engine");
+
+    assert_eq!(engine.value("NET"), Some("n".to_string()));
+    assert_eq!(engine.value("HAS_DMA"), Some("y".to_string()));
+    // `default HAS_DMA` follows the symbol's value
+    assert_eq!(engine.value("DMA_ENGINE"), Some("y".to_string()));
+    // Non-boolean symbols have no value while their dependencies are unmet
+    assert_eq!(engine.value("NET_BUFFERS"), None);
+}
+
+#[test]
+fn test_user_value_needs_visibility() {
+    let mut engine = load_fixture("engine");
+
+    // Invisible while NET is off, so the user value has no effect yet
+    engine.set_user_value("NET_DIAG", "y");
+    assert_eq!(engine.visibility("NET_DIAG"), Tristate::No);
+    assert_eq!(engine.value("NET_DIAG"), Some("n".to_string()));
+
+    // ... but it is kept and applies once NET is enabled
+    engine.set_user_value("NET", "y");
+    assert_eq!(engine.value("NET_DIAG"), Some("y".to_string()));
+    assert_eq!(engine.value("NET_BUFFERS"), Some("64".to_string()));
+
+    engine.set_user_value("NET", "n");
+    assert_eq!(engine.value("NET_DIAG"), Some("n".to_string()));
+    assert_eq!(engine.user_value("NET_DIAG"), Some(&"y".to_string()));
+}
+
+#[test]
+fn test_select_overrides_user_value() {
+    let mut engine = load_fixture("engine");
+
+    // CRC has no prompt: only select can enable it
+    engine.set_user_value("CRC", "y");
+    assert_eq!(engine.value("CRC"), Some("n".to_string()));
+
+    engine.set_user_value("FS", "y");
+    assert_eq!(engine.value("CRC"), Some("y".to_string()));
+
+    engine.set_user_value("FS", "n");
+    assert_eq!(engine.value("CRC"), Some("n".to_string()));
+}
+
+#[test]
+fn test_imply_is_a_weak_default() {
+    let mut engine = load_fixture("engine");
+
+    engine.set_user_value("DEBUG", "y");
+    assert_eq!(engine.value("DEBUG_INFO"), Some("y".to_string()));
+
+    // Unlike select, the user can still turn an implied symbol off
+    engine.set_user_value("DEBUG_INFO", "n");
+    assert_eq!(engine.value("DEBUG_INFO"), Some("n".to_string()));
+    assert_eq!(engine.value("DEBUG"), Some("y".to_string()));
+}
+
+#[test]
+fn test_commands_agree() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+    fs::write(&config_path, "NET=y\nDEBUG=y\n").unwrap();
+
+    let (from_defconfig, _) = DefconfigLoader::new(KCONFIG, SRCTREE)
+        .load(&config_path)
+        .unwrap();
+    let (from_oldconfig, _) = OldConfigLoader::new(KCONFIG, SRCTREE)
+        .load_and_merge(&config_path)
+        .unwrap();
+
+    for (name, symbol) in from_defconfig.all_symbols() {
+        assert_eq!(
+            from_oldconfig.get_value(name),
+            symbol.value,
+            "Mismatch for {}",
+            name
+        );
+    }
+    assert_eq!(
+        from_oldconfig.get_value("NET_BUFFERS"),
+        Some("64".to_string())
+    );
+    assert_eq!(
+        from_oldconfig.get_value("DEBUG_INFO"),
+        Some("y".to_string())
+    );
+}
+
+#[test]
+fn test_conditional_defaults_first_match_wins() {
+    let mut engine = load_fixture("defaults");
+
+    assert_eq!(engine.value("LOG_BUF_SIZE"), Some("128".to_string()));
+    assert_eq!(engine.value("LOG_COMPRESS"), Some("y".to_string()));
+    // No default applies, so the string stays unset
+    assert_eq!(engine.value("LOG_LEVEL"), None);
+    assert_eq!(engine.value("ALLOC_FAST"), Some("y".to_string()));
+
+    engine.set_user_value("SMALL", "y");
+    assert_eq!(engine.value("LOG_BUF_SIZE"), Some("16".to_string()));
+    assert_eq!(engine.value("LOG_COMPRESS"), Some("n".to_string()));
+    assert_eq!(engine.value("LOG_LEVEL"), Some("warn".to_string()));
+    assert_eq!(engine.value("ALLOC_TINY"), Some("y".to_string()));
+    assert_eq!(engine.value("ALLOC_FAST"), Some("n".to_string()));
+}
+
+#[test]
+fn test_conditional_prompt_visibility() {
+    let mut engine = load_fixture("prompts");
+
+    // Hidden prompts make the symbols non-user-settable
+    engine.set_user_value("TRACE_RAW", "y");
+    engine.set_user_value("SCHED_TUNING", "20");
+    assert_eq!(engine.visibility("TRACE_RAW"), Tristate::No);
+    assert!(engine
+        .resolver()
+        .is_prompt_hidden("TRACE_RAW", engine.symbols()));
+    assert_eq!(engine.value("TRACE_RAW"), Some("n".to_string()));
+    assert_eq!(engine.value("SCHED_TUNING"), Some("10".to_string()));
+
+    engine.set_user_value("EXPERT", "y");
+    assert_eq!(engine.visibility("TRACE_RAW"), Tristate::Yes);
+    assert_eq!(engine.value("TRACE_RAW"), Some("y".to_string()));
+    assert_eq!(engine.value("SCHED_TUNING"), Some("20".to_string()));
+}
+
+#[test]
+fn test_conditional_prompt_hidden_in_oldconfig() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+    fs::write(&config_path, "CONFIG_EXPERT=n\n").unwrap();
+
+    let loader = OldConfigLoader::new("tests/fixtures/prompts/Kconfig", "tests/fixtures/prompts");
+    let (symbols, changes) = loader.load_and_merge(&config_path).unwrap();
+
+    // Only STATS can be set by the user while EXPERT is off
+    assert_eq!(changes.new_symbols, vec!["STATS".to_string()]);
+    assert_eq!(symbols.get_value("SCHED_TUNING"), Some("10".to_string()));
+}
+
+#[test]
+fn test_dependencies_accumulate_and_inherit() {
+    let mut engine = load_fixture("inherit");
+
+    // `if ARM` applies to ARM_LPAE on an x86 configuration
+    engine.set_user_value("X86", "y");
+    engine.set_user_value("ARM_LPAE", "y");
+    assert_eq!(engine.value("ARM_LPAE"), Some("n".to_string()));
+    assert!(engine
+        .resolver()
+        .can_enable("ARM_LPAE", engine.symbols())
+        .is_err());
+
+    // Every `depends on` line must hold
+    engine.set_user_value("USB", "y");
+    engine.set_user_value("USB_PCI", "y");
+    assert_eq!(engine.value("USB_PCI"), Some("n".to_string()));
+
+    // `menu ... depends on PCI` gates its children
+    assert_eq!(engine.value("PCI_NIC"), Some("n".to_string()));
+    engine.set_user_value("PCI", "y");
+    assert_eq!(engine.value("USB_PCI"), Some("y".to_string()));
+    assert_eq!(engine.value("PCI_NIC"), Some("y".to_string()));
+
+    // Choice dependencies apply to the options
+    assert_eq!(engine.value("PLAT_DESKTOP"), Some("y".to_string()));
+    engine.set_user_value("X86", "n");
+    engine.set_user_value("ARM", "y");
+    assert_eq!(engine.value("PLAT_DESKTOP"), Some("n".to_string()));
+    assert_eq!(engine.value("PLAT_SERVER"), Some("n".to_string()));
+    assert_eq!(engine.value("ARM_LPAE"), Some("y".to_string()));
+}
+
+#[test]
+fn test_modules_symbol_gates_m() {
+    let mut engine = load_fixture("keywords");
+
+    // MODULES is off, so tristates behave like bools
+    assert!(!engine.resolver().modules_enabled(engine.symbols()));
+    assert_eq!(engine.value("ALWAYS_ON"), Some("y".to_string()));
+    assert_eq!(engine.value("DRIVER"), Some("y".to_string()));
+    assert_eq!(engine.value("DRIVER_HELPER"), Some("y".to_string()));
+
+    engine.set_user_value("MODULES", "y");
+    assert_eq!(engine.value("DRIVER"), Some("m".to_string()));
+    assert_eq!(engine.value("DRIVER_HELPER"), Some("m".to_string()));
+}
+
+#[test]
+fn test_optional_choice_and_transitional() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".config");
+    fs::write(&config_path, "CONFIG_OLD_NAME=y\n").unwrap();
+
+    let loader = OldConfigLoader::new("tests/fixtures/keywords/Kconfig", "tests/fixtures/keywords");
+    let (symbols, changes) = loader.load_and_merge(&config_path).unwrap();
+
+    // The old value carries over to the renamed option
+    assert_eq!(symbols.get_value("NEW_NAME"), Some("y".to_string()));
+    assert!(!changes.new_symbols.contains(&"OLD_NAME".to_string()));
+
+    // An optional choice selects nothing by default
+    assert_eq!(symbols.get_value("SCHED_FAIR"), Some("n".to_string()));
+    assert_eq!(symbols.get_value("SCHED_FIFO"), Some("n".to_string()));
+
+    rust_kbuild::config::ConfigWriter::write(&config_path, &symbols).unwrap();
+    let written = fs::read_to_string(&config_path).unwrap();
+    assert!(!written.contains("OLD_NAME"));
+    assert!(written.contains("NEW_NAME=y"));
+}
+
+#[test]
+fn test_option_env_default() {
+    std::env::set_var("RKCONF_TEST_BUILD_HOME", "/opt/build");
+    let engine = load_fixture("keywords");
+
+    assert_eq!(engine.value("BUILD_HOME"), Some("/opt/build".to_string()));
+    assert_eq!(
+        engine.value("DEFCONFIG_LIST"),
+        Some("arch/defconfig".to_string())
+    );
+}
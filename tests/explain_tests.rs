@@ -0,0 +1,132 @@
+mod common;
+
+use common::load_fixture;
+use rust_kbuild::config::ConfigEngine;
+use rust_kbuild::kconfig::{Tristate, ValueOrigin};
+use std::fs;
+use tempfile::TempDir;
+
+fn default_line(engine: &ConfigEngine, name: &str) -> Option<usize> {
+    match engine.origin(name) {
+        Some(ValueOrigin::Default { span }) => Some(span.line),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_origins_of_defaults() {
+    let engine = load_fixture("explain");
+
+    assert_eq!(default_line(&engine, "DEBUG"), Some(26));
+    assert_eq!(engine.origin("CRYPTO"), Some(&ValueOrigin::NoDefault));
+    assert_eq!(
+        engine.origin("NET_TLS"),
+        Some(&ValueOrigin::UnmetDependencies)
+    );
+    assert_eq!(
+        engine.origin("NET_PORT"),
+        Some(&ValueOrigin::UnmetDependencies)
+    );
+    assert_eq!(
+        engine.origin("SCHED_FAIR"),
+        Some(&ValueOrigin::ChoiceDefault)
+    );
+    assert_eq!(
+        engine.origin("SCHED_RT"),
+        Some(&ValueOrigin::ChoiceOther {
+            selected: "SCHED_FAIR".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_origins_follow_user_values() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+    fs::write(
+        &config,
+        "# Board\nCONFIG_NET=y\nCONFIG_NET_TLS=y\nCONFIG_SCHED_RT=y\n",
+    )
+    .unwrap();
+
+    let mut engine = load_fixture("explain");
+    engine.read_user_values(&config).unwrap();
+
+    assert_eq!(
+        engine.origin("NET"),
+        Some(&ValueOrigin::User { line: Some(2) })
+    );
+    assert_eq!(
+        engine.origin("SCHED_RT"),
+        Some(&ValueOrigin::User { line: Some(4) })
+    );
+    assert_eq!(
+        engine.origin("CRYPTO"),
+        Some(&ValueOrigin::Selected {
+            by: "NET_TLS".to_string()
+        })
+    );
+    assert_eq!(
+        engine.origin("HW_RANDOM"),
+        Some(&ValueOrigin::Implied {
+            by: "NET_TLS".to_string()
+        })
+    );
+    // The conditional default, not the last one
+    assert_eq!(engine.value("NET_PORT"), Some("443".to_string()));
+    assert_eq!(default_line(&engine, "NET_PORT"), Some(21));
+
+    // A value set interactively has no line
+    engine.set_user_value("DEBUG", "n");
+    assert_eq!(
+        engine.origin("DEBUG"),
+        Some(&ValueOrigin::User { line: None })
+    );
+}
+
+#[test]
+fn test_select_wins_over_user_value() {
+    let mut engine = load_fixture("explain");
+    engine.set_user_value("CRYPTO", "n");
+    engine.set_user_value("NET", "y");
+    engine.set_user_value("NET_TLS", "y");
+
+    assert_eq!(engine.value("CRYPTO"), Some("y".to_string()));
+    assert_eq!(
+        engine.origin("CRYPTO"),
+        Some(&ValueOrigin::Selected {
+            by: "NET_TLS".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_explain() {
+    let mut engine = load_fixture("explain");
+    engine.set_user_value("NET", "y");
+    engine.set_user_value("NET_TLS", "y");
+
+    let explanation = engine.explain("CRYPTO").unwrap();
+    assert_eq!(explanation.value, Some("y".to_string()));
+    assert_eq!(
+        explanation.selected_by,
+        vec![("NET_TLS".to_string(), Some("y".to_string()))]
+    );
+    assert_eq!(explanation.defined_at[0].line, 12);
+
+    let explanation = engine.explain("NET_TLS").unwrap();
+    assert_eq!(
+        explanation.depends_on.as_ref().map(|(_, value)| *value),
+        Some(Tristate::Yes)
+    );
+
+    let text = engine.explain("CRYPTO").unwrap().to_string();
+    assert!(
+        text.starts_with("CRYPTO=y: selected by NET_TLS\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("  selected by: NET_TLS=y\n"), "{}", text);
+
+    assert!(engine.explain("UNKNOWN").is_none());
+}
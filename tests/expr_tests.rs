@@ -0,0 +1,204 @@
+use rust_kbuild::config::ConfigEngine;
+use rust_kbuild::kconfig::{
+    evaluate_expr, Entry, Expr, Parser, Spanned, SymbolTable, SymbolType, Tristate,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn sym(name: &str) -> Box<Spanned<Expr>> {
+    Expr::Symbol(name.to_string()).into()
+}
+
+fn constant(value: &str) -> Box<Spanned<Expr>> {
+    Expr::Const(value.to_string()).into()
+}
+
+fn table() -> SymbolTable {
+    let mut symbols = SymbolTable::new();
+    for (name, symbol_type, value) in [
+        ("YES", SymbolType::Tristate, "y"),
+        ("MOD", SymbolType::Tristate, "m"),
+        ("NO", SymbolType::Bool, "n"),
+        ("COUNT", SymbolType::Int, "10"),
+        ("BASE", SymbolType::Hex, "0x100"),
+        ("NAME", SymbolType::String, "abc"),
+    ] {
+        symbols.add_symbol(name.to_string(), symbol_type);
+        symbols.set_value(name, value.to_string());
+    }
+    symbols
+}
+
+fn eval(expr: &Expr) -> Tristate {
+    evaluate_expr(expr, &table()).unwrap()
+}
+
+#[test]
+fn test_tristate_logic() {
+    assert_eq!(eval(&Expr::And(sym("YES"), sym("MOD"))), Tristate::Module);
+    assert_eq!(eval(&Expr::And(sym("MOD"), sym("NO"))), Tristate::No);
+    assert_eq!(eval(&Expr::Or(sym("MOD"), sym("NO"))), Tristate::Module);
+    assert_eq!(eval(&Expr::Or(sym("MOD"), sym("YES"))), Tristate::Yes);
+
+    // !m stays m
+    assert_eq!(eval(&Expr::Not(sym("MOD"))), Tristate::Module);
+    assert_eq!(eval(&Expr::Not(sym("YES"))), Tristate::No);
+    assert_eq!(eval(&Expr::Not(sym("NO"))), Tristate::Yes);
+}
+
+#[test]
+fn test_constant_symbols() {
+    assert_eq!(eval(&Expr::Symbol("y".to_string())), Tristate::Yes);
+    assert_eq!(eval(&Expr::Symbol("m".to_string())), Tristate::Module);
+    assert_eq!(eval(&Expr::Symbol("n".to_string())), Tristate::No);
+
+    // Non-boolean and undefined symbols are n in a boolean context
+    assert_eq!(eval(&Expr::Symbol("COUNT".to_string())), Tristate::No);
+    assert_eq!(eval(&Expr::Symbol("UNDEFINED".to_string())), Tristate::No);
+}
+
+#[test]
+fn test_expr_display() {
+    let expr = Expr::And(
+        Expr::Or(sym("A"), Expr::Not(sym("B")).into()).into(),
+        Expr::Not(Expr::And(sym("C"), Expr::Equal(sym("D"), constant("abc")).into()).into()).into(),
+    );
+    assert_eq!(expr.to_string(), "(A || !B) && !(C && D = \"abc\")");
+    assert_eq!(
+        Expr::Or(sym("A"), Expr::And(sym("B"), sym("C")).into()).to_string(),
+        "A || B && C"
+    );
+    assert_eq!(
+        Expr::GreaterEqual(sym("COUNT"), constant("16")).to_string(),
+        "COUNT >= 16"
+    );
+}
+
+#[test]
+fn test_typed_comparisons() {
+    // Numeric, not lexicographic: "10" > "9"
+    assert_eq!(
+        eval(&Expr::Greater(sym("COUNT"), constant("9"))),
+        Tristate::Yes
+    );
+    assert_eq!(eval(&Expr::Equal(sym("COUNT"), sym("10"))), Tristate::Yes);
+    assert_eq!(
+        eval(&Expr::Less(sym("BASE"), constant("0x1000"))),
+        Tristate::Yes
+    );
+    assert_eq!(
+        eval(&Expr::Equal(sym("BASE"), constant("256"))),
+        Tristate::Yes
+    );
+
+    // Tristate values order as n < m < y
+    assert_eq!(eval(&Expr::Less(sym("MOD"), sym("y"))), Tristate::Yes);
+    assert_eq!(eval(&Expr::Equal(sym("MOD"), sym("m"))), Tristate::Yes);
+    assert_eq!(
+        eval(&Expr::NotEqual(sym("NO"), constant("n"))),
+        Tristate::No
+    );
+
+    assert_eq!(
+        eval(&Expr::Equal(sym("NAME"), constant("abc"))),
+        Tristate::Yes
+    );
+    assert_eq!(
+        eval(&Expr::Less(sym("NAME"), constant("abd"))),
+        Tristate::Yes
+    );
+}
+
+#[test]
+fn test_dependency_on_module_caps_tristate() {
+    let temp_dir = TempDir::new().unwrap();
+    let kconfig = temp_dir.path().join("Kconfig");
+    fs::write(
+        &kconfig,
+        r#"
+config BASE
+	tristate "Base"
+
+config DRIVER
+	tristate "Driver"
+	depends on BASE
+	default y
+
+config FEATURE
+	bool "Feature"
+	depends on BASE
+
+config USER
+	tristate "User"
+	select HELPER
+
+config HELPER
+	tristate "Helper"
+"#,
+    )
+    .unwrap();
+
+    let mut engine = ConfigEngine::load(&kconfig, temp_dir.path()).unwrap();
+    engine.set_user_value("BASE", "m");
+
+    // The default y is capped at m by the dependency
+    assert_eq!(engine.value("DRIVER"), Some("m".to_string()));
+    assert_eq!(
+        engine.resolver().max_value("DRIVER", engine.symbols()),
+        Tristate::Module
+    );
+
+    engine.set_user_value("DRIVER", "y");
+    assert_eq!(engine.value("DRIVER"), Some("m".to_string()));
+
+    // A bool depending on m can still be y
+    engine.set_user_value("FEATURE", "y");
+    assert_eq!(engine.value("FEATURE"), Some("y".to_string()));
+
+    // Selecting from m forces the selected symbol to at least m
+    engine.set_user_value("USER", "m");
+    assert_eq!(engine.value("HELPER"), Some("m".to_string()));
+    assert_eq!(
+        engine.resolver().select_value("HELPER", engine.symbols()),
+        Tristate::Module
+    );
+}
+
+#[test]
+fn test_expr_display_round_trips_constants() {
+    let temp = TempDir::new().unwrap();
+    let values = ["42", "0x1F", "007", "+5", "-5", "0xzz", "0x", "abc"];
+    let kconfig: String = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let depends = Expr::Equal(sym("A"), constant(value));
+            format!("config C{}\n\tbool\n\tdepends on {}\n\n", i, depends)
+        })
+        .collect();
+    fs::write(temp.path().join("Kconfig"), &kconfig).unwrap();
+
+    // Only canonical numbers are written without quotes
+    assert!(kconfig.contains("depends on A = 42\n"));
+    assert!(kconfig.contains("depends on A = 0x1F\n"));
+    assert!(kconfig.contains("depends on A = \"007\"\n"));
+    assert!(kconfig.contains("depends on A = \"0xzz\"\n"));
+
+    let ast = Parser::new(temp.path().join("Kconfig"), temp.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let parsed: Vec<Expr> = ast
+        .entries
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Config(c) => c.properties.depends.as_deref().cloned(),
+            _ => None,
+        })
+        .collect();
+    let written: Vec<Expr> = values
+        .iter()
+        .map(|value| Expr::Equal(sym("A"), constant(value)))
+        .collect();
+    assert_eq!(parsed, written);
+}
@@ -0,0 +1,204 @@
+use rust_kbuild::cli::{format_files, FmtMode};
+use rust_kbuild::kconfig::{
+    format_source, Entry, KconfigFile, LineKind, Parser, SyntaxNode, SyntaxTree,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn copy_tree(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_tree(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+fn parse(dir: &Path) -> (KconfigFile, Vec<PathBuf>) {
+    let mut parser = Parser::new(dir.join("Kconfig"), dir).unwrap();
+    let ast = parser.parse().unwrap();
+    (ast, parser.files())
+}
+
+/// The AST without source locations, which formatting may change
+fn without_spans(ast: &KconfigFile) -> String {
+    let mut debug = format!("{:?}", ast.entries);
+    while let Some(start) = debug.find("Span { file:") {
+        let end = start + debug[start..].find('}').unwrap() + 1;
+        debug.replace_range(start..end, "Span");
+    }
+    debug
+}
+
+#[test]
+fn test_syntax_tree_round_trips() {
+    let inputs = [
+        fs::read_to_string("tests/fixtures/fmt/Kconfig").unwrap(),
+        fs::read_to_string("tests/fixtures/diagnostics/Kconfig").unwrap(),
+        "config A\r\n\tbool \"a\"\r\n\thelp\r\n\t  text\r\n".to_string(),
+        "menu \"unclosed\"\nconfig B\n  int   # no newline at the end".to_string(),
+        "endif\nconfig C\n\tstring \"unterminated\n".to_string(),
+    ];
+
+    for input in &inputs {
+        assert_eq!(SyntaxTree::parse(input, "Kconfig").to_string(), *input);
+    }
+}
+
+#[test]
+fn test_syntax_tree_structure() {
+    let source = fs::read_to_string("tests/fixtures/fmt/Kconfig").unwrap();
+    let tree = SyntaxTree::parse(&source, "Kconfig");
+
+    let headers: Vec<String> = tree
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            SyntaxNode::Entry { header, .. } | SyntaxNode::Block { header, .. } => {
+                Some(header.to_string().trim().to_string())
+            }
+            SyntaxNode::Line(_) => None,
+        })
+        .collect();
+    assert_eq!(
+        headers,
+        [
+            "mainmenu   \"Formatter test\"",
+            "config MODULES",
+            "config NET",
+            "config NET_NAME",
+            "menu   Drivers",
+            "choice",
+            "source \"$(ARCH)/Kconfig\"",
+        ]
+    );
+
+    // The comment between attributes stays with its entry, the blank
+    // lines after the help text belong to it as well
+    let net_name = tree
+        .nodes
+        .iter()
+        .find_map(|node| match node {
+            SyntaxNode::Entry { header, body } if header.to_string().contains("NET_NAME") => {
+                Some(body)
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(net_name[2].kind, LineKind::Comment);
+
+    let lines = tree.lines();
+    assert_eq!(lines[0].kind, LineKind::Blank);
+    assert_eq!(lines[6].kind, LineKind::Assignment);
+    assert_eq!(lines[13].kind, LineKind::Help);
+    assert!(lines[14..21]
+        .iter()
+        .all(|line| line.kind == LineKind::HelpText));
+    assert_eq!(lines[21].keyword(), Some("config"));
+}
+
+#[test]
+fn test_format_layout() {
+    let source = fs::read_to_string("tests/fixtures/fmt/Kconfig").unwrap();
+    let formatted = format_source(&source);
+
+    assert!(
+        formatted.starts_with("# Top-level comment\nmainmenu \"Formatter test\"\n\nARCH := x86\n")
+    );
+    assert!(formatted.contains(
+        "config MODULES\n\tbool \"Enable modules\" # trailing comment\n\toption modules\n"
+    ));
+    assert!(formatted.contains(concat!(
+        "config NET\n",
+        "\tbool \"Networking\"\n",
+        "\tdefault y\n",
+        "\tdepends on !MODULES || (ARCH_OK && y)\n",
+        "\thelp\n",
+        "\t  Networking support.\n",
+        "\n",
+        "\t    Indented example.\n",
+        "\t  Second paragraph\twith a tab.\n",
+        "\n",
+        "config NET_NAME\n",
+        "\tstring\n",
+        "\tprompt \"Name with \\\"quotes\\\"\"\n",
+    )));
+    assert!(formatted.contains("\t# comment between attributes\n"));
+    assert!(formatted.contains(
+        "menu \"Drivers\"\n\tvisible if NET\n\n\tdepends on NET\nif NET\n\nconfig ETH\n"
+    ));
+    assert!(formatted.contains(
+        "\ttristate \"Ethernet\" if NET\n\tdefault m if MODULES\n\tselect NET_CORE if NET = y\n"
+    ));
+    assert!(formatted.contains("endif # NET\n"));
+    assert!(formatted.ends_with("source \"$(ARCH)/Kconfig\"\n"));
+
+    assert_eq!(format_source(&formatted), formatted);
+}
+
+#[test]
+fn test_format_preserves_ast() {
+    for fixture in [
+        "tests/fixtures/fmt",
+        "examples/sample_project",
+        "tests/fixtures/keywords",
+        "tests/fixtures/spans",
+    ] {
+        let temp = TempDir::new().unwrap();
+        copy_tree(Path::new(fixture), temp.path());
+
+        let (before, files) = parse(temp.path());
+        format_files(&files, FmtMode::InPlace).unwrap();
+        let (after, _) = parse(temp.path());
+
+        assert_eq!(without_spans(&after), without_spans(&before), "{}", fixture);
+        assert!(
+            format_files(&files, FmtMode::Check).unwrap().is_empty(),
+            "{}",
+            fixture
+        );
+    }
+}
+
+#[test]
+fn test_help_text_is_normalized() {
+    let (ast, _) = parse(Path::new("tests/fixtures/fmt"));
+    let help = ast
+        .entries
+        .iter()
+        .find_map(|entry| match entry {
+            Entry::Config(config) if config.name == "NET" => config.properties.help.clone(),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        help,
+        "Networking support.\n\n  Indented example.\nSecond paragraph\twith a tab.\n"
+    );
+}
+
+#[test]
+fn test_check_reports_unformatted_files() {
+    let temp = TempDir::new().unwrap();
+    copy_tree(Path::new("tests/fixtures/fmt"), temp.path());
+    let kconfig = temp.path().join("Kconfig");
+    let arch = temp.path().join("x86/Kconfig");
+    let original = fs::read_to_string(&kconfig).unwrap();
+
+    let files = vec![kconfig.clone(), arch.clone()];
+    assert_eq!(format_files(&files, FmtMode::Check).unwrap(), files);
+    assert_eq!(fs::read_to_string(&kconfig).unwrap(), original);
+
+    assert_eq!(format_files(&files, FmtMode::InPlace).unwrap(), files);
+    assert_eq!(
+        fs::read_to_string(&arch).unwrap(),
+        "config ARCH_OK\n\tdef_bool y\n"
+    );
+    assert!(format_files(&files, FmtMode::Check).unwrap().is_empty());
+}
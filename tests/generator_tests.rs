@@ -0,0 +1,121 @@
+use rust_kbuild::config::{ConfigEngine, ConfigGenerator};
+use rust_kbuild::kconfig::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/generator/Kconfig";
+const SRCTREE: &str = "tests/fixtures/generator";
+
+#[test]
+fn test_generate_config_rs() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("config.rs");
+
+    let ast = Parser::new(KCONFIG, SRCTREE).unwrap().parse().unwrap();
+    let engine = ConfigEngine::from_entries(&ast.entries);
+    ConfigGenerator::generate_config_rs(&output, engine.symbols(), &ast.entries).unwrap();
+
+    let expected = r#"//! Automatically generated file; DO NOT EDIT.
+
+/// Enable loadable module support
+pub const MODULES: bool = true;
+
+/// 64-bit kernel
+///
+/// Build a 64-bit kernel.
+///
+/// Say Y on any recent machine.
+pub const X86_64: bool = true;
+
+/// Intel PRO/1000 support
+pub const E1000: bool = true;
+
+/// Debugging
+pub const DEBUG: bool = false;
+
+/// Clock offset
+pub const OFFSET: i64 = -5;
+
+/// Physical base address
+pub const PHYS_BASE: u64 = 0x80000000;
+
+/// Boot banner
+pub const BANNER: &str = "say \"hi\" \\o/";
+
+pub const FOO: bool = true;
+
+/// Upper-case foo
+pub const FOO_2: bool = false;
+
+/// Fair scheduler
+pub const SCHED_FAIR: bool = true;
+
+/// Real-time scheduler
+pub const SCHED_RT: bool = false;
+"#;
+    assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+}
+
+#[test]
+fn test_generate_config_rs_is_deterministic() {
+    let temp_dir = TempDir::new().unwrap();
+    let ast = Parser::new(KCONFIG, SRCTREE).unwrap().parse().unwrap();
+
+    let outputs: Vec<String> = (0..2)
+        .map(|i| {
+            let output = temp_dir.path().join(format!("config{}.rs", i));
+            let engine = ConfigEngine::from_entries(&ast.entries);
+            ConfigGenerator::generate_config_rs(&output, engine.symbols(), &ast.entries).unwrap();
+            fs::read_to_string(&output).unwrap()
+        })
+        .collect();
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn test_generate_autoconf_h_types_values() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("autoconf.h");
+
+    let ast = Parser::new(KCONFIG, SRCTREE).unwrap().parse().unwrap();
+    let engine = ConfigEngine::from_entries(&ast.entries);
+    ConfigGenerator::generate_autoconf_h_with_prefix(&output, engine.symbols(), "CONFIG_").unwrap();
+
+    let header = fs::read_to_string(&output).unwrap();
+    let defines: Vec<&str> = header
+        .lines()
+        .filter(|line| line.starts_with("#define"))
+        .collect();
+    for expected in [
+        "#define CONFIG_X86_64 1",
+        "#define CONFIG_E1000_MODULE 1",
+        "#define CONFIG_OFFSET -5",
+        "#define CONFIG_PHYS_BASE 0x80000000",
+        r#"#define CONFIG_BANNER "say \"hi\" \\o/""#,
+        "#define CONFIG_SCHED_FAIR 1",
+    ] {
+        assert!(
+            defines.contains(&expected),
+            "Missing {:?} in:\n{}",
+            expected,
+            header
+        );
+    }
+    assert!(!header.contains("CONFIG_E1000 "));
+    assert!(!header.contains("CONFIG_DEBUG"));
+    assert!(!header.contains("CONFIG_SCHED_RT"));
+}
+
+#[test]
+fn test_generate_kconfig_h() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("kconfig.h");
+    ConfigGenerator::generate_kconfig_h(&output).unwrap();
+
+    let header = fs::read_to_string(&output).unwrap();
+    assert!(header.contains("#define IS_BUILTIN(option) __is_defined(option)"));
+    assert!(header.contains("#define IS_MODULE(option) __is_defined(option##_MODULE)"));
+    assert!(
+        header.contains("#define IS_ENABLED(option) __or(IS_BUILTIN(option), IS_MODULE(option))")
+    );
+}
@@ -0,0 +1,149 @@
+use rust_kbuild::cli::to_json;
+use rust_kbuild::kconfig::{
+    lint, DiagnosticCode, LintConfig, LintLevel, LintRule, Parser, Severity,
+};
+
+fn lint_fixture(config: &LintConfig) -> Vec<(LintRule, usize, Severity)> {
+    let mut parser = Parser::new("tests/fixtures/lint/Kconfig", "tests/fixtures/lint").unwrap();
+    let ast = parser.parse().unwrap();
+    lint(&ast, config)
+        .into_iter()
+        .map(|d| match d.code {
+            DiagnosticCode::Lint(rule) => (rule, d.span.line, d.severity),
+            other => panic!("unexpected code {}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_lint_reports_each_rule() {
+    let findings: Vec<(LintRule, usize)> = lint_fixture(&LintConfig::new())
+        .into_iter()
+        .map(|(rule, line, _)| (rule, line))
+        .collect();
+
+    assert_eq!(
+        findings,
+        vec![
+            (LintRule::UndefinedSymbol, 8),
+            // PHYLIB depends on NET, which ETH depends on too
+            (LintRule::SelectUnmetDependency, 16),
+            (LintRule::DuplicateDefault, 18),
+            (LintRule::DuplicateDefault, 20),
+            (LintRule::MissingHelp, 33),
+            (LintRule::DefaultOutOfRange, 38),
+            (LintRule::ConflictingType, 43),
+            (LintRule::UnreachableSymbol, 45),
+            (LintRule::UnreachableSymbol, 51),
+        ]
+    );
+}
+
+#[test]
+fn test_lint_messages() {
+    let mut parser = Parser::new("tests/fixtures/lint/Kconfig", "tests/fixtures/lint").unwrap();
+    let ast = parser.parse().unwrap();
+    let messages: Vec<String> = lint(&ast, &LintConfig::new())
+        .into_iter()
+        .map(|d| d.message)
+        .collect();
+
+    assert!(messages.contains(&"Symbol MISSING_BUS is not defined".to_string()));
+    assert!(messages.contains(
+        &"ETH selects MDIO, which also depends on PCI; the select can force it on while that is unmet".to_string()
+    ));
+    assert!(messages.contains(
+        &"Default m of ETH repeats the default at tests/fixtures/lint/Kconfig:17:2".to_string()
+    ));
+    assert!(messages.contains(
+        &"Default n of ETH never applies: the default at tests/fixtures/lint/Kconfig:19:2 always does".to_string()
+    ));
+    assert!(messages.contains(&"Default 128 of BUFFERS is outside its range 1 to 64".to_string()));
+    assert!(messages
+        .iter()
+        .any(|m| m.starts_with("BUFFERS is declared hex here, but int at")));
+    assert!(messages.contains(&"DEAD can never be set: its dependencies are never met".to_string()));
+}
+
+#[test]
+fn test_lint_levels() {
+    let mut config = LintConfig::new();
+    assert_eq!(config.level(LintRule::ConflictingType), LintLevel::Deny);
+    assert_eq!(config.level(LintRule::MissingHelp), LintLevel::Warn);
+
+    config
+        .apply_rules(
+            "# CI settings\nmissing-help = allow\n\nL0001 = deny\nconflicting-type = warn\n",
+        )
+        .unwrap();
+    let findings = lint_fixture(&config);
+
+    assert!(findings
+        .iter()
+        .all(|(rule, _, _)| *rule != LintRule::MissingHelp));
+    assert!(findings.contains(&(LintRule::UndefinedSymbol, 8, Severity::Error)));
+    assert!(findings.contains(&(LintRule::ConflictingType, 43, Severity::Warning)));
+
+    assert!(config.apply_rules("no-such-rule = deny\n").is_err());
+    assert!(config.apply_rules("missing-help = sometimes\n").is_err());
+    assert_eq!(
+        "L0005".parse::<LintRule>().unwrap(),
+        LintRule::UnreachableSymbol
+    );
+}
+
+#[test]
+fn test_lint_json_output() {
+    let mut parser = Parser::new("tests/fixtures/lint/Kconfig", "tests/fixtures/lint").unwrap();
+    let ast = parser.parse().unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&to_json(&lint(&ast, &LintConfig::new()))).unwrap();
+
+    assert_eq!(json["errors"], 1);
+    assert_eq!(json["warnings"], 8);
+    let first = &json["diagnostics"][0];
+    assert_eq!(first["code"], "L0001");
+    assert_eq!(first["rule"], "undefined-symbol");
+    assert_eq!(first["severity"], "warning");
+    assert_eq!(first["line"], 8);
+    assert_eq!(first["column"], 20);
+    assert_eq!(first["file"], "tests/fixtures/lint/Kconfig");
+}
+
+#[test]
+fn test_lint_reads_hex_ranges_like_the_engine() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("Kconfig"),
+        r#"config BASE
+	hex "Base address"
+	range 1000 2000
+	default 0x1800
+	help
+	  Where the device is mapped.
+
+config LIMIT
+	hex "Limit"
+	range 0x1000 0x2000
+	default 0x3000
+	help
+	  The highest address.
+"#,
+    )
+    .unwrap();
+
+    let ast = Parser::new(temp.path().join("Kconfig"), temp.path())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let messages: Vec<String> = lint(&ast, &LintConfig::new())
+        .into_iter()
+        .map(|d| d.message)
+        .collect();
+
+    // A hex symbol reads `1000` as 0x1000
+    assert_eq!(
+        messages,
+        vec!["Default 0x3000 of LIMIT is outside its range 0x1000 to 0x2000".to_string()]
+    );
+}
@@ -0,0 +1,88 @@
+use rust_kbuild::config::output::write_if_changed;
+use rust_kbuild::config::{ConfigEngine, ConfigGenerator, ConfigWriter};
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/layout/Kconfig";
+const SRCTREE: &str = "tests/fixtures/layout";
+
+#[test]
+fn test_write_if_changed_leaves_identical_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("auto.conf");
+
+    assert!(write_if_changed(&path, b"NET=y\n").unwrap());
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+    assert!(!write_if_changed(&path, b"NET=y\n").unwrap());
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+
+    assert!(write_if_changed(&path, b"NET=m\n").unwrap());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "NET=m\n");
+
+    // The temporary file is renamed away
+    let names: Vec<_> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec!["auto.conf"]);
+}
+
+#[test]
+fn test_config_writer_keeps_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+    let backup = temp_dir.path().join(".config.old");
+    let mut engine = ConfigEngine::load(KCONFIG, SRCTREE).unwrap();
+
+    assert!(ConfigWriter::write(&config, engine.symbols()).unwrap());
+    assert!(!backup.exists());
+    let first = fs::read_to_string(&config).unwrap();
+
+    // Writing the same configuration again changes nothing
+    assert!(!ConfigWriter::write(&config, engine.symbols()).unwrap());
+    assert!(!backup.exists());
+
+    engine.set_user_value("NET", "n");
+    assert!(ConfigWriter::write(&config, engine.symbols()).unwrap());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), first);
+    assert!(fs::read_to_string(&config)
+        .unwrap()
+        .contains("# NET is not set"));
+}
+
+#[test]
+fn test_generators_report_unchanged_outputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let auto_conf = temp_dir.path().join("auto.conf");
+    let autoconf_h = temp_dir.path().join("autoconf.h");
+    let kconfig_h = temp_dir.path().join("kconfig.h");
+    let engine = ConfigEngine::load(KCONFIG, SRCTREE).unwrap();
+    let symbols = engine.symbols();
+
+    assert!(ConfigGenerator::generate_auto_conf(&auto_conf, symbols).unwrap());
+    assert!(ConfigGenerator::generate_autoconf_h(&autoconf_h, symbols).unwrap());
+    assert!(ConfigGenerator::generate_kconfig_h(&kconfig_h).unwrap());
+
+    assert!(!ConfigGenerator::generate_auto_conf(&auto_conf, symbols).unwrap());
+    assert!(!ConfigGenerator::generate_autoconf_h(&autoconf_h, symbols).unwrap());
+    assert!(!ConfigGenerator::generate_kconfig_h(&kconfig_h).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_if_changed_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("autoconf.h");
+    fs::write(&path, "old\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    assert!(write_if_changed(&path, b"new\n").unwrap());
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o640
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+}
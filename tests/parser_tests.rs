@@ -23,7 +23,7 @@ fn test_parse_source_directive() {
 
     assert!(result.is_ok());
     let ast = result.unwrap();
-    
+
     // Should have mainmenu, config, source, and entries from sourced file
     assert!(ast.entries.len() >= 2);
 }
@@ -41,7 +41,274 @@ fn test_parse_example_project() {
     }
     assert!(result.is_ok());
     let ast = result.unwrap();
-    
+
     // Should parse all entries including sourced files
-    assert!(ast.entries.len() > 0);
+    assert!(!ast.entries.is_empty());
+}
+
+#[test]
+fn test_parse_conditional_defaults() {
+    use rust_kbuild::kconfig::{Entry, Expr};
+
+    let kconfig_path = PathBuf::from("tests/fixtures/defaults/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/defaults");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let log_buf = ast
+        .entries
+        .iter()
+        .find_map(|e| match e {
+            Entry::Config(c) if c.name == "LOG_BUF_SIZE" => Some(c),
+            _ => None,
+        })
+        .unwrap();
+    let defaults: Vec<_> = log_buf
+        .properties
+        .defaults
+        .iter()
+        .map(|(value, condition, _)| (value.node.clone(), condition.as_deref().cloned()))
+        .collect();
+    assert_eq!(
+        defaults,
+        vec![
+            (
+                Expr::Const("16".to_string()),
+                Some(Expr::Symbol("SMALL".to_string()))
+            ),
+            (Expr::Const("128".to_string()), None),
+        ]
+    );
+
+    let choice = ast
+        .entries
+        .iter()
+        .find_map(|e| match e {
+            Entry::Choice(c) => Some(c),
+            _ => None,
+        })
+        .unwrap();
+    let defaults: Vec<_> = choice
+        .defaults
+        .iter()
+        .map(|(name, condition, _)| (name.clone(), condition.as_deref().cloned()))
+        .collect();
+    assert_eq!(
+        defaults,
+        vec![
+            (
+                "ALLOC_TINY".to_string(),
+                Some(Expr::Symbol("SMALL".to_string()))
+            ),
+            ("ALLOC_FAST".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_prompt_conditions() {
+    use rust_kbuild::kconfig::{Entry, Expr};
+
+    let kconfig_path = PathBuf::from("tests/fixtures/prompts/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/prompts");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let configs: Vec<_> = ast
+        .entries
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Config(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(configs.len(), 4);
+
+    let expert = Some(Expr::Symbol("EXPERT".to_string()).into());
+    // Both the type-with-prompt and the `prompt` forms keep the condition
+    assert_eq!(configs[1].properties.prompt.as_deref(), Some("Raw tracing"));
+    assert_eq!(configs[1].properties.prompt_condition, expert);
+    assert_eq!(
+        configs[2].properties.prompt.as_deref(),
+        Some("Scheduler tuning")
+    );
+    assert_eq!(configs[2].properties.prompt_condition, expert);
+    assert_eq!(configs[3].properties.prompt_condition, None);
+}
+
+#[test]
+fn test_parse_multiple_depends_are_anded() {
+    use rust_kbuild::kconfig::{Entry, Expr};
+
+    let kconfig_path = PathBuf::from("tests/fixtures/inherit/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/inherit");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let usb_pci = ast
+        .entries
+        .iter()
+        .find_map(|e| match e {
+            Entry::Config(c) if c.name == "USB_PCI" => Some(c),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        usb_pci.properties.depends,
+        Some(
+            Expr::And(
+                Expr::Symbol("USB".to_string()).into(),
+                Expr::Symbol("PCI".to_string()).into(),
+            )
+            .into()
+        )
+    );
+}
+
+#[test]
+fn test_parse_remaining_keywords() {
+    use rust_kbuild::kconfig::{Entry, Expr, SymbolType};
+
+    let kconfig_path = PathBuf::from("tests/fixtures/keywords/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/keywords");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let config = |name: &str| {
+        ast.entries
+            .iter()
+            .find_map(|e| match e {
+                Entry::Config(c) if c.name == name => Some(c.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    assert!(config("MODULES").properties.modules);
+
+    let always_on = config("ALWAYS_ON");
+    assert_eq!(always_on.symbol_type, SymbolType::Bool);
+    assert_eq!(always_on.properties.defaults.len(), 1);
+    let (value, condition, _) = &always_on.properties.defaults[0];
+    assert_eq!(value.node, Expr::Symbol("y".to_string()));
+    assert!(condition.is_none());
+
+    let helper = config("DRIVER_HELPER");
+    assert_eq!(helper.symbol_type, SymbolType::Tristate);
+    assert_eq!(helper.properties.defaults.len(), 1);
+    let (value, condition, _) = &helper.properties.defaults[0];
+    assert_eq!(value.node, Expr::Symbol("DRIVER".to_string()));
+    assert!(condition.is_none());
+
+    assert_eq!(
+        config("BUILD_HOME").properties.env.as_deref(),
+        Some("RKCONF_TEST_BUILD_HOME")
+    );
+    assert!(config("OLD_NAME").properties.transitional);
+
+    let choice = ast
+        .entries
+        .iter()
+        .find_map(|e| match e {
+            Entry::Choice(c) => Some(c),
+            _ => None,
+        })
+        .unwrap();
+    assert!(choice.optional);
+}
+
+#[test]
+fn test_parse_spans() {
+    use rust_kbuild::kconfig::{Entry, Expr, PropertyKind};
+
+    let kconfig_path = PathBuf::from("tests/fixtures/spans/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/spans");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let lines = |span: &rust_kbuild::kconfig::Span| (span.line, span.column, span.end_line);
+
+    let main_menu = ast.entries.iter().find_map(|e| match e {
+        Entry::MainMenu(m) => Some(m),
+        _ => None,
+    });
+    assert_eq!(main_menu.map(|m| lines(&m.span)), Some((1, 1, 1)));
+
+    // A config ends with the last line of its help text
+    let Entry::Config(net) = &ast.entries[1] else {
+        panic!("expected NET")
+    };
+    assert_eq!(net.span.file, kconfig_path);
+    assert_eq!(lines(&net.span), (3, 1, 9));
+    let kinds: Vec<_> = net
+        .properties
+        .spans
+        .iter()
+        .map(|p| (p.kind, p.span.line))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (PropertyKind::Type, 4),
+            (PropertyKind::Default, 5),
+            (PropertyKind::Help, 6)
+        ]
+    );
+    assert_eq!(lines(&net.properties.spans[0].span), (4, 2, 4));
+    assert_eq!(net.properties.spans[2].span.end_line, 9);
+
+    let Entry::Menu(menu) = &ast.entries[2] else {
+        panic!("expected a menu")
+    };
+    assert_eq!(lines(&menu.span), (11, 1, 18));
+    assert_eq!(menu.spans[0].kind, PropertyKind::Depends);
+    assert_eq!(menu.spans[0].span.line, 12);
+
+    // Every expression node carries its own span
+    let Entry::Config(eth) = &menu.entries[0] else {
+        panic!("expected ETH")
+    };
+    assert_eq!(lines(&eth.span), (14, 1, 16));
+    assert_eq!(eth.properties.spans[1].kind, PropertyKind::Depends);
+    let depends = eth.properties.depends.as_ref().unwrap();
+    assert_eq!(lines(&depends.span), (16, 13, 16));
+    let Expr::And(net_operand, negated) = &depends.node else {
+        panic!("expected NET && !(SLIP || PPP)")
+    };
+    assert_eq!(net_operand.span.column, 13);
+    assert_eq!(negated.span.column, 20);
+    let Expr::Not(alternatives) = &negated.node else {
+        panic!("expected !(SLIP || PPP)")
+    };
+    assert_eq!(alternatives.span.column, 22);
+    let symbols: Vec<_> = depends
+        .symbols()
+        .into_iter()
+        .map(|(name, span)| (name, span.column))
+        .collect();
+    assert_eq!(symbols, vec![("NET", 13), ("SLIP", 22), ("PPP", 30)]);
+
+    let Entry::If(if_block) = &ast.entries[3] else {
+        panic!("expected an if block")
+    };
+    assert_eq!(lines(&if_block.span), (20, 1, 23));
+    assert_eq!(if_block.spans[0].kind, PropertyKind::Condition);
+    assert_eq!(if_block.condition.node, Expr::Symbol("NET".to_string()));
+    assert_eq!(lines(&if_block.condition.span), (20, 4, 20));
+
+    // Entries keep the file they were sourced from
+    let Entry::Config(extra) = &ast.entries[4] else {
+        panic!("expected the sourced ETH")
+    };
+    assert_eq!(extra.span.file, srctree.join("Kconfig.extra"));
+    assert_eq!(lines(&extra.span), (2, 1, 3));
+    let Entry::Source(source) = &ast.entries[5] else {
+        panic!("expected the source entry")
+    };
+    assert_eq!(lines(&source.span), (25, 1, 25));
 }
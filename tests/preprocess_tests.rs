@@ -0,0 +1,116 @@
+use rust_kbuild::kconfig::{Entry, Parser, Preprocessor};
+use rust_kbuild::KconfigError;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn preprocess(input: &str) -> String {
+    let mut preprocessor = Preprocessor::new();
+    preprocessor.process(input, Path::new("Kconfig")).unwrap()
+}
+
+#[test]
+fn test_simple_and_recursive_variables() {
+    let output =
+        preprocess("A := one\nB = $(A) two\nA := three\nconfig FOO\n\tbool \"$(A) $(B)\"\n");
+
+    // Assignments become empty lines so line numbers are preserved
+    assert_eq!(output, "\n\n\nconfig FOO\n\tbool \"three three two\"\n");
+}
+
+#[test]
+fn test_append_variable() {
+    let output = preprocess("FLAGS := -a\nFLAGS += -b\n$(FLAGS)\n");
+    assert_eq!(output, "\n\n-a -b\n");
+}
+
+#[test]
+fn test_builtin_functions() {
+    let output = preprocess(
+        "$(if,,yes,no)\n$(if,x,yes,no)\n$(shell,echo hello)\n$(success,true)\n$(failure,true)\n$(lineno)\n",
+    );
+    assert_eq!(output, "no\nyes\nhello\ny\nn\n6\n");
+}
+
+#[test]
+fn test_user_defined_function() {
+    let output = preprocess("greet = hello-$(1)-$(2)\n$(greet,a,b)\n");
+    assert_eq!(output, "\nhello-a-b\n");
+}
+
+#[test]
+fn test_environment_reference() {
+    std::env::set_var("RKCONF_TEST_ARCH", "riscv");
+
+    let mut preprocessor = Preprocessor::new();
+    let output = preprocessor
+        .process(
+            "source \"arch/$(RKCONF_TEST_ARCH)/Kconfig\"\n",
+            Path::new("Kconfig"),
+        )
+        .unwrap();
+
+    assert_eq!(output, "source \"arch/riscv/Kconfig\"\n");
+    assert!(preprocessor.env_vars().any(|v| v == "RKCONF_TEST_ARCH"));
+}
+
+#[test]
+fn test_help_and_comments_not_expanded() {
+    let input = "config FOO\n\tbool\n\thelp\n\t  Uses $(shell,echo x)\n\n# $(shell,echo y)\n";
+    assert_eq!(preprocess(input), input);
+}
+
+#[test]
+fn test_recursive_variable_error() {
+    let mut preprocessor = Preprocessor::new();
+    let result = preprocessor.process("A = $(A)\n\nconfig $(A)\n", Path::new("Kconfig"));
+
+    match result {
+        Err(KconfigError::Macro { file, line, .. }) => {
+            assert_eq!(file, Path::new("Kconfig"));
+            assert_eq!(line, 3);
+        }
+        other => panic!("Expected macro error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_error_if_reports_location() {
+    let mut preprocessor = Preprocessor::new();
+    let result = preprocessor.process("\n$(error-if,y,unsupported)\n", Path::new("Kconfig"));
+
+    let err = result.unwrap_err();
+    assert_eq!(err.to_string(), "Macro error at Kconfig:2: unsupported");
+}
+
+#[test]
+fn test_parser_expands_macros_across_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    let sub_dir = temp_dir.path().join("arch").join("x86");
+    fs::create_dir_all(&sub_dir).unwrap();
+
+    fs::write(
+        temp_dir.path().join("Kconfig"),
+        "SRCARCH := x86\ncc-name := $(shell,echo gcc)\n\nsource \"arch/$(SRCARCH)/Kconfig\"\n",
+    )
+    .unwrap();
+    fs::write(
+        sub_dir.join("Kconfig"),
+        "config ARCH_$(SRCARCH)\n\tbool \"Built with $(cc-name)\"\n",
+    )
+    .unwrap();
+
+    let mut parser = Parser::new(temp_dir.path().join("Kconfig"), temp_dir.path()).unwrap();
+    let ast = parser.parse().unwrap();
+
+    let config = ast
+        .entries
+        .iter()
+        .find_map(|entry| match entry {
+            Entry::Config(config) => Some(config),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(config.name, "ARCH_x86");
+    assert_eq!(config.properties.prompt.as_deref(), Some("Built with gcc"));
+}
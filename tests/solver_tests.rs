@@ -0,0 +1,141 @@
+mod common;
+
+use common::load_fixture;
+
+fn assignments(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_solve_chain_of_dependencies() {
+    let mut engine = load_fixture("solver");
+    let solution = engine.solve("E1000", "y").unwrap();
+
+    assert_eq!(
+        solution.assignments,
+        assignments(&[("NET", "y"), ("NET_DRIVERS", "y"), ("PCI", "y")])
+    );
+    assert!(solution.only_enables());
+
+    solution.apply(&mut engine);
+    assert_eq!(engine.value("E1000"), Some("y".to_string()));
+}
+
+#[test]
+fn test_solve_takes_cheaper_alternative() {
+    let engine = load_fixture("solver");
+    let solution = engine.solve("TLS_OFFLOAD", "y").unwrap();
+
+    // SSL alone is cheaper than NET and NET_TLS
+    assert_eq!(
+        solution.assignments,
+        assignments(&[("SSL", "y"), ("DEBUG", "n")])
+    );
+    assert!(!solution.only_enables());
+}
+
+#[test]
+fn test_solve_through_select() {
+    let engine = load_fixture("solver");
+    let solution = engine.solve("HW_CRYPTO", "y").unwrap();
+
+    assert_eq!(solution.assignments, assignments(&[("CRYPTO_USER", "y")]));
+    assert_eq!(
+        solution.side_effects,
+        vec![(
+            "CRYPTO".to_string(),
+            Some("n".to_string()),
+            Some("y".to_string())
+        )]
+    );
+}
+
+#[test]
+fn test_solve_picks_choice_option() {
+    let engine = load_fixture("solver");
+    let solution = engine.solve("RT_THROTTLE", "y").unwrap();
+
+    assert_eq!(solution.assignments, assignments(&[("SCHED_RT", "y")]));
+    assert_eq!(
+        solution.side_effects,
+        vec![(
+            "SCHED_FAIR".to_string(),
+            Some("y".to_string()),
+            Some("n".to_string())
+        )]
+    );
+}
+
+#[test]
+fn test_solve_replaces_picked_choice_option() {
+    let mut engine = load_fixture("solver");
+    engine.set_user_value("SCHED_FAIR", "y");
+    let solution = engine.solve("RT_THROTTLE", "y").unwrap();
+
+    // Picking SCHED_RT replaces the user's pick
+    assert_eq!(solution.assignments, assignments(&[("SCHED_RT", "y")]));
+    solution.apply(&mut engine);
+    assert_eq!(engine.value("RT_THROTTLE"), Some("y".to_string()));
+    assert_eq!(engine.value("SCHED_FAIR"), Some("n".to_string()));
+    assert_eq!(engine.user_value("SCHED_FAIR"), None);
+}
+
+#[test]
+fn test_solve_disable_selected_symbol() {
+    let mut engine = load_fixture("solver");
+    engine.set_user_value("CRYPTO_USER", "y");
+
+    let solution = engine.solve("CRYPTO", "n").unwrap();
+    assert_eq!(solution.assignments, assignments(&[("CRYPTO_USER", "n")]));
+}
+
+#[test]
+fn test_solve_int_value() {
+    let engine = load_fixture("solver");
+    let solution = engine.solve("LOG_LEVEL", "5").unwrap();
+
+    assert_eq!(
+        solution.assignments,
+        assignments(&[("NET", "y"), ("NET_TLS", "y")])
+    );
+    // Out of range values cannot be reached
+    assert!(engine.solve("LOG_LEVEL", "9").is_none());
+}
+
+#[test]
+fn test_solve_nothing_to_do_or_impossible() {
+    let engine = load_fixture("solver");
+
+    let solution = engine.solve("DEBUG", "y").unwrap();
+    assert!(solution.assignments.is_empty());
+    assert_eq!(solution.to_string(), "DEBUG=y needs no other changes\n");
+
+    assert!(engine.solve("LOOP_A", "y").is_none());
+    assert!(engine.solve("UNKNOWN", "y").is_none());
+    assert!(engine.solve("LOG_LEVEL", "ten").is_none());
+}
+
+#[test]
+fn test_solution_display() {
+    let engine = load_fixture("solver");
+    let text = engine.solve("RT_THROTTLE", "y").unwrap().to_string();
+
+    assert_eq!(
+        text,
+        "To set RT_THROTTLE=y, also set:\n  SCHED_RT=y\nThis also changes:\n  SCHED_FAIR: y -> n\n"
+    );
+}
+
+#[test]
+fn test_solution_display_side_effects_only() {
+    let engine = load_fixture("solver");
+    let text = engine.solve("SCHED_FAIR", "n").unwrap().to_string();
+
+    assert_eq!(
+        text,
+        "SCHED_FAIR=n needs no other user values\nThis also changes:\n  SCHED_RT: n -> y\n"
+    );
+}
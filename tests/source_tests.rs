@@ -24,7 +24,10 @@ fn test_circular_source_detection() {
     // Should detect circular dependency
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert!(matches!(err, rust_kbuild::KconfigError::RecursiveSource { .. }));
+    assert!(matches!(
+        err,
+        rust_kbuild::KconfigError::RecursiveSource { .. }
+    ));
 }
 
 #[test]
@@ -38,7 +41,59 @@ fn test_nested_source() {
     // Should handle nested source directives
     assert!(result.is_ok());
     let ast = result.unwrap();
-    
+
     // Verify we got content from multiple files
     assert!(ast.entries.len() > 3);
 }
+
+fn config_names(entries: &[rust_kbuild::kconfig::Entry]) -> Vec<String> {
+    entries
+        .iter()
+        .filter_map(|e| match e {
+            rust_kbuild::kconfig::Entry::Config(c) => Some(c.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_source_variants_and_globs() {
+    let kconfig_path = PathBuf::from("tests/fixtures/source_variants/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/source_variants");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+
+    // Globs expand in sorted order and skip hidden directories; rsource
+    // resolves against the including file and already parsed files are
+    // not parsed again; osource/orsource tolerate missing files
+    assert_eq!(
+        config_names(&ast.entries),
+        vec!["ROOT", "PLUGIN_ALPHA", "PLUGIN_BETA", "BETA_EXTRA"]
+    );
+
+    let sources: Vec<_> = ast
+        .entries
+        .iter()
+        .filter_map(|e| match e {
+            rust_kbuild::kconfig::Entry::Source(s) => {
+                Some((s.path.clone(), s.optional, s.relative))
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(sources.contains(&(PathBuf::from("extra/Kconfig"), false, true)));
+    assert!(sources.contains(&(PathBuf::from("missing/Kconfig"), true, false)));
+    assert!(sources.contains(&(PathBuf::from("optional/*/Kconfig"), true, true)));
+}
+
+#[test]
+fn test_source_glob_without_matches() {
+    let kconfig_path = PathBuf::from("tests/fixtures/source_variants/Kconfig.missing");
+    let srctree = PathBuf::from("tests/fixtures/source_variants");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let err = parser.parse().unwrap_err();
+
+    assert!(matches!(err, rust_kbuild::KconfigError::FileNotFound(_)));
+}
@@ -9,25 +9,28 @@ fn test_menuconfig_app_initialization_with_values() {
     // Setup test Kconfig
     let kconfig_path = PathBuf::from("examples/sample_project/Kconfig");
     let srctree = PathBuf::from("examples/sample_project");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     // Create symbol table with some values
     let mut symbol_table = SymbolTable::new();
     symbol_table.add_symbol("HAVE_ARCH".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("DEBUG".to_string(), SymbolType::Bool);
     symbol_table.add_symbol("VERBOSE".to_string(), SymbolType::Bool);
-    
+
     symbol_table.set_value("HAVE_ARCH", "y".to_string());
     symbol_table.set_value("DEBUG", "y".to_string());
     symbol_table.set_value("VERBOSE", "n".to_string());
-    
+
     // Create MenuConfigApp - this should initialize values in both all_items AND menu_tree
     let app = MenuConfigApp::new(ast.entries, symbol_table);
-    
+
     // The app should be created successfully
-    assert!(app.is_ok(), "MenuConfigApp should be created successfully with initialized values");
+    assert!(
+        app.is_ok(),
+        "MenuConfigApp should be created successfully with initialized values"
+    );
 }
 
 /// Test that MenuConfigApp can be created without pre-existing config values
@@ -36,56 +39,128 @@ fn test_menuconfig_app_initialization_with_defaults() {
     // Setup test Kconfig
     let kconfig_path = PathBuf::from("examples/sample_project/Kconfig");
     let srctree = PathBuf::from("examples/sample_project");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     // Create empty symbol table
     let symbol_table = SymbolTable::new();
-    
+
     // Create MenuConfigApp - this should initialize with default values
     let app = MenuConfigApp::new(ast.entries, symbol_table);
-    
+
     // The app should be created successfully with defaults
-    assert!(app.is_ok(), "MenuConfigApp should be created successfully with default values");
+    assert!(
+        app.is_ok(),
+        "MenuConfigApp should be created successfully with default values"
+    );
 }
 
 /// Integration test: Load a config file and verify app initializes correctly
 #[test]
 fn test_menuconfig_app_with_config_file() {
     use rust_kbuild::config::ConfigReader;
-    
+
     // Create a temporary config file
     let config_content = r#"
 CONFIG_HAVE_ARCH=y
 CONFIG_DEBUG=y
 CONFIG_VERBOSE=n
 "#;
-    
+
     let temp_dir = std::env::temp_dir();
     let config_path = temp_dir.join("test_config_menuconfig");
     std::fs::write(&config_path, config_content).unwrap();
-    
+
     // Parse Kconfig
     let kconfig_path = PathBuf::from("examples/sample_project/Kconfig");
     let srctree = PathBuf::from("examples/sample_project");
-    
+
     let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
     let ast = parser.parse().unwrap();
-    
+
     // Load config file and build symbol table
     let config_values = ConfigReader::read(&config_path).unwrap();
     let mut symbol_table = SymbolTable::new();
     for (name, value) in config_values {
         symbol_table.set_value(&name, value);
     }
-    
+
     // Create MenuConfigApp with loaded config
     let app = MenuConfigApp::new(ast.entries, symbol_table);
-    
+
     // The app should be created successfully
-    assert!(app.is_ok(), "MenuConfigApp should be created successfully with config file");
-    
+    assert!(
+        app.is_ok(),
+        "MenuConfigApp should be created successfully with config file"
+    );
+
     // Cleanup
     std::fs::remove_file(config_path).ok();
 }
+
+/// Symbols whose prompt condition is not met are hidden from the menu
+#[test]
+fn test_conditional_prompts_hidden_in_menu() {
+    use rust_kbuild::config::ConfigEngine;
+    use rust_kbuild::ui::state::ConfigState;
+
+    let kconfig_path = PathBuf::from("tests/fixtures/prompts/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/prompts");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+    let engine = ConfigEngine::from_entries(&ast.entries);
+
+    let mut state = ConfigState::build_from_entries(&ast.entries);
+    for items in state.menu_tree.values_mut() {
+        for item in items {
+            item.is_visible = !engine
+                .resolver()
+                .is_prompt_hidden(&item.id, engine.symbols());
+        }
+    }
+
+    let ids: Vec<String> = state
+        .get_items_for_path(&[])
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    assert_eq!(ids, vec!["EXPERT".to_string(), "STATS".to_string()]);
+}
+
+/// Editing an int symbol whose dependencies are not met offers the
+/// solver's fix
+#[test]
+fn test_edit_blocked_int_offers_fix() {
+    use crossterm::event::{KeyCode, KeyEvent};
+    use rust_kbuild::ui::app::DialogType;
+
+    let kconfig_path = PathBuf::from("tests/fixtures/solver/Kconfig");
+    let srctree = PathBuf::from("tests/fixtures/solver");
+
+    let mut parser = Parser::new(&kconfig_path, &srctree).unwrap();
+    let ast = parser.parse().unwrap();
+    let mut app = MenuConfigApp::new(ast.entries, SymbolTable::new()).unwrap();
+
+    // LOG_LEVEL comes right before LOOP_A and LOOP_B, the last items
+    for code in [KeyCode::End, KeyCode::Up, KeyCode::Up, KeyCode::Enter] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    let Some(DialogType::DependencyError { fix: Some(fix), .. }) = app.dialog() else {
+        panic!(
+            "expected a dependency error with a fix, got {:?}",
+            app.dialog()
+        );
+    };
+    assert_eq!(fix.symbol, "LOG_LEVEL");
+    assert_eq!(fix.value, "3");
+    assert_eq!(
+        fix.assignments,
+        vec![
+            ("NET".to_string(), "y".to_string()),
+            ("NET_TLS".to_string(), "y".to_string()),
+        ]
+    );
+}
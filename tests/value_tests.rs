@@ -0,0 +1,169 @@
+mod common;
+
+use common::load_fixture;
+use rust_kbuild::config::{ConfigReader, ConfigWriter, DefconfigLoader, OldConfigLoader};
+use rust_kbuild::kconfig::{
+    normalize_value, quote_string, unquote_string, SymbolTable, SymbolType,
+};
+use std::fs;
+use tempfile::TempDir;
+
+const KCONFIG: &str = "tests/fixtures/ranges/Kconfig";
+const SRCTREE: &str = "tests/fixtures/ranges";
+
+#[test]
+fn test_normalize_value() {
+    assert_eq!(
+        normalize_value(&SymbolType::Int, "-42"),
+        Ok("-42".to_string())
+    );
+    assert_eq!(normalize_value(&SymbolType::Int, "+7"), Ok("7".to_string()));
+    assert!(normalize_value(&SymbolType::Int, "0x10").is_err());
+    assert!(normalize_value(&SymbolType::Int, "").is_err());
+
+    assert_eq!(
+        normalize_value(&SymbolType::Hex, "ff"),
+        Ok("0xff".to_string())
+    );
+    assert_eq!(
+        normalize_value(&SymbolType::Hex, "0X1F"),
+        Ok("0x1F".to_string())
+    );
+    assert!(normalize_value(&SymbolType::Hex, "0xg").is_err());
+
+    assert_eq!(
+        normalize_value(&SymbolType::Tristate, "m"),
+        Ok("m".to_string())
+    );
+    assert!(normalize_value(&SymbolType::Bool, "m").is_err());
+}
+
+#[test]
+fn test_string_quoting_roundtrip() {
+    let value = r#"say "hi" \o/"#;
+    let quoted = quote_string(value);
+
+    assert_eq!(quoted, r#""say \"hi\" \\o/""#);
+    assert_eq!(unquote_string(&quoted), Some(value.to_string()));
+    // Text after the closing quote or a missing one is malformed
+    assert_eq!(unquote_string(r#""a" b"#), None);
+    assert_eq!(unquote_string(r#""a"#), None);
+}
+
+#[test]
+fn test_conditional_range_first_match_wins() {
+    let mut engine = load_fixture("ranges");
+    assert_eq!(
+        engine.resolver().active_range("MAX_CPUS", engine.symbols()),
+        Some((1, 64))
+    );
+
+    engine.set_user_value("SMALL", "y");
+    assert_eq!(
+        engine.resolver().active_range("MAX_CPUS", engine.symbols()),
+        Some((1, 4))
+    );
+    // The default no longer fits and is clamped
+    assert_eq!(engine.value("MAX_CPUS"), Some("4".to_string()));
+}
+
+#[test]
+fn test_range_bound_from_symbol() {
+    let mut engine = load_fixture("ranges");
+    engine.set_user_value("MIN_BUFFERS", "10");
+    engine.set_user_value("BUFFERS", "3");
+
+    assert_eq!(
+        engine.resolver().active_range("BUFFERS", engine.symbols()),
+        Some((10, 128))
+    );
+    assert_eq!(engine.value("BUFFERS"), Some("10".to_string()));
+}
+
+#[test]
+fn test_invalid_user_value_is_ignored() {
+    let mut engine = load_fixture("ranges");
+    engine.set_user_value("BUFFERS", "ten");
+    engine.set_user_value("PHYS_OFFSET", "3000");
+
+    assert_eq!(engine.value("BUFFERS"), Some("16".to_string()));
+    assert_eq!(engine.value("PHYS_OFFSET"), Some("0x3000".to_string()));
+    assert_eq!(
+        engine.check_value("BUFFERS", "ten"),
+        Some("invalid value, expected a decimal number".to_string())
+    );
+    assert_eq!(
+        engine.check_value("PHYS_OFFSET", "0x20000"),
+        Some("outside range 0x1000 to 0xffff, clamped to 0xffff".to_string())
+    );
+    assert_eq!(engine.check_value("PHYS_OFFSET", "0x3000"), None);
+}
+
+#[test]
+fn test_defconfig_warnings_name_the_line() {
+    let loader = DefconfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, warnings) = loader
+        .load("tests/fixtures/ranges/board_defconfig")
+        .unwrap();
+
+    assert_eq!(symbols.get_value("MAX_CPUS"), Some("64".to_string()));
+    assert_eq!(symbols.get_value("BUFFERS"), Some("16".to_string()));
+    assert_eq!(symbols.get_value("PHYS_OFFSET"), Some("0x3000".to_string()));
+    assert_eq!(
+        symbols.get_value("BANNER"),
+        Some(r#"say "hi" \o/"#.to_string())
+    );
+
+    let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(warnings.len(), 2, "Unexpected warnings: {:?}", messages);
+    assert_eq!(warnings[0].line, Some(1));
+    assert!(messages[0].starts_with("line 1: "), "{}", messages[0]);
+    assert!(
+        messages[0].contains("outside range 1 to 64, clamped to 64"),
+        "{}",
+        messages[0]
+    );
+    assert_eq!(warnings[1].line, Some(2));
+    assert!(
+        messages[1].contains("expected a decimal number"),
+        "{}",
+        messages[1]
+    );
+}
+
+#[test]
+fn test_oldconfig_reports_values_not_kept() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+    fs::write(
+        &config,
+        "CONFIG_SMALL=y\nCONFIG_MAX_CPUS=8\nCONFIG_BUFFERS=32\n",
+    )
+    .unwrap();
+
+    let loader = OldConfigLoader::new(KCONFIG, SRCTREE);
+    let (symbols, changes) = loader.load_and_merge(&config).unwrap();
+
+    assert_eq!(symbols.get_value("MAX_CPUS"), Some("4".to_string()));
+    assert_eq!(symbols.get_value("BUFFERS"), Some("32".to_string()));
+    assert_eq!(changes.warnings.len(), 1);
+    assert_eq!(changes.warnings[0].symbol, "MAX_CPUS");
+    assert_eq!(changes.warnings[0].line, Some(2));
+    assert!(changes.has_changes());
+}
+
+#[test]
+fn test_writer_escapes_strings() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join(".config");
+
+    let mut symbols = SymbolTable::new();
+    symbols.add_symbol("BANNER".to_string(), SymbolType::String);
+    symbols.set_value("BANNER", r#"C:\ "x""#.to_string());
+    ConfigWriter::write(&config, &symbols).unwrap();
+
+    let content = fs::read_to_string(&config).unwrap();
+    assert!(content.contains(r#"BANNER="C:\\ \"x\"""#), "{}", content);
+    let values = ConfigReader::read(&config).unwrap();
+    assert_eq!(values.get("BANNER"), Some(&r#"C:\ "x""#.to_string()));
+}